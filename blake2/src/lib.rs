@@ -0,0 +1,364 @@
+//! An implementation of the [BLAKE2][1] cryptographic hash algorithms.
+//!
+//! `Blake2b` works on 64-bit words and outputs up to 64 bytes, `Blake2s` on 32-bit words and
+//! outputs up to 32 bytes. Both have a native keyed mode, and their variable-output versions
+//! `VarBlake2b` and `VarBlake2s` bind the output size in the parameter block.
+//!
+//! # Usage
+//!
+//! ```rust
+//! # #[macro_use] extern crate hex_literal;
+//! # extern crate blake2;
+//! # fn main() {
+//! use blake2::{Blake2s, Digest};
+//!
+//! let result = Blake2s::digest(b"abc");
+//! assert_eq!(result[..], hex!("
+//!     508c5e8c327c14e2e1a72ba34eeb452f37458b209ed63a294d999b4c86675982
+//! ")[..]);
+//! # }
+//! ```
+//!
+//! The last block is compressed with a finalization flag, so a digest is not a chaining value
+//! that more blocks can be appended to.
+//!
+//! [1]: https://blake2.net
+#![no_std]
+#[macro_use]
+extern crate opaque_debug;
+#[macro_use]
+pub extern crate digest;
+#[cfg(feature = "std")]
+extern crate std;
+#[cfg(test)]
+#[macro_use]
+extern crate hex_literal;
+use digest::generic_array::typenum::{U128, U32, U64};
+use digest::generic_array::GenericArray;
+pub use digest::Digest;
+use digest::InvalidOutputSize;
+use digest::{BlockInput, FixedOutput, Input, Reset, VariableOutput};
+
+#[rustfmt::skip]
+const SIGMA: [[usize; 16]; 10] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+// the SHA-512 and SHA-256 initial values
+#[rustfmt::skip]
+const IV_B: [u64; 8] = [
+    0x6a09e667f3bcc908, 0xbb67ae8584caa73b, 0x3c6ef372fe94f82b, 0xa54ff53a5f1d36f1,
+    0x510e527fade682d1, 0x9b05688c2b3e6c1f, 0x1f83d9abfb41bd6b, 0x5be0cd19137e2179,
+];
+#[rustfmt::skip]
+const IV_S: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+macro_rules! blake2_impl {
+    (
+        $var:ident, $fixed:ident, $compress:ident, $word:ident, $block:expr, $max_out:expr,
+        $block_size:ident, $output_size:ident, $rounds:expr, $iv:expr, $rot:expr, $doc:expr
+    ) => {
+        /// The compression function: `t` counts the bytes hashed so far, including this block,
+        /// and `last` flags the final block
+        pub fn $compress(h: &mut [$word; 8], block: &[u8; $block], t: u128, last: bool) {
+            const BYTES: usize = $block / 16;
+            let mut m = [0 as $word; 16];
+            for (word, bytes) in m.iter_mut().zip(block.chunks(BYTES)) {
+                let mut le = [0u8; BYTES];
+                le.copy_from_slice(bytes);
+                *word = $word::from_le_bytes(le);
+            }
+
+            let mut v = [0 as $word; 16];
+            v[..8].copy_from_slice(h);
+            v[8..].copy_from_slice(&$iv);
+            v[12] ^= t as $word;
+            v[13] ^= (t >> (8 * BYTES)) as $word;
+            if last {
+                v[14] = !v[14];
+            }
+
+            let (r1, r2, r3, r4) = $rot;
+            let mut g = |a: usize, b: usize, c: usize, d: usize, x: $word, y: $word| {
+                v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+                v[d] = (v[d] ^ v[a]).rotate_right(r1);
+                v[c] = v[c].wrapping_add(v[d]);
+                v[b] = (v[b] ^ v[c]).rotate_right(r2);
+                v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+                v[d] = (v[d] ^ v[a]).rotate_right(r3);
+                v[c] = v[c].wrapping_add(v[d]);
+                v[b] = (v[b] ^ v[c]).rotate_right(r4);
+            };
+            for r in 0..$rounds {
+                let s = &SIGMA[r % 10];
+                g(0, 4, 8, 12, m[s[0]], m[s[1]]);
+                g(1, 5, 9, 13, m[s[2]], m[s[3]]);
+                g(2, 6, 10, 14, m[s[4]], m[s[5]]);
+                g(3, 7, 11, 15, m[s[6]], m[s[7]]);
+                g(0, 5, 10, 15, m[s[8]], m[s[9]]);
+                g(1, 6, 11, 12, m[s[10]], m[s[11]]);
+                g(2, 7, 8, 13, m[s[12]], m[s[13]]);
+                g(3, 4, 9, 14, m[s[14]], m[s[15]]);
+            }
+
+            for i in 0..8 {
+                h[i] ^= v[i] ^ v[i + 8];
+            }
+        }
+
+        #[doc = $doc]
+        #[doc = ", with the output size chosen at runtime"]
+        #[derive(Clone)]
+        pub struct $var {
+            h: [$word; 8],
+            t: u128,
+            // the last block is only compressed on finalization
+            buffer: [u8; $block],
+            buffer_len: usize,
+            output_size: usize,
+            key: [u8; $max_out],
+            key_len: usize,
+        }
+
+        impl $var {
+            /// Keyed hashing (BLAKE2's native MAC mode) with `output_size` bytes of output
+            pub fn new_keyed(key: &[u8], output_size: usize) -> $var {
+                if key.len() > $max_out {
+                    panic!("Invalid key, should be at most {} bytes", $max_out);
+                }
+                if output_size == 0 || output_size > $max_out {
+                    panic!("Invalid output size, should be 1 to {} bytes", $max_out);
+                }
+                let mut h = $iv;
+                h[0] ^= 0x0101_0000 ^ ((key.len() as $word) << 8) ^ output_size as $word;
+                let mut hasher = $var {
+                    h,
+                    t: 0,
+                    buffer: [0; $block],
+                    buffer_len: 0,
+                    output_size,
+                    key: [0; $max_out],
+                    key_len: key.len(),
+                };
+                hasher.key[..key.len()].copy_from_slice(key);
+                if !key.is_empty() {
+                    // the key, zero-padded, is the first block
+                    hasher.buffer[..key.len()].copy_from_slice(key);
+                    hasher.buffer_len = $block;
+                }
+                hasher
+            }
+
+            /// The current chaining value, i.e. the state before the (buffered) last block
+            pub fn state(&self) -> [$word; 8] {
+                self.h
+            }
+
+            fn update(&mut self, mut input: &[u8]) {
+                while !input.is_empty() {
+                    if self.buffer_len == $block {
+                        self.t += $block as u128;
+                        $compress(&mut self.h, &self.buffer, self.t, false);
+                        self.buffer_len = 0;
+                    }
+                    let n = ($block - self.buffer_len).min(input.len());
+                    self.buffer[self.buffer_len..self.buffer_len + n].copy_from_slice(&input[..n]);
+                    self.buffer_len += n;
+                    input = &input[n..];
+                }
+            }
+
+            fn finalize(&mut self) -> [u8; 8 * $block / 16] {
+                self.t += self.buffer_len as u128;
+                for byte in self.buffer[self.buffer_len..].iter_mut() {
+                    *byte = 0;
+                }
+                $compress(&mut self.h, &self.buffer, self.t, true);
+                let mut out = [0u8; 8 * $block / 16];
+                for (bytes, word) in out.chunks_mut($block / 16).zip(self.h.iter()) {
+                    bytes.copy_from_slice(&word.to_le_bytes());
+                }
+                out
+            }
+        }
+
+        impl VariableOutput for $var {
+            fn new(output_size: usize) -> Result<Self, InvalidOutputSize> {
+                if output_size == 0 || output_size > $max_out {
+                    return Err(InvalidOutputSize);
+                }
+                Ok($var::new_keyed(&[], output_size))
+            }
+
+            fn output_size(&self) -> usize {
+                self.output_size
+            }
+
+            fn variable_result<F: FnOnce(&[u8])>(mut self, f: F) {
+                let out = self.finalize();
+                f(&out[..self.output_size]);
+            }
+        }
+
+        impl Input for $var {
+            fn input<B: AsRef<[u8]>>(&mut self, input: B) {
+                self.update(input.as_ref());
+            }
+        }
+
+        impl Reset for $var {
+            fn reset(&mut self) {
+                let key = self.key;
+                *self = $var::new_keyed(&key[..self.key_len], self.output_size);
+            }
+        }
+
+        #[doc = $doc]
+        #[doc = ", with the full output size"]
+        #[derive(Clone)]
+        pub struct $fixed {
+            inner: $var,
+        }
+
+        impl $fixed {
+            /// Keyed hashing (BLAKE2's native MAC mode)
+            pub fn new_keyed(key: &[u8]) -> $fixed {
+                $fixed {
+                    inner: $var::new_keyed(key, $max_out),
+                }
+            }
+
+            /// The current chaining value, i.e. the state before the (buffered) last block
+            pub fn state(&self) -> [$word; 8] {
+                self.inner.state()
+            }
+        }
+
+        impl Default for $fixed {
+            fn default() -> Self {
+                $fixed::new_keyed(&[])
+            }
+        }
+
+        impl BlockInput for $fixed {
+            type BlockSize = $block_size;
+        }
+
+        impl Input for $fixed {
+            fn input<B: AsRef<[u8]>>(&mut self, input: B) {
+                self.inner.update(input.as_ref());
+            }
+        }
+
+        impl FixedOutput for $fixed {
+            type OutputSize = $output_size;
+
+            fn fixed_result(mut self) -> GenericArray<u8, Self::OutputSize> {
+                GenericArray::clone_from_slice(&self.inner.finalize())
+            }
+        }
+
+        impl Reset for $fixed {
+            fn reset(&mut self) {
+                self.inner.reset();
+            }
+        }
+
+        impl_opaque_debug!($var);
+        impl_opaque_debug!($fixed);
+        impl_write!($var);
+        impl_write!($fixed);
+    };
+}
+
+blake2_impl!(
+    VarBlake2b,
+    Blake2b,
+    compress_b,
+    u64,
+    128,
+    64,
+    U128,
+    U64,
+    12,
+    IV_B,
+    (32, 24, 16, 63),
+    "The BLAKE2b hasher"
+);
+blake2_impl!(
+    VarBlake2s,
+    Blake2s,
+    compress_s,
+    u32,
+    64,
+    32,
+    U64,
+    U32,
+    10,
+    IV_S,
+    (16, 12, 8, 7),
+    "The BLAKE2s hasher"
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blake2_reference_digests() {
+        assert_eq!(
+            Blake2b::digest(b"")[..],
+            hex!(
+                "786a02f742015903c6c6fd852552d272912f4740e15847618a86e217f71f5419
+                 d25e1031afee585313896444934eb04b903a685b1448b755d56f701afe9be2ce"
+            )[..]
+        );
+        assert_eq!(
+            Blake2b::digest(b"abc")[..],
+            hex!(
+                "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d1
+                 7d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923"
+            )[..]
+        );
+        // exactly one block, which must be kept for finalization
+        assert_eq!(
+            Blake2b::digest(&[b'x'; 128][..])[..],
+            hex!(
+                "082b91ea2e15d1556d2ceefdd5af5d64d31b4e01aff1959724578876293825b2
+                 36ee8079173a0a38160d7d6685d6bca0bfb62c177b3599b8727d9173e2115b91"
+            )[..]
+        );
+    }
+
+    #[test]
+    fn blake2_keyed() {
+        let mut mac = Blake2b::new_keyed(b"secret key");
+        Digest::input(&mut mac, &[b'a'; 300][..]);
+        assert_eq!(
+            mac.result()[..],
+            hex!(
+                "7cc6cd83ae0d775e6f4e8eb093766abf1e6bfc401bfcb11d0c471a933edcd478
+                 ff6e1539230cca723084b28149bca039ea7e261841c80c8516d973a9cd2acf38"
+            )[..]
+        );
+
+        let mut mac = VarBlake2s::new_keyed(b"secret key", 16);
+        mac.input(&[b'a'; 128][..]);
+        mac.reset();
+        mac.input(&[b'a'; 128][..]);
+        mac.variable_result(|tag| {
+            assert_eq!(tag, hex!("8a52ea4a1e018ff3034a4169e519753b"));
+        });
+    }
+}
@@ -1,4 +1,5 @@
 use prng::mt19937::{MT19937Rng, RngCore};
+use prng::seed_search::SeedSearch;
 use rand::{self, Rng};
 use std::thread;
 use std::time::{Duration, SystemTime};
@@ -32,18 +33,13 @@ fn delayed_rng() -> u32 {
 }
 
 fn crack_seed(output: u32) -> Option<u32> {
-    // the seed is within 2000 sec ago
-    let candidate = SystemTime::now()
-        .duration_since(SystemTime::UNIX_EPOCH)
-        .unwrap()
-        .as_secs() as u32
-        - 3000;
-
-    for i in 0..3000 {
-        let mut rng = MT19937Rng::new(candidate + i);
-        if rng.next_u32() == output {
-            return Some(candidate + i);
-        }
-    }
-    None
+    // the seed is within 3000 sec ago
+    SeedSearch::new(
+        |seed| MT19937Rng::new(seed as u32),
+        |rng| rng.next_u32() == output,
+    )
+    .first_match()
+    .time_window(SystemTime::now(), Duration::from_secs(3000), Duration::default())
+    .first()
+    .map(|seed| *seed as u32)
 }
@@ -1,7 +1,11 @@
-use challenges::chal24;
-use prng::mt19937::{MT19937Rng, RngCore};
+use prng::mt19937::MT19937Rng;
+use prng::seed_search::{self, SeedSearch};
+use prng::stream::StreamCipher;
+use prng::token::{self, TokenFormat};
 use std::time::SystemTime;
 
+const RESET_TOKEN: TokenFormat = TokenFormat::Hex(32);
+
 fn main() {
     println!("🔓 Challenge 24");
     recover_key();
@@ -16,54 +20,49 @@ fn recover_key() {
 
     // prepare plaintext with randomized prefix and encrypt it
     let pt = prepare_plaintext();
-    let ct = chal24::encrypt(key, &pt);
+    let ct = StreamCipher::new(MT19937Rng::new(key as u32)).apply(&pt);
 
     let partial_key_stream = xor::xor(&b"AAAAAAAAAAAAAA"[..], &ct[ct.len() - 14..]).unwrap();
-    for i in 0..(u16::max_value() as usize) + 1 {
-        let mut mt_candidate = MT19937Rng::new(i as u32);
-        let mut key_stream = vec![0 as u8; ct.len()];
-        mt_candidate.fill_bytes(&mut key_stream);
-        if key_stream.ends_with(&partial_key_stream) {
-            println!("💡 Found key: {:?}", i);
-            return;
-        }
+    let seeds = SeedSearch::new(
+        |seed| MT19937Rng::new(seed as u32),
+        |rng| key_stream_matches(rng, ct.len(), &partial_key_stream),
+    )
+    .first_match()
+    .range(0..=u16::max_value() as u64);
+    if let Some(key) = seeds.first() {
+        println!("💡 Found key: {:?}", key);
     }
 }
 
+fn key_stream_matches(rng: &mut MT19937Rng, len: usize, partial_key_stream: &[u8]) -> bool {
+    StreamCipher::new(rng)
+        .keystream(len)
+        .ends_with(partial_key_stream)
+}
+
 fn prepare_plaintext() -> Vec<u8> {
     let mut pt = vec![rand::random::<u8>(); rand::random::<u8>() as usize];
     pt.extend_from_slice(&b"AAAAAAAAAAAAAA"[..]);
     pt
 }
 
-fn gen_reset_token() -> Vec<u8> {
-    let seed = SystemTime::now()
-        .duration_since(SystemTime::UNIX_EPOCH)
-        .unwrap()
-        .as_secs() as u16;
-    let pt = prepare_plaintext();
-    chal24::encrypt(seed, &pt)
+fn gen_reset_token() -> String {
+    let seed = seed_search::unix_time(SystemTime::now());
+    RESET_TOKEN.generate(&mut MT19937Rng::new(seed as u32))
 }
 
-fn gen_invalid_reset_token() -> Vec<u8> {
-    chal24::encrypt(rand::random::<u16>(), &prepare_plaintext())
+fn gen_invalid_reset_token() -> String {
+    RESET_TOKEN.generate(&mut MT19937Rng::new(rand::random::<u32>()))
 }
 
-fn check_reset_token(token: &[u8]) -> bool {
+fn check_reset_token(token: &str) -> bool {
     // assumption: it hasn't elasped 1 min (60 sec) since the token generation
-    let now = SystemTime::now()
-        .duration_since(SystemTime::UNIX_EPOCH)
-        .unwrap()
-        .as_secs() as u16;
-
-    let partial_key_stream = xor::xor(&b"AAAAAAAAAAAAAA"[..], &token[token.len() - 14..]).unwrap();
-    for i in now - 60..now + 1 {
-        let mut mt_candidate = MT19937Rng::new(i as u32);
-        let mut key_stream = vec![0 as u8; token.len()];
-        mt_candidate.fill_bytes(&mut key_stream);
-        if key_stream.ends_with(&partial_key_stream) {
-            return true;
-        }
-    }
-    false
+    let now = seed_search::unix_time(SystemTime::now());
+    !token::find_seed(
+        RESET_TOKEN,
+        &[token],
+        |seed| MT19937Rng::new(seed as u32),
+        now - 60..=now,
+    )
+    .is_empty()
 }
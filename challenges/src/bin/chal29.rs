@@ -1,29 +1,20 @@
-use challenges::{chal28::*, chal29::*};
+use challenges::chal28::*;
+use cryptanalysis::length_extension::LengthExtension;
 
 fn main() {
     println!("🔓 Challenge 29");
     let mac = SecretPrefixMac::new();
-    let key_size = deduce_key_size(&mac).unwrap();
     let original_msg =
         "comment1=cooking%20MCs;userdata=foo;comment2=%20like%20a%20pound%20of%20bacon".as_bytes();
+    let tag = mac.sign(original_msg);
 
-    let padding = get_md_padding(&random_bytes(key_size + original_msg.len() as u32));
-    let extension = b";admin=true";
-    let mut h = mac.transparent_sign(&[original_msg, &padding].concat());
-    h.input(&extension[..]);
-    let forged_tag = h.result().to_vec();
+    // the key is at most 255 bytes long, the server tells which guess is right
+    let attack = LengthExtension::<Sha1>::new(&tag, b";admin=true");
+    let forged = attack
+        .forge_all(original_msg, 0..=255)
+        .into_iter()
+        .find(|(msg, forged_tag)| mac.verify(msg, forged_tag));
 
-    assert!(mac.verify(&[original_msg, &padding, extension].concat(), &forged_tag));
+    assert!(forged.is_some());
     println!("Successfully forged a tag via extension attack");
 }
-
-fn deduce_key_size(mac: &SecretPrefixMac) -> Option<u32> {
-    let ref_tag = mac.sign(&[]);
-    for key_size in 0..256 {
-        let padding = get_md_padding(&random_bytes(key_size));
-        if mac.raw_sign(&padding) == ref_tag {
-            return Some(key_size);
-        }
-    }
-    None
-}
@@ -1,7 +1,8 @@
 use cipher::{self, Mode};
 use dh::{mod_p::Dh, DH};
+use hmac::kdf;
 use num::bigint::BigUint;
-use sha1::{Digest, Sha1};
+use sha1::Sha1;
 
 fn main() {
     println!("🔓 Challenge 34");
@@ -70,18 +71,19 @@ fn mitm() {
     println!("MiTM Succeed!");
 }
 
+// AES key derived from the DH shared secret with HKDF-SHA1
+fn derive_key(session_key: &BigUint) -> Vec<u8> {
+    kdf::hkdf::<Sha1>(&[], &session_key.to_bytes_le(), b"chal34 aes-128-cbc key", 16)
+}
+
 fn encrypt(session_key: &BigUint, msg: &[u8]) -> Vec<u8> {
-    let mut h = Sha1::new();
-    h.input(session_key.to_bytes_le());
-    let enc_key = &h.result().to_vec()[..16];
+    let enc_key = derive_key(session_key);
 
     let cbc_cipher = cipher::new(Mode::CBC);
     cbc_cipher.encrypt(&enc_key, &msg)
 }
 fn decrypt(session_key: &BigUint, ct: &[u8]) -> Vec<u8> {
-    let mut h = Sha1::new();
-    h.input(session_key.to_bytes_le());
-    let enc_key = &h.result().to_vec()[..16];
+    let enc_key = derive_key(session_key);
 
     let cbc_cipher = cipher::new(Mode::CBC);
     cbc_cipher.decrypt(&enc_key, &ct)
@@ -48,19 +48,15 @@ impl SrpClient for ClientState {
         hasher.input([self.A.to_str_radix(16).as_bytes(), B.to_str_radix(16).as_bytes()].concat());
         let u = bytes_to_biguint(&hasher.result().to_vec());
 
-        // get x = Sha256(salt || password)
-        let mut hasher = Sha256::new();
-        hasher.input([salt.to_owned(), PASSWORD.as_bytes().to_vec()].concat());
-        let x = bytes_to_biguint(&hasher.result().to_vec());
+        // get x = PBKDF2(password, salt)
+        let x = derive_x(salt, PASSWORD.as_bytes());
 
         // derive shared session key
         let kgx = (self.k * &self.dh.exp(&x)) % &self.dh.p;
         let base = mod_sub(&B, &kgx, &self.dh.p);
         let S = base.modpow(&(&self.a + &u * &x), &self.dh.p);
 
-        let mut hasher = Sha256::new();
-        hasher.input(S.to_bytes_le());
-        self.K = hasher.result().to_vec();
+        self.K = derive_session_key(&S);
 
         // get hmac tag for verification of the ephermal shared key
         let mut hmac_sha256 = Hmac::<Sha256>::new_varkey(&self.K).expect("HMAC can take varkey");
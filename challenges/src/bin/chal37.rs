@@ -3,7 +3,7 @@ use challenges::{chal36, SrpClient};
 use dh::mod_p::Dh;
 use hmac::{Hmac, Mac};
 use num::{BigUint, Zero};
-use sha2::{Digest, Sha256};
+use sha2::Sha256;
 use std::thread;
 use std::time::Duration;
 
@@ -25,9 +25,7 @@ impl SrpClient for ClientState {
     }
     fn kex(&mut self, salt: &[u8], _B: &BigUint) -> Vec<u8> {
         let S = BigUint::zero();
-        let mut hasher = Sha256::new();
-        hasher.input(S.to_bytes_le());
-        let K = hasher.result().to_vec();
+        let K = chal36::derive_session_key(&S);
 
         let mut hmac_sha256 = Hmac::<Sha256>::new_varkey(&K).expect("HMAC can take varkey");
         hmac_sha256.input(&salt);
@@ -1,24 +1,15 @@
 #![allow(non_snake_case)]
 
-use challenges::{chal38, random_bytes};
+use challenges::{chal36, chal38, random_bytes};
+use cryptanalysis::password::{Cracker, Rule};
 use dh::{mod_p::Dh, DH};
 use hmac::{Hmac, Mac};
 use num::bigint::RandBigInt;
-use sha2::{Digest, Sha256};
+use sha2::Sha256;
 use std::thread;
 use std::time::Duration;
 
-const DICTIONARY: &[&[u8]] = &[
-    b"dad birthday",
-    b"mom birthday",
-    b"my birthday",
-    b"username",
-    b"password",
-    b"1234567890",
-    b"password_is_username",
-    b"username_is_password",
-    b"111111111",
-];
+const WORDLIST: &str = "challenges/data/chal38.txt";
 
 #[tokio::main]
 async fn main() {
@@ -71,25 +62,22 @@ fn mitm_dict_attack() -> Option<String> {
     let tag = client.kex(&salt, &B, &u);
 
     // Now Eve use dictionary attack on potential password candidates with the HMAC tag
-    for pwd in DICTIONARY {
-        let mut hasher = Sha256::new();
-        hasher.input([salt.clone(), pwd.to_vec()].concat());
-        let x = chal38::bytes_to_biguint(&hasher.result().to_vec());
+    let verifier = |pwd: &[u8]| {
+        let x = chal36::derive_x(&salt, pwd);
         let v = dh.exp(&x);
 
         let S = (A.clone() * v.modpow(&u, &dh.p)).modpow(&b, &dh.p);
-
-        let mut hasher = Sha256::new();
-        hasher.input(S.to_bytes_le());
-        let K = hasher.result().to_vec();
+        let K = chal36::derive_session_key(&S);
 
         let mut hmac_sha256 = Hmac::<Sha256>::new_varkey(&K).expect("HMAC can take varkey");
         hmac_sha256.input(&salt);
-
-        if hmac_sha256.verify(&tag).is_ok() {
-            return Some(String::from_utf8(pwd.to_vec()).unwrap());
-        }
-    }
-
-    None
+        hmac_sha256.verify(&tag).is_ok()
+    };
+    let report = Cracker::new(verifier)
+        .rules(Rule::defaults())
+        .wordlist(WORDLIST)
+        .expect("Failed to read the wordlist");
+    println!("{}", report);
+
+    report.password.map(|pwd| String::from_utf8(pwd).unwrap())
 }
@@ -0,0 +1,17 @@
+use cryptanalysis::birthday::{AesCompression, TruncatedHash};
+use cryptanalysis::multicollision::cascade_collision;
+
+fn main() {
+    println!("🔓 Challenge 52");
+    let f = TruncatedHash::new(AesCompression, 16);
+    let g = TruncatedHash::new(AesCompression, 32).with_iv(0x5eed);
+
+    let collision = cascade_collision(&f, &g, 0);
+    let (m1, m2) = &collision.messages;
+    assert_ne!(m1, m2);
+    assert_eq!((f.hash(m1), g.hash(m1)), (f.hash(m2), g.hash(m2)));
+    println!(
+        "Found a collision of f || g with {} calls to f and {} calls to g",
+        collision.f_calls, collision.g_calls
+    );
+}
@@ -12,20 +12,6 @@ impl SecretPrefixMac {
             key: random_bytes(rand::random::<u8>() as u32),
         }
     }
-
-    /// Signed MAC on msg without padding
-    pub fn raw_sign(&self, msg: &[u8]) -> Vec<u8> {
-        let mut h = Sha1::new();
-        h.input(&[self.key.clone(), msg.to_vec()].concat());
-        h.raw_result().to_vec()
-    }
-
-    /// Sign a MAC and return Sha1 (its internal states)
-    pub fn transparent_sign(&self, msg: &[u8]) -> Sha1 {
-        let mut h = Sha1::new();
-        h.input([self.key.clone(), msg.to_vec()].concat());
-        h
-    }
 }
 
 impl MAC for SecretPrefixMac {
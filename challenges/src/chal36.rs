@@ -2,7 +2,7 @@ use super::{random_bytes, SrpClient};
 use bytes::Buf;
 use dh::{mod_p::Dh, DH};
 use encoding::hex;
-use hmac::{Hmac, Mac};
+use hmac::{kdf, Hmac, Mac};
 use hyper::{Body, client::Client, Method, Request, Uri};
 use num::BigUint;
 use rocket::{self, get, post, routes};
@@ -48,9 +48,7 @@ impl ServerState {
         self.k = 3;
         self.salt = random_bytes(32); // 32 is arbitary, can be any number
 
-        let mut hasher = Sha256::new();
-        hasher.input([self.salt.clone(), PASSWORD.as_bytes().to_vec()].concat());
-        let x = bytes_to_biguint(&hasher.result().to_vec());
+        let x = derive_x(&self.salt, PASSWORD.as_bytes());
 
         self.database.insert(EMAIL.to_string(), self.dh.exp(&x));
     }
@@ -71,9 +69,7 @@ impl ServerState {
 
         let S = (A * v.modpow(&u, &self.dh.p)).modpow(&self.b, &self.dh.p);
 
-        let mut hasher = Sha256::new();
-        hasher.input(S.to_bytes_le());
-        self.K = hasher.result().to_vec();
+        self.K = derive_session_key(&S);
 
         (self.salt.clone(), B)
     }
@@ -146,6 +142,19 @@ pub fn bytes_to_biguint(b: &[u8]) -> BigUint {
     BigUint::from_bytes_le(&b)
 }
 
+/// PBKDF2 iterations for the password hash, kept low so that the demos stay snappy
+pub const PBKDF2_ROUNDS: u32 = 1000;
+
+/// x = PBKDF2-HMAC-SHA256(password, salt)
+pub fn derive_x(salt: &[u8], password: &[u8]) -> BigUint {
+    bytes_to_biguint(&kdf::pbkdf2::<Sha256>(password, salt, PBKDF2_ROUNDS, 32))
+}
+
+/// K = HKDF-SHA256(S)
+pub fn derive_session_key(S: &BigUint) -> Vec<u8> {
+    kdf::hkdf::<Sha256>(&[], &S.to_bytes_le(), b"srp session key", 32)
+}
+
 pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
 
 async fn query_init() -> Result<()> {
@@ -1,4 +1,7 @@
-use super::random_bytes;
+use super::{
+    chal36::{derive_session_key, derive_x},
+    random_bytes,
+};
 use bytes::Buf;
 use dh::{mod_p::Dh, DH};
 use encoding::hex;
@@ -14,7 +17,7 @@ use rocket::{
 use rocket_contrib::json::Json;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use sha2::{Digest, Sha256};
+use sha2::Sha256;
 use std::collections::HashMap;
 use std::sync::RwLock;
 
@@ -45,9 +48,7 @@ impl ServerState {
         self.dh = Dh::new();
         self.salt = random_bytes(32); // 32 is arbitary, can be any number
 
-        let mut hasher = Sha256::new();
-        hasher.input([self.salt.clone(), PASSWORD.as_bytes().to_vec()].concat());
-        let x = bytes_to_biguint(&hasher.result().to_vec());
+        let x = derive_x(&self.salt, PASSWORD.as_bytes());
 
         self.database.insert(EMAIL.to_string(), self.dh.exp(&x));
     }
@@ -66,9 +67,7 @@ impl ServerState {
 
         let S = (A * v.modpow(&u, &self.dh.p)).modpow(&self.b, &self.dh.p);
 
-        let mut hasher = Sha256::new();
-        hasher.input(S.to_bytes_le());
-        self.K = hasher.result().to_vec();
+        self.K = derive_session_key(&S);
 
         (self.salt.clone(), B, u)
     }
@@ -221,17 +220,13 @@ impl ClientState {
     }
     /// perform key exchange, return HMAC tag for verification
     pub fn kex(&mut self, salt: &[u8], B: &BigUint, u: &BigUint) -> Vec<u8> {
-        // get x = Sha256(salt || password)
-        let mut hasher = Sha256::new();
-        hasher.input([salt.to_owned(), PASSWORD.as_bytes().to_vec()].concat());
-        let x = bytes_to_biguint(&hasher.result().to_vec());
+        // get x = PBKDF2(password, salt)
+        let x = derive_x(salt, PASSWORD.as_bytes());
 
         // derive shared session key
         let S = B.modpow(&(&self.a + u * &x), &self.dh.p);
 
-        let mut hasher = Sha256::new();
-        hasher.input(S.to_bytes_le());
-        self.K = hasher.result().to_vec();
+        self.K = derive_session_key(&S);
 
         // get hmac tag for verification of the ephermal shared key
         let mut hmac_sha256 = Hmac::<Sha256>::new_varkey(&self.K).expect("HMAC can take varkey");
@@ -7,9 +7,7 @@ use rand::{self, RngCore};
 
 pub mod chal18;
 pub mod chal23;
-pub mod chal24;
 pub mod chal28;
-pub mod chal29;
 pub mod chal30;
 pub mod chal31;
 pub mod chal36;
@@ -0,0 +1,445 @@
+// Generic compositions of a `Cipher` with a MAC into authenticated encryption.
+//
+// Encrypt-then-MAC authenticates the ciphertext and is verified before anything gets decrypted.
+// MAC-then-encrypt is provided for comparison (TLS CBC suites): the tag is hidden under the
+// encryption, so the ciphertext must be decrypted and unpadded first, which is what padding oracle
+// and Lucky13-style timing attacks exploit.
+use super::{gcm::constant_time_eq, padding::PaddingError, Cipher, IvCipher};
+use crypto_mac::generic_array::typenum::Unsigned;
+use hmac::Mac;
+use rand::RngCore;
+use std::cell::RefCell;
+use std::error::Error;
+use std::fmt;
+use std::marker::PhantomData;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum AeadError {
+    InvalidLength,
+    InvalidTag,
+}
+
+impl fmt::Display for AeadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            AeadError::InvalidLength => write!(f, "Ciphertext truncated or not made of whole blocks"),
+            AeadError::InvalidTag => write!(f, "Invalid authentication tag"),
+        }
+    }
+}
+impl Error for AeadError {}
+
+/// Derive a `len`-byte subkey from the master `key` using the MAC as PRF in counter mode
+/// (NIST SP 800-108): K(i) = MAC(key, i || label)
+pub fn derive_key<M: Mac>(key: &[u8], label: &[u8], len: usize) -> Vec<u8> {
+    let mut derived: Vec<u8> = vec![];
+    let mut counter: u32 = 1;
+    while derived.len() < len {
+        let mut mac = M::new_varkey(key).expect("HMAC can take varkey");
+        mac.input(&counter.to_be_bytes());
+        mac.input(label);
+        derived.extend_from_slice(&mac.result().code());
+        counter += 1;
+    }
+    derived.truncate(len);
+    derived
+}
+
+// MAC over the length-prefixed associated data followed by `data`
+fn compute_tag<M: Mac>(mac_key: &[u8], aad: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = M::new_varkey(mac_key).expect("HMAC can take varkey");
+    mac.input(&(aad.len() as u64).to_be_bytes());
+    mac.input(aad);
+    mac.input(data);
+    mac.result().code().to_vec()
+}
+
+// (encryption key, MAC key) derived from the master key
+fn derive_keys<M: Mac>(key: &[u8], enc_key_len: usize) -> (Vec<u8>, Vec<u8>) {
+    (
+        derive_key::<M>(key, b"encryption", enc_key_len),
+        derive_key::<M>(key, b"authentication", M::OutputSize::to_usize()),
+    )
+}
+
+// The wrapped cipher under a fresh IV/nonce per message, which travels in front of the ciphertext:
+// ciphers which already prepend it (CBC, PCBC, CFB, OFB, CTS) are not framed twice.
+struct Framing<C: IvCipher> {
+    cipher: C,
+    enc_key_len: usize,
+    rng: RefCell<Box<dyn RngCore>>,
+}
+
+impl<C: IvCipher> Framing<C> {
+    fn new(cipher: C) -> Framing<C> {
+        Framing {
+            cipher,
+            enc_key_len: 16,
+            rng: RefCell::new(Box::new(rand::thread_rng())),
+        }
+    }
+
+    fn iv_len(&self) -> usize {
+        self.cipher.iv_len()
+    }
+
+    fn fresh_iv(&self) -> Vec<u8> {
+        let mut iv = vec![0; self.iv_len()];
+        self.rng.borrow_mut().fill_bytes(&mut iv);
+        iv
+    }
+
+    // IV || ct
+    fn encrypt(&self, key: &[u8], iv: &[u8], msg: &[u8]) -> Vec<u8> {
+        let cipher = self.cipher.with_iv(iv);
+        let ct = cipher.encrypt(key, msg);
+        if cipher.prepends_iv() {
+            ct
+        } else {
+            [iv, &ct[..]].concat()
+        }
+    }
+
+    fn decrypt(&self, key: &[u8], ct: &[u8]) -> Result<Vec<u8>, AeadError> {
+        if ct.len() < self.iv_len() {
+            return Err(AeadError::InvalidLength);
+        }
+        let (iv, body) = ct.split_at(self.iv_len());
+        let cipher = self.cipher.with_iv(iv);
+        let ct = if cipher.prepends_iv() { ct } else { body };
+        cipher.try_decrypt(key, ct).map_err(|e| match e {
+            PaddingError::InvalidBlockSize => AeadError::InvalidLength,
+            PaddingError::InvalidPadding => AeadError::InvalidTag,
+        })
+    }
+}
+
+/// Encrypt-then-MAC: the wire format is IV || ct || tag, with a fresh IV/nonce for every message
+/// and the tag covering IV || ct.
+pub struct EncryptThenMac<C, M>
+where
+    C: IvCipher,
+    M: Mac,
+{
+    framing: Framing<C>,
+    mac: PhantomData<M>,
+}
+
+impl<C, M> EncryptThenMac<C, M>
+where
+    C: IvCipher,
+    M: Mac,
+{
+    /// Compose `cipher` (taking 16-byte keys) with the MAC `M`, the IV/nonce of `cipher` is
+    /// replaced for every message
+    pub fn new(cipher: C) -> EncryptThenMac<C, M> {
+        EncryptThenMac {
+            framing: Framing::new(cipher),
+            mac: PhantomData,
+        }
+    }
+
+    /// Length of the key derived for the cipher, e.g. 32 for XTS
+    pub fn with_key_len(mut self, enc_key_len: usize) -> EncryptThenMac<C, M> {
+        self.framing.enc_key_len = enc_key_len;
+        self
+    }
+
+    /// Source of randomness for the IVs/nonces, e.g. seeded for reproducible output
+    pub fn rng<R: RngCore + 'static>(mut self, rng: R) -> EncryptThenMac<C, M> {
+        self.framing.rng = RefCell::new(Box::new(rng));
+        self
+    }
+
+    pub fn tag_len(&self) -> usize {
+        M::OutputSize::to_usize()
+    }
+
+    /// Authenticated encryption of `msg` with associated data `aad`, returns IV || ct || tag
+    pub fn seal(&self, key: &[u8], aad: &[u8], msg: &[u8]) -> Vec<u8> {
+        let (enc_key, mac_key) = derive_keys::<M>(key, self.framing.enc_key_len);
+        let ct = self.framing.encrypt(&enc_key, &self.framing.fresh_iv(), msg);
+        let tag = compute_tag::<M>(&mac_key, aad, &ct);
+        [ct, tag].concat()
+    }
+
+    /// Authenticated decryption of IV || ct || tag, the tag is verified before any decryption
+    pub fn open(&self, key: &[u8], aad: &[u8], ct: &[u8]) -> Result<Vec<u8>, AeadError> {
+        if ct.len() < self.tag_len() {
+            return Err(AeadError::InvalidLength);
+        }
+        let (ct, tag) = ct.split_at(ct.len() - self.tag_len());
+
+        let (enc_key, mac_key) = derive_keys::<M>(key, self.framing.enc_key_len);
+        if !constant_time_eq(&compute_tag::<M>(&mac_key, aad, ct), tag) {
+            return Err(AeadError::InvalidTag);
+        }
+        // an authentic ciphertext is well formed, unless the sender is broken
+        self.framing.decrypt(&enc_key, ct)
+    }
+}
+
+impl<C, M> Cipher for EncryptThenMac<C, M>
+where
+    C: IvCipher,
+    M: Mac,
+{
+    fn encrypt(&self, key: &[u8], msg: &[u8]) -> Vec<u8> {
+        self.seal(key, &[], msg)
+    }
+
+    // NOTE: an empty plaintext is returned if the tag fails to verify, use `open` to tell them apart
+    fn decrypt(&self, key: &[u8], ct: &[u8]) -> Vec<u8> {
+        self.open(key, &[], ct).unwrap_or_default()
+    }
+}
+
+/// MAC-then-encrypt: the wire format is IV || E(msg || tag), with a fresh IV/nonce for every
+/// message and the tag covering the associated data, the IV and the plaintext. Invalid padding and
+/// invalid tags are reported alike, but only the latter go through the MAC, a timing difference
+/// left on purpose to demonstrate why the composition is fragile.
+pub struct MacThenEncrypt<C, M>
+where
+    C: IvCipher,
+    M: Mac,
+{
+    framing: Framing<C>,
+    mac: PhantomData<M>,
+}
+
+impl<C, M> MacThenEncrypt<C, M>
+where
+    C: IvCipher,
+    M: Mac,
+{
+    /// Compose `cipher` (taking 16-byte keys) with the MAC `M`, the IV/nonce of `cipher` is
+    /// replaced for every message
+    pub fn new(cipher: C) -> MacThenEncrypt<C, M> {
+        MacThenEncrypt {
+            framing: Framing::new(cipher),
+            mac: PhantomData,
+        }
+    }
+
+    /// Length of the key derived for the cipher, e.g. 32 for XTS
+    pub fn with_key_len(mut self, enc_key_len: usize) -> MacThenEncrypt<C, M> {
+        self.framing.enc_key_len = enc_key_len;
+        self
+    }
+
+    /// Source of randomness for the IVs/nonces, e.g. seeded for reproducible output
+    pub fn rng<R: RngCore + 'static>(mut self, rng: R) -> MacThenEncrypt<C, M> {
+        self.framing.rng = RefCell::new(Box::new(rng));
+        self
+    }
+
+    pub fn tag_len(&self) -> usize {
+        M::OutputSize::to_usize()
+    }
+
+    /// Authenticated encryption of `msg` with associated data `aad`, returns IV || E(msg || tag)
+    pub fn seal(&self, key: &[u8], aad: &[u8], msg: &[u8]) -> Vec<u8> {
+        let (enc_key, mac_key) = derive_keys::<M>(key, self.framing.enc_key_len);
+        let iv = self.framing.fresh_iv();
+        let tag = compute_tag::<M>(&mac_key, aad, &[&iv[..], msg].concat());
+        self.framing.encrypt(&enc_key, &iv, &[msg, &tag[..]].concat())
+    }
+
+    /// Decrypt, then verify the tag found at the end of the plaintext
+    pub fn open(&self, key: &[u8], aad: &[u8], ct: &[u8]) -> Result<Vec<u8>, AeadError> {
+        let (enc_key, mac_key) = derive_keys::<M>(key, self.framing.enc_key_len);
+        let pt = self.framing.decrypt(&enc_key, ct)?;
+        if pt.len() < self.tag_len() {
+            return Err(AeadError::InvalidLength);
+        }
+        let (msg, tag) = pt.split_at(pt.len() - self.tag_len());
+
+        let iv = &ct[..self.framing.iv_len()];
+        if !constant_time_eq(&compute_tag::<M>(&mac_key, aad, &[iv, msg].concat()), tag) {
+            return Err(AeadError::InvalidTag);
+        }
+        Ok(msg.to_vec())
+    }
+}
+
+impl<C, M> Cipher for MacThenEncrypt<C, M>
+where
+    C: IvCipher,
+    M: Mac,
+{
+    fn encrypt(&self, key: &[u8], msg: &[u8]) -> Vec<u8> {
+        self.seal(key, &[], msg)
+    }
+
+    // NOTE: an empty plaintext is returned if the tag fails to verify, use `open` to tell them apart
+    fn decrypt(&self, key: &[u8], ct: &[u8]) -> Vec<u8> {
+        self.open(key, &[], ct).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cbc::AES_128_CBC,
+        cfb::AES_128_CFB,
+        ctr::AES_128_CTR,
+        cts::{Variant, AES_128_CBC_CTS},
+        ecb::AES_128_ECB,
+        gcm::AES_128_GCM,
+        ofb::AES_128_OFB,
+        pcbc::AES_128_PCBC,
+        xts::AES_128_XTS,
+    };
+    use hmac::Hmac;
+    use rand::{rngs::StdRng, SeedableRng};
+    use sha1::Sha1;
+
+    const KEY: &[u8] = b"some master key of any length";
+    const MSG: &[u8] = b"comment1=cooking%20MCs;userdata=;comment2=%20like%20a%20pound%20of%20bacon";
+
+    #[test]
+    fn encrypt_then_mac() {
+        let aead = EncryptThenMac::<_, Hmac<Sha1>>::new(AES_128_CBC::new());
+        let ct = aead.seal(KEY, b"header", MSG);
+        assert_eq!(ct.len(), 16 + 80 + 20);
+        assert_eq!(aead.open(KEY, b"header", &ct).unwrap(), MSG.to_vec());
+
+        // chal16-style bit flipping is caught before decryption
+        let mut tampered = ct.clone();
+        tampered[20] ^= 1;
+        assert_eq!(aead.open(KEY, b"header", &tampered), Err(AeadError::InvalidTag));
+        assert_eq!(aead.open(KEY, b"footer", &ct), Err(AeadError::InvalidTag));
+        assert_eq!(
+            aead.open(KEY, b"header", &ct[..10]),
+            Err(AeadError::InvalidLength)
+        );
+
+        let aead = EncryptThenMac::<_, Hmac<Sha1>>::new(AES_128_CTR::new());
+        assert_eq!(aead.encrypt(KEY, MSG).len(), 8 + MSG.len() + 20);
+        assert_eq!(aead.decrypt(KEY, &aead.encrypt(KEY, MSG)), MSG.to_vec());
+    }
+
+    // the same message sealed twice under `cipher`, whose own IV is never used
+    fn fresh_iv<C: IvCipher>(cipher: C) {
+        let etm = EncryptThenMac::<_, Hmac<Sha1>>::new(cipher.with_iv(&cipher.iv()));
+        let (ct1, ct2) = (etm.seal(KEY, b"", MSG), etm.seal(KEY, b"", MSG));
+        assert_ne!(ct1, ct2);
+        assert_eq!(etm.open(KEY, b"", &ct1).unwrap(), MSG.to_vec());
+        assert_eq!(etm.open(KEY, b"", &ct2).unwrap(), MSG.to_vec());
+
+        let mte = MacThenEncrypt::<_, Hmac<Sha1>>::new(cipher);
+        let (ct1, ct2) = (mte.seal(KEY, b"", MSG), mte.seal(KEY, b"", MSG));
+        assert_ne!(ct1, ct2);
+        assert_eq!(mte.open(KEY, b"", &ct1).unwrap(), MSG.to_vec());
+        assert_eq!(mte.open(KEY, b"", &ct2).unwrap(), MSG.to_vec());
+    }
+
+    #[test]
+    fn fresh_iv_per_message() {
+        fresh_iv(AES_128_CBC::new());
+        fresh_iv(AES_128_CTR::new());
+        fresh_iv(AES_128_GCM::new());
+        fresh_iv(AES_128_OFB::new());
+
+        // CTR keystreams no longer cancel out across messages
+        let aead = EncryptThenMac::<_, Hmac<Sha1>>::new(AES_128_CTR::new());
+        let ct1 = aead.seal(KEY, b"", &[b'a'; 16]);
+        let ct2 = aead.seal(KEY, b"", &[b'b'; 16]);
+        assert_ne!(xor::xor(&ct1[8..24], &ct2[8..24]).unwrap(), vec![3; 16]);
+
+        // the IV is authenticated
+        let mut tampered = ct1.clone();
+        tampered[0] ^= 1;
+        assert_eq!(aead.open(KEY, b"", &tampered), Err(AeadError::InvalidTag));
+
+        // and drawn from the chosen RNG
+        let seeded =
+            || EncryptThenMac::<_, Hmac<Sha1>>::new(AES_128_CBC::new()).rng(StdRng::seed_from_u64(7));
+        assert_eq!(seeded().seal(KEY, b"", MSG), seeded().seal(KEY, b"", MSG));
+    }
+
+    #[test]
+    fn mac_then_encrypt() {
+        let aead = MacThenEncrypt::<_, Hmac<Sha1>>::new(AES_128_CBC::new());
+        let ct = aead.seal(KEY, b"header", MSG);
+        assert_eq!(ct.len(), 16 + 96);
+        assert_eq!(aead.open(KEY, b"header", &ct).unwrap(), MSG.to_vec());
+
+        // a padding error is indistinguishable from a bad tag (save for timing)
+        let mut tampered = ct.clone();
+        tampered[ct.len() - 17] ^= 0xff;
+        assert_eq!(aead.open(KEY, b"header", &tampered), Err(AeadError::InvalidTag));
+        let mut tampered = ct.clone();
+        tampered[ct.len() - 18] ^= 1;
+        assert_eq!(aead.open(KEY, b"header", &tampered), Err(AeadError::InvalidTag));
+        let mut tampered = ct.clone();
+        tampered[3] ^= 1;
+        assert_eq!(aead.open(KEY, b"header", &tampered), Err(AeadError::InvalidTag));
+    }
+
+    // MAC-then-encrypt under a block mode must not panic on partial or missing blocks
+    fn truncated_blocks<C: IvCipher>(cipher: C) {
+        let aead = MacThenEncrypt::<_, Hmac<Sha1>>::new(cipher);
+        let ct = aead.seal(KEY, b"", MSG);
+        let invalid = Err(AeadError::InvalidLength);
+        assert_eq!(aead.open(KEY, b"", &ct[..ct.len() - 1]), invalid);
+        assert_eq!(aead.open(KEY, b"", &[]), invalid);
+        assert_eq!(aead.open(KEY, b"", &ct[..20]), invalid);
+        // whole blocks, too short for a tag (or invalid padding)
+        assert!(aead.open(KEY, b"", &ct[..32]).is_err());
+    }
+
+    #[test]
+    fn malformed_ciphertext() {
+        truncated_blocks(AES_128_CBC::new());
+        truncated_blocks(AES_128_PCBC::new());
+
+        let aead = EncryptThenMac::<_, Hmac<Sha1>>::new(AES_128_CBC::new());
+        assert_eq!(aead.open(KEY, b"", &[]), Err(AeadError::InvalidLength));
+        assert_eq!(aead.open(KEY, b"", &[0; 40]), Err(AeadError::InvalidTag));
+    }
+
+    // cut within or just past the IV, both compositions report a length error instead of panicking
+    fn truncated<C: IvCipher>(cipher: C, key_len: usize) {
+        let iv_len = cipher.iv_len();
+        let etm = EncryptThenMac::<_, Hmac<Sha1>>::new(cipher.with_iv(&cipher.iv())).with_key_len(key_len);
+        let ct = etm.seal(KEY, b"", MSG);
+        assert_eq!(
+            etm.open(KEY, b"", &ct[..etm.tag_len() - 1]),
+            Err(AeadError::InvalidLength)
+        );
+
+        let mte = MacThenEncrypt::<_, Hmac<Sha1>>::new(cipher).with_key_len(key_len);
+        let ct = mte.seal(KEY, b"", MSG);
+        for &len in [0, iv_len.saturating_sub(1), iv_len + 8].iter() {
+            assert_eq!(mte.open(KEY, b"", &ct[..len]), Err(AeadError::InvalidLength));
+        }
+    }
+
+    #[test]
+    fn truncated_ciphertext_every_mode() {
+        truncated(AES_128_ECB::new(), 16);
+        truncated(AES_128_CBC::new(), 16);
+        truncated(AES_128_CTR::new(), 16);
+        truncated(AES_128_GCM::new(), 16);
+        truncated(AES_128_CFB::new(1), 16);
+        truncated(AES_128_CFB::new(8), 16);
+        truncated(AES_128_CFB::new(128), 16);
+        truncated(AES_128_OFB::new(), 16);
+        truncated(AES_128_PCBC::new(), 16);
+        truncated(AES_128_XTS::new(), 32);
+        truncated(AES_128_CBC_CTS::new(Variant::CS3), 16);
+    }
+
+    #[test]
+    fn derived_keys_are_independent() {
+        let (enc_key, mac_key) = derive_keys::<Hmac<Sha1>>(KEY, 32);
+        assert_eq!((enc_key.len(), mac_key.len()), (32, 20));
+        assert_ne!(enc_key[..20], mac_key[..]);
+        assert_eq!(
+            enc_key[..16],
+            derive_key::<Hmac<Sha1>>(KEY, b"encryption", 16)[..]
+        );
+    }
+}
@@ -0,0 +1,181 @@
+use super::{cbc, cfb, ctr, cts, ecb, gcm, ofb, padding, pcbc, xts, Cipher, Mode};
+use rand::RngCore;
+use std::convert::TryInto;
+
+/// Builder to instantiate a cipher with a chosen key, IV/nonce, padding and source of randomness.
+/// The key and IV/nonce, when not explicitly provided, are drawn from the RNG, thus seeding the RNG
+/// makes them reproducible. ISO 10126 filler bytes are drawn afresh from the thread RNG on every
+/// encryption, so ciphertexts under `Scheme::Iso10126` differ regardless of the seed.
+pub struct CipherBuilder {
+    mode: Mode,
+    key: Option<Vec<u8>>,
+    iv: Option<Vec<u8>>,
+    padding: padding::Scheme,
+    rng: Box<dyn RngCore>,
+}
+
+impl CipherBuilder {
+    pub fn new(mode: Mode) -> CipherBuilder {
+        CipherBuilder {
+            mode,
+            key: None,
+            iv: None,
+            padding: padding::Scheme::default(),
+            rng: Box::new(rand::thread_rng()),
+        }
+    }
+
+    pub fn key(mut self, key: &[u8]) -> CipherBuilder {
+        self.key = Some(key.to_vec());
+        self
+    }
+
+    /// Set the IV, nonce (CTR, GCM) or tweak (XTS), see `iv_len` for the expected length
+    pub fn iv(mut self, iv: &[u8]) -> CipherBuilder {
+        self.iv = Some(iv.to_vec());
+        self
+    }
+
+    /// Padding scheme of ECB, CBC and PCBC, ignored by the other modes
+    pub fn padding(mut self, padding: padding::Scheme) -> CipherBuilder {
+        self.padding = padding;
+        self
+    }
+
+    /// Source of randomness for the key and IV/nonce if they are not explicitly provided
+    pub fn rng<R: RngCore + 'static>(mut self, rng: R) -> CipherBuilder {
+        self.rng = Box::new(rng);
+        self
+    }
+
+    /// Length of the IV/nonce in bytes expected by the mode
+    pub fn iv_len(mode: &Mode) -> usize {
+        match mode {
+            Mode::ECB => 0,
+            Mode::CTR => 8,
+            Mode::GCM => 12,
+            _ => 16,
+        }
+    }
+
+    /// Length of the key in bytes expected by the mode
+    pub fn key_len(mode: &Mode) -> usize {
+        match mode {
+            Mode::XTS => 32,
+            _ => 16,
+        }
+    }
+
+    pub fn build(mut self) -> KeyedCipher {
+        let key = match self.key.take() {
+            Some(key) => key,
+            None => self.random_bytes(Self::key_len(&self.mode)),
+        };
+        let iv = match self.iv.take() {
+            Some(iv) => iv,
+            None => self.random_bytes(Self::iv_len(&self.mode)),
+        };
+        if iv.len() != Self::iv_len(&self.mode) {
+            panic!(
+                "Invalid iv length, {:?} expects {} bytes",
+                self.mode,
+                Self::iv_len(&self.mode)
+            );
+        }
+
+        let cipher: Box<dyn Cipher> = match self.mode {
+            Mode::ECB => Box::from(ecb::AES_128_ECB::new().with_padding(self.padding)),
+            Mode::CBC => Box::from(cbc::AES_128_CBC::from_iv(&iv).with_padding(self.padding)),
+            Mode::CTR => Box::from(ctr::AES_128_CTR::new_with_nonce(u64::from_le_bytes(
+                iv[..].try_into().unwrap(),
+            ))),
+            Mode::GCM => Box::from(gcm::AES_128_GCM::new_with_nonce(&iv)),
+            Mode::CFB1 => Box::from(cfb::AES_128_CFB::from_iv(&iv, 1)),
+            Mode::CFB8 => Box::from(cfb::AES_128_CFB::from_iv(&iv, 8)),
+            Mode::CFB128 => Box::from(cfb::AES_128_CFB::from_iv(&iv, 128)),
+            Mode::OFB => Box::from(ofb::AES_128_OFB::from_iv(&iv)),
+            Mode::PCBC => Box::from(pcbc::AES_128_PCBC::from_iv(&iv).with_padding(self.padding)),
+            Mode::XTS => Box::from(xts::AES_128_XTS::from_sector(u128::from_le_bytes(
+                iv[..].try_into().unwrap(),
+            ))),
+            Mode::CTS => Box::from(cts::AES_128_CBC_CTS::from_iv(&iv, cts::Variant::CS3)),
+        };
+        KeyedCipher { cipher, key }
+    }
+
+    fn random_bytes(&mut self, len: usize) -> Vec<u8> {
+        let mut bytes = vec![0; len];
+        self.rng.fill_bytes(&mut bytes);
+        bytes
+    }
+}
+
+/// A cipher bound to its key, produced by `CipherBuilder`
+pub struct KeyedCipher {
+    cipher: Box<dyn Cipher>,
+    key: Vec<u8>,
+}
+
+impl KeyedCipher {
+    pub fn encrypt(&self, msg: &[u8]) -> Vec<u8> {
+        self.cipher.encrypt(&self.key, msg)
+    }
+
+    pub fn decrypt(&self, ct: &[u8]) -> Vec<u8> {
+        self.cipher.decrypt(&self.key, ct)
+    }
+
+    pub fn key(&self) -> &[u8] {
+        &self.key
+    }
+
+    /// Returns the IV/nonce (or tweak) in use, e.g. for an oracle to log it
+    pub fn iv(&self) -> Vec<u8> {
+        self.cipher.iv()
+    }
+
+    /// Returns the underlying unkeyed cipher
+    pub fn cipher(&self) -> &dyn Cipher {
+        self.cipher.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn builder_is_reproducible() {
+        let modes = || vec![Mode::ECB, Mode::CBC, Mode::CTR, Mode::GCM, Mode::PCBC, Mode::XTS];
+        let msg = b"Privacy is necessary for an open society".to_vec();
+        for (mode1, mode2) in modes().into_iter().zip(modes()) {
+            let cipher1 = CipherBuilder::new(mode1).rng(StdRng::seed_from_u64(42)).build();
+            let cipher2 = CipherBuilder::new(mode2).rng(StdRng::seed_from_u64(42)).build();
+            assert_eq!(cipher1.key(), cipher2.key());
+            assert_eq!(cipher1.iv(), cipher2.iv());
+            assert_eq!(cipher1.encrypt(&msg), cipher2.encrypt(&msg));
+            assert_eq!(cipher1.decrypt(&cipher1.encrypt(&msg)), msg);
+        }
+    }
+
+    #[test]
+    fn builder_with_chosen_iv() {
+        let key = b"i am pied piper!".to_vec();
+        let iv = b"yellow submarine".to_vec();
+        let msg = b"Privacy".to_vec();
+        let cipher = CipherBuilder::new(Mode::CBC).key(&key).iv(&iv).build();
+
+        assert_eq!(cipher.iv(), iv);
+        assert_eq!(
+            cipher.encrypt(&msg),
+            cbc::AES_128_CBC::from_iv(&iv).encrypt(&key, &msg)
+        );
+
+        let cipher = CipherBuilder::new(Mode::CBC)
+            .key(&key)
+            .padding(padding::Scheme::NoPadding)
+            .build();
+        assert_eq!(cipher.encrypt(&iv).len(), 32);
+    }
+}
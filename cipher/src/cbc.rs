@@ -1,5 +1,9 @@
 #![allow(dead_code)]
-use super::{from_blocks, into_blocks, padding, random_bytes_array, Cipher};
+use super::{
+    from_blocks, into_blocks,
+    padding::{self, Padding},
+    random_bytes_array, Cipher, IvCipher,
+};
 use openssl::symm::{Cipher as SslCipher, Crypter as SslCrypter, Mode};
 use std::convert::TryInto;
 
@@ -7,6 +11,7 @@ use std::convert::TryInto;
 #[derive(Default)]
 pub struct AES_128_CBC {
     iv: [u8; 16],
+    padding: padding::Scheme,
 }
 
 impl AES_128_CBC {
@@ -14,7 +19,10 @@ impl AES_128_CBC {
     pub fn new() -> AES_128_CBC {
         let mut iv = [0 as u8; 16];
         random_bytes_array(&mut iv);
-        AES_128_CBC { iv }
+        AES_128_CBC {
+            iv,
+            padding: padding::Scheme::default(),
+        }
     }
 
     pub fn from_iv(iv: &[u8]) -> AES_128_CBC {
@@ -23,9 +31,16 @@ impl AES_128_CBC {
         }
         AES_128_CBC {
             iv: iv.try_into().unwrap(),
+            padding: padding::Scheme::default(),
         }
     }
 
+    /// Replace the default PKCS#7 padding scheme
+    pub fn with_padding(mut self, padding: padding::Scheme) -> AES_128_CBC {
+        self.padding = padding;
+        self
+    }
+
     /// Validate whether `blocks` is truncated into a list of 128-bit(16-byte) block.
     fn validate_block(blocks: &[Vec<u8>]) {
         for block in blocks.iter() {
@@ -35,22 +50,6 @@ impl AES_128_CBC {
         }
     }
 
-    /// Add padding to the trailing block.
-    fn add_padding(blocks: &mut Vec<Vec<u8>>) {
-        let result = padding::add(blocks, 16);
-        if result.is_err() {
-            panic!("failed to add padding, internal bug");
-        }
-    }
-
-    /// Remove trailing padding, mostly used on decrypted blocks
-    fn remove_padding(blocks: &mut Vec<Vec<u8>>) {
-        let result = padding::remove(blocks, 16);
-        if result.is_err() {
-            panic!("failed to remove padding, internal bug");
-        }
-    }
-
     /// return plaintext with padding
     pub fn decrypt_with_padding(key: &[u8], ct: &[u8]) -> Vec<u8> {
         // format ciphertext to 2D vector
@@ -80,10 +79,12 @@ impl AES_128_CBC {
 
 impl Cipher for AES_128_CBC {
     fn encrypt(&self, key: &[u8], msg: &[u8]) -> Vec<u8> {
-        // format msg into 2D vector blocks
-        let mut msg_block = into_blocks(msg, 16);
-        // Pad and validate msg blocks
-        Self::add_padding(&mut msg_block);
+        // Pad and format msg into 2D vector blocks
+        let padded_msg = match self.padding.pad(msg, 16) {
+            Ok(padded_msg) => padded_msg,
+            Err(e) => panic!("failed to add padding: {}", e),
+        };
+        let msg_block = into_blocks(&padded_msg, 16);
         Self::validate_block(&msg_block);
 
         let mut ct: Vec<Vec<u8>> = vec![Vec::new(); msg_block.len()];
@@ -106,11 +107,37 @@ impl Cipher for AES_128_CBC {
     }
 
     fn decrypt(&self, key: &[u8], ct: &[u8]) -> Vec<u8> {
-        let pt = Self::decrypt_with_padding(&key, &ct);
-        let mut pt_2d = into_blocks(&pt, 16);
-        // remove padding
-        Self::remove_padding(&mut pt_2d);
-        from_blocks(&pt_2d)
+        match self.try_decrypt(key, ct) {
+            Ok(pt) => pt,
+            Err(e) => panic!("failed to remove padding: {}", e),
+        }
+    }
+
+    fn try_decrypt(&self, key: &[u8], ct: &[u8]) -> Result<Vec<u8>, padding::PaddingError> {
+        // at least the IV, and whole blocks only
+        if ct.len() < 16 || !ct.len().is_multiple_of(16) {
+            return Err(padding::PaddingError::InvalidBlockSize);
+        }
+        let pt = Self::decrypt_with_padding(key, ct);
+        self.padding.unpad(&pt, 16)
+    }
+
+    fn iv(&self) -> Vec<u8> {
+        self.iv.to_vec()
+    }
+}
+
+impl IvCipher for AES_128_CBC {
+    fn iv_len(&self) -> usize {
+        16
+    }
+
+    fn with_iv(&self, iv: &[u8]) -> AES_128_CBC {
+        AES_128_CBC::from_iv(iv).with_padding(self.padding)
+    }
+
+    fn prepends_iv(&self) -> bool {
+        true
     }
 }
 
@@ -129,4 +156,15 @@ mod tests {
         assert_eq!(cipher.decrypt(&key, &cipher.encrypt(&key, &msg1)), msg1);
         assert_eq!(cipher.decrypt(&key, &cipher.encrypt(&key, &msg2)), msg2);
     }
+
+    #[test]
+    fn malformed_ciphertext() {
+        let cipher = AES_128_CBC::new();
+        let key = b"i am pied piper!".to_vec();
+        let ct = cipher.encrypt(&key, b"Privacy is necessary");
+        let invalid = Err(padding::PaddingError::InvalidBlockSize);
+        assert_eq!(cipher.try_decrypt(&key, &ct[..ct.len() - 1]), invalid);
+        assert_eq!(cipher.try_decrypt(&key, &[]), invalid);
+        assert_eq!(cipher.try_decrypt(&key, &ct[..15]), invalid);
+    }
 }
@@ -0,0 +1,137 @@
+use super::{ecb::AES_128_ECB, padding, random_bytes_array, Cipher, IvCipher};
+use std::convert::TryInto;
+
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone)]
+pub struct AES_128_CFB {
+    iv: [u8; 16],
+    segment_bits: usize,
+}
+
+impl AES_128_CFB {
+    /// Instantiate a new `AES_128_CFB` cipher with a random `iv` and a feedback of 1, 8 or 128 bits
+    pub fn new(segment_bits: usize) -> AES_128_CFB {
+        let mut iv = [0; 16];
+        random_bytes_array(&mut iv);
+        Self::from_iv(&iv, segment_bits)
+    }
+
+    pub fn from_iv(iv: &[u8], segment_bits: usize) -> AES_128_CFB {
+        if iv.len() != 16 {
+            panic!("Invalid iv value, should be a [u8; 16]");
+        }
+        if ![1, 8, 128].contains(&segment_bits) {
+            panic!("Invalid segment size, only 1, 8 and 128-bit feedback are supported");
+        }
+        AES_128_CFB {
+            iv: iv.try_into().unwrap(),
+            segment_bits,
+        }
+    }
+
+    // The shift register is fed back with ciphertext, i.e. the output when encrypting and the
+    // input when decrypting.
+    fn process(&self, key: &[u8], input: &[u8], encrypt: bool) -> Vec<u8> {
+        let mut register = u128::from_be_bytes(self.iv);
+        let mut output: Vec<u8> = vec![];
+        let keystream = |register: u128| AES_128_ECB::encrypt_block(key, &register.to_be_bytes());
+
+        match self.segment_bits {
+            128 => {
+                for block in input.chunks(16) {
+                    let out = xor::xor(&keystream(register)[..block.len()], block).unwrap();
+                    if block.len() == 16 {
+                        let feedback = if encrypt { &out[..] } else { block };
+                        register = u128::from_be_bytes(feedback.try_into().unwrap());
+                    }
+                    output.extend_from_slice(&out);
+                }
+            }
+            8 => {
+                for byte in input.iter() {
+                    let out = byte ^ keystream(register)[0];
+                    let feedback = if encrypt { out } else { *byte };
+                    register = (register << 8) | feedback as u128;
+                    output.push(out);
+                }
+            }
+            _ => {
+                for byte in input.iter() {
+                    let mut out = 0;
+                    for i in (0..8).rev() {
+                        let bit = (byte >> i) & 1;
+                        let out_bit = bit ^ (keystream(register)[0] >> 7);
+                        let feedback = if encrypt { out_bit } else { bit };
+                        register = (register << 1) | feedback as u128;
+                        out |= out_bit << i;
+                    }
+                    output.push(out);
+                }
+            }
+        }
+        output
+    }
+}
+
+impl Cipher for AES_128_CFB {
+    fn encrypt(&self, key: &[u8], msg: &[u8]) -> Vec<u8> {
+        [self.iv.to_vec(), self.process(key, msg, true)].concat()
+    }
+
+    fn decrypt(&self, key: &[u8], ct: &[u8]) -> Vec<u8> {
+        if ct.len() < 16 {
+            panic!("Invalid ciphertext, should be prefixed with a 16-byte iv");
+        }
+        Self::from_iv(&ct[..16], self.segment_bits).process(key, &ct[16..], false)
+    }
+
+    fn try_decrypt(&self, key: &[u8], ct: &[u8]) -> Result<Vec<u8>, padding::PaddingError> {
+        if ct.len() < 16 {
+            return Err(padding::PaddingError::InvalidBlockSize);
+        }
+        Ok(self.decrypt(key, ct))
+    }
+
+    fn iv(&self) -> Vec<u8> {
+        self.iv.to_vec()
+    }
+}
+
+impl IvCipher for AES_128_CFB {
+    fn iv_len(&self) -> usize {
+        16
+    }
+
+    fn with_iv(&self, iv: &[u8]) -> AES_128_CFB {
+        AES_128_CFB::from_iv(iv, self.segment_bits)
+    }
+
+    fn prepends_iv(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use openssl::symm::{self, Cipher as SslCipher};
+
+    #[test]
+    fn cfb_correctness() {
+        let msg = b"Privacy is necessary for an open society in the electronic age".to_vec();
+        let key = b"i am pied piper!".to_vec();
+        let ssl_ciphers = [
+            (1, SslCipher::aes_128_cfb1()),
+            (8, SslCipher::aes_128_cfb8()),
+            (128, SslCipher::aes_128_cfb128()),
+        ];
+
+        for (segment_bits, ssl_cipher) in ssl_ciphers.iter() {
+            let cipher = AES_128_CFB::new(*segment_bits);
+            let ct = cipher.encrypt(&key, &msg);
+            let expected = symm::encrypt(*ssl_cipher, &key, Some(&ct[..16]), &msg).unwrap();
+            assert_eq!(ct[16..], expected[..]);
+            assert_eq!(cipher.decrypt(&key, &ct), msg);
+        }
+    }
+}
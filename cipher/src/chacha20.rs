@@ -0,0 +1,158 @@
+use super::{random_bytes_array, Cipher, StreamCipher};
+use std::convert::TryInto;
+
+// "expand 32-byte k"
+const CONSTANTS: [u32; 4] = [0x6170_7865, 0x3320_646e, 0x7962_2d32, 0x6b20_6574];
+
+/// ChaCha20 stream cipher as specified in RFC 8439: 256-bit key, 96-bit nonce and 32-bit block
+/// counter. Like CTR, the nonce is not prepended to the ciphertext.
+#[derive(Debug, Clone, Default)]
+pub struct ChaCha20 {
+    nonce: [u8; 12],
+    counter: u32,
+}
+
+impl ChaCha20 {
+    /// Instantiate a new `ChaCha20` cipher with a random nonce and an initial counter of 0
+    pub fn new() -> ChaCha20 {
+        let mut nonce = [0; 12];
+        random_bytes_array(&mut nonce);
+        ChaCha20 { nonce, counter: 0 }
+    }
+
+    pub fn new_with_nonce(nonce: &[u8], counter: u32) -> ChaCha20 {
+        if nonce.len() != 12 {
+            panic!("Invalid nonce length, should be 12 bytes");
+        }
+        ChaCha20 {
+            nonce: nonce.try_into().unwrap(),
+            counter,
+        }
+    }
+
+    pub fn nonce(&self) -> &[u8] {
+        &self.nonce
+    }
+
+    pub fn counter(&self) -> u32 {
+        self.counter
+    }
+}
+
+fn quarter_round(state: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] = (state[d] ^ state[a]).rotate_left(16);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_left(12);
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] = (state[d] ^ state[a]).rotate_left(8);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_left(7);
+}
+
+/// ChaCha20 block function, returns the 64-byte keystream block for the given counter
+pub fn block(key: &[u8], counter: u32, nonce: &[u8]) -> [u8; 64] {
+    if key.len() != 32 {
+        panic!("Invalid key length, should be 32 bytes");
+    }
+    if nonce.len() != 12 {
+        panic!("Invalid nonce length, should be 12 bytes");
+    }
+    let words = |bytes: &[u8]| -> Vec<u32> {
+        bytes
+            .chunks(4)
+            .map(|w| u32::from_le_bytes(w.try_into().unwrap()))
+            .collect()
+    };
+
+    let mut init = [0; 16];
+    init[..4].copy_from_slice(&CONSTANTS);
+    init[4..12].copy_from_slice(&words(key));
+    init[12] = counter;
+    init[13..].copy_from_slice(&words(nonce));
+
+    let mut state = init;
+    for _ in 0..10 {
+        // column rounds then diagonal rounds
+        quarter_round(&mut state, 0, 4, 8, 12);
+        quarter_round(&mut state, 1, 5, 9, 13);
+        quarter_round(&mut state, 2, 6, 10, 14);
+        quarter_round(&mut state, 3, 7, 11, 15);
+        quarter_round(&mut state, 0, 5, 10, 15);
+        quarter_round(&mut state, 1, 6, 11, 12);
+        quarter_round(&mut state, 2, 7, 8, 13);
+        quarter_round(&mut state, 3, 4, 9, 14);
+    }
+
+    let mut out = [0; 64];
+    for (i, chunk) in out.chunks_mut(4).enumerate() {
+        chunk.copy_from_slice(&state[i].wrapping_add(init[i]).to_le_bytes());
+    }
+    out
+}
+
+impl StreamCipher for ChaCha20 {
+    fn key_stream(&self, key: &[u8], len: usize) -> Vec<u8> {
+        let mut key_stream: Vec<u8> = vec![];
+        let mut counter = self.counter;
+        while key_stream.len() < len {
+            key_stream.extend_from_slice(&block(key, counter, &self.nonce));
+            counter = counter.wrapping_add(1);
+        }
+        key_stream.truncate(len);
+        key_stream
+    }
+}
+
+impl Cipher for ChaCha20 {
+    fn encrypt(&self, key: &[u8], msg: &[u8]) -> Vec<u8> {
+        self.apply_key_stream(key, msg)
+    }
+
+    fn decrypt(&self, key: &[u8], ct: &[u8]) -> Vec<u8> {
+        self.apply_key_stream(key, ct)
+    }
+
+    fn iv(&self) -> Vec<u8> {
+        self.nonce.to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use encoding::hex::hexstr_to_bytes as hex;
+    use openssl::symm::{self, Cipher as SslCipher};
+
+    #[test]
+    fn chacha20_rfc8439_vector() {
+        // source: RFC 8439, Section 2.4.2
+        let key: Vec<u8> = (0..32).collect();
+        let cipher = ChaCha20::new_with_nonce(&hex("000000000000004a00000000").unwrap(), 1);
+        let msg = b"Ladies and Gentlemen of the class of '99: If I could offer you only one tip \
+                    for the future, sunscreen would be it."
+            .to_vec();
+        let expected = hex("6e2e359a2568f98041ba0728dd0d6981e97e7aec1d4360c20a27afccfd9fae0b\
+             f91b65c5524733ab8f593dabcd62b3571639d624e65152ab8f530c359f0861d8\
+             07ca0dbf500d6a6156a38e088a22b65e52bc514d16ccf806818ce91ab7793736\
+             5af90bbf74a35be6b40b8eedf2785e42874d")
+        .unwrap();
+
+        assert_eq!(cipher.encrypt(&key, &msg), expected);
+        assert_eq!(cipher.decrypt(&key, &expected), msg);
+    }
+
+    #[test]
+    fn chacha20_correctness() {
+        let cipher = ChaCha20::new();
+        let key = b"i am pied piper!i am pied piper!".to_vec();
+        let msg = b"Privacy is necessary for an open society in the electronic age. Privacy is not \
+                    secrecy."
+            .to_vec();
+
+        // openssl takes the 32-bit little endian counter followed by the nonce as its iv
+        let iv = [&0u32.to_le_bytes()[..], cipher.nonce()].concat();
+        let expected = symm::encrypt(SslCipher::chacha20(), &key, Some(&iv), &msg).unwrap();
+        assert_eq!(cipher.encrypt(&key, &msg), expected);
+    }
+}
@@ -1,5 +1,6 @@
-use super::{from_blocks, Cipher};
+use super::{from_blocks, Cipher, IvCipher};
 use openssl::symm::{Cipher as SslCipher, Crypter as SslCrypter, Mode};
+use std::convert::TryInto;
 
 #[allow(non_camel_case_types)]
 #[derive(Default)]
@@ -29,45 +30,53 @@ impl Cipher for AES_128_CTR {
         if key.len() != 16 {
             panic!("Invalid key length, should be 16 bytes");
         }
+        apply_keystream(key, msg, |ctr| self.format_counter(ctr))
+    }
 
-        let mut ct: Vec<Vec<u8>> = vec![];
-        let mut encrypter = SslCrypter::new(SslCipher::aes_128_ecb(), Mode::Encrypt, key, None).unwrap();
-        encrypter.pad(false);
-
-        for (ctr, msg_block) in msg.chunks(16).enumerate() {
-            let mut key_stream = vec![0 as u8; 32];
-            let mut count = encrypter
-                .update(&self.format_counter(ctr as u64), &mut key_stream[..])
-                .unwrap();
-            count += encrypter.finalize(&mut key_stream[count..]).unwrap();
-            key_stream.truncate(count);
-            key_stream.truncate(msg_block.len());
+    fn decrypt(&self, key: &[u8], ct: &[u8]) -> Vec<u8> {
+        apply_keystream(key, ct, |ctr| self.format_counter(ctr))
+    }
 
-            ct.push(xor::xor(&key_stream[..], &msg_block).unwrap());
-        }
+    fn iv(&self) -> Vec<u8> {
+        self.nonce.to_le_bytes().to_vec()
+    }
+}
 
-        from_blocks(&ct)
+impl IvCipher for AES_128_CTR {
+    fn iv_len(&self) -> usize {
+        8
     }
 
-    fn decrypt(&self, key: &[u8], ct: &[u8]) -> Vec<u8> {
-        let mut pt: Vec<Vec<u8>> = vec![];
-        let mut encrypter = SslCrypter::new(SslCipher::aes_128_ecb(), Mode::Encrypt, key, None).unwrap();
-        encrypter.pad(false);
+    fn with_iv(&self, iv: &[u8]) -> AES_128_CTR {
+        AES_128_CTR::new_with_nonce(u64::from_le_bytes(
+            iv.try_into().expect("Invalid nonce length, should be 8 bytes"),
+        ))
+    }
+}
 
-        for (ctr, ct_block) in ct.chunks(16).enumerate() {
-            let mut key_stream = vec![0 as u8; 32];
-            let mut count = encrypter
-                .update(&self.format_counter(ctr as u64), &mut key_stream[..])
-                .unwrap();
-            count += encrypter.finalize(&mut key_stream[count..]).unwrap();
-            key_stream.truncate(count);
-            key_stream.truncate(ct_block.len());
+/// XOR `msg` with the keystream E(k, counter(0)) || E(k, counter(1)) || ..., where `counter(i)`
+/// returns the 16-byte counter block for the i-th block. Shared by all counter-based modes.
+pub fn apply_keystream<F>(key: &[u8], msg: &[u8], counter: F) -> Vec<u8>
+where
+    F: Fn(u64) -> Vec<u8>,
+{
+    let mut out: Vec<Vec<u8>> = vec![];
+    let mut encrypter = SslCrypter::new(SslCipher::aes_128_ecb(), Mode::Encrypt, key, None).unwrap();
+    encrypter.pad(false);
 
-            pt.push(xor::xor(&key_stream[..], &ct_block).unwrap());
-        }
+    for (ctr, msg_block) in msg.chunks(16).enumerate() {
+        let mut key_stream = vec![0; 32];
+        let mut count = encrypter
+            .update(&counter(ctr as u64), &mut key_stream[..])
+            .unwrap();
+        count += encrypter.finalize(&mut key_stream[count..]).unwrap();
+        key_stream.truncate(count);
+        key_stream.truncate(msg_block.len());
 
-        from_blocks(&pt)
+        out.push(xor::xor(&key_stream[..], msg_block).unwrap());
     }
+
+    from_blocks(&out)
 }
 
 #[cfg(test)]
@@ -0,0 +1,187 @@
+use super::{cbc::AES_128_CBC, ecb::AES_128_ECB, padding, random_bytes_array, Cipher, IvCipher};
+use std::convert::TryInto;
+
+/// Ordering of the last two ciphertext blocks, see NIST SP 800-38A Addendum
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variant {
+    /// C[n-1]* || C[n], the truncated block comes first
+    CS1,
+    /// same as CS1 if the message is block-aligned, otherwise CS3
+    CS2,
+    /// C[n] || C[n-1]*, always swapped (Kerberos)
+    CS3,
+}
+
+/// CBC with ciphertext stealing, no padding and the ciphertext is as long as the message.
+/// The message should be at least one block long.
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone)]
+pub struct AES_128_CBC_CTS {
+    iv: [u8; 16],
+    variant: Variant,
+}
+
+impl AES_128_CBC_CTS {
+    /// Instantiate a new `AES_128_CBC_CTS` cipher with a random `iv`.
+    pub fn new(variant: Variant) -> AES_128_CBC_CTS {
+        let mut iv = [0; 16];
+        random_bytes_array(&mut iv);
+        Self::from_iv(&iv, variant)
+    }
+
+    pub fn from_iv(iv: &[u8], variant: Variant) -> AES_128_CBC_CTS {
+        if iv.len() != 16 {
+            panic!("Invalid iv value, should be a [u8; 16]");
+        }
+        AES_128_CBC_CTS {
+            iv: iv.try_into().unwrap(),
+            variant,
+        }
+    }
+
+    // whether the last two blocks are swapped compared to CS1 for a message of `len` bytes
+    fn swapped(&self, len: usize) -> bool {
+        len > 16
+            && match self.variant {
+                Variant::CS1 => false,
+                Variant::CS2 => !len.is_multiple_of(16),
+                Variant::CS3 => true,
+            }
+    }
+
+    // swap the partial (or full) trailing block `d` bytes and the full block before it
+    fn swap_tail(ct: &mut Vec<u8>, d: usize) {
+        let n = ct.len();
+        let (first, second) = (ct[n - 16 - d..n - 16].to_vec(), ct[n - 16..].to_vec());
+        ct.truncate(n - 16 - d);
+        ct.extend([second, first].concat());
+    }
+
+    // swap the full block and the trailing `d` bytes back into CS1 order
+    fn unswap_tail(ct: &mut Vec<u8>, d: usize) {
+        let n = ct.len();
+        let (first, second) = (ct[n - 16 - d..n - d].to_vec(), ct[n - d..].to_vec());
+        ct.truncate(n - 16 - d);
+        ct.extend([second, first].concat());
+    }
+}
+
+impl Cipher for AES_128_CBC_CTS {
+    fn encrypt(&self, key: &[u8], msg: &[u8]) -> Vec<u8> {
+        if msg.len() < 16 {
+            panic!("Invalid message, ciphertext stealing requires at least one block");
+        }
+        // C[n] = E(k, (P[n]* || 0) ^ C[n-1]) then truncate C[n-1] to the length of P[n]*
+        let cbc = AES_128_CBC::from_iv(&self.iv).with_padding(padding::Scheme::ZeroPadding);
+        let mut ct = cbc.encrypt(key, msg)[16..].to_vec();
+        let d = match msg.len() % 16 {
+            0 => 16,
+            d => d,
+        };
+        let n = ct.len();
+        if n > 16 {
+            ct.drain(n - 32 + d..n - 16);
+        }
+
+        if self.swapped(msg.len()) {
+            Self::swap_tail(&mut ct, d);
+        }
+        [self.iv.to_vec(), ct].concat()
+    }
+
+    fn decrypt(&self, key: &[u8], ct: &[u8]) -> Vec<u8> {
+        if ct.len() < 32 {
+            panic!("Invalid ciphertext, should be prefixed with a 16-byte iv and at least one block");
+        }
+        let (iv, ct) = ct.split_at(16);
+        let d = match ct.len() % 16 {
+            0 => 16,
+            d => d,
+        };
+        let mut ct = ct.to_vec();
+        if self.swapped(ct.len()) {
+            Self::unswap_tail(&mut ct, d);
+        }
+
+        // recover the stolen bytes of C[n-1] from D(k, C[n]) = (P[n]* || 0) ^ C[n-1]
+        let n = ct.len();
+        if n > 16 {
+            let z = AES_128_ECB::decrypt_block(key, &ct[n - 16..]);
+            ct.splice(n - 16..n - 16, z[d..].iter().cloned());
+        }
+        let cbc = AES_128_CBC::from_iv(iv).with_padding(padding::Scheme::NoPadding);
+        let mut pt = cbc.decrypt(key, &[iv, &ct[..]].concat());
+        pt.truncate(n);
+        pt
+    }
+
+    fn try_decrypt(&self, key: &[u8], ct: &[u8]) -> Result<Vec<u8>, padding::PaddingError> {
+        // the IV and at least one block
+        if ct.len() < 32 {
+            return Err(padding::PaddingError::InvalidBlockSize);
+        }
+        Ok(self.decrypt(key, ct))
+    }
+
+    fn iv(&self) -> Vec<u8> {
+        self.iv.to_vec()
+    }
+}
+
+impl IvCipher for AES_128_CBC_CTS {
+    fn iv_len(&self) -> usize {
+        16
+    }
+
+    fn with_iv(&self, iv: &[u8]) -> AES_128_CBC_CTS {
+        AES_128_CBC_CTS::from_iv(iv, self.variant)
+    }
+
+    fn prepends_iv(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cts_correctness() {
+        let key = b"i am pied piper!".to_vec();
+        let msg = b"Privacy is necessary for an open society in the electronic age".to_vec();
+        for variant in [Variant::CS1, Variant::CS2, Variant::CS3].iter() {
+            let cipher = AES_128_CBC_CTS::new(*variant);
+            for len in [16, 17, 31, 32, 48, 62].iter() {
+                let ct = cipher.encrypt(&key, &msg[..*len]);
+                assert_eq!(ct.len(), 16 + len);
+                assert_eq!(cipher.decrypt(&key, &ct), msg[..*len].to_vec());
+            }
+        }
+    }
+
+    #[test]
+    fn cts_variants() {
+        let key = b"i am pied piper!".to_vec();
+        let iv = b"yellow submarine".to_vec();
+        let msg = b"Privacy is necessary for an open society".to_vec(); // 40 bytes
+        let cs1 = AES_128_CBC_CTS::from_iv(&iv, Variant::CS1).encrypt(&key, &msg);
+        let cs2 = AES_128_CBC_CTS::from_iv(&iv, Variant::CS2).encrypt(&key, &msg);
+        let cs3 = AES_128_CBC_CTS::from_iv(&iv, Variant::CS3).encrypt(&key, &msg);
+
+        // the leading blocks are plain CBC, only the tail differs
+        let cbc = AES_128_CBC::from_iv(&iv).encrypt(&key, &msg);
+        assert_eq!(cs1[..32], cbc[..32]);
+        assert_eq!(cs2, cs3);
+        assert_eq!(cs1[32..40], cs3[48..]);
+        assert_eq!(cs1[40..], cs3[32..48]);
+
+        // block-aligned: CS1 and CS2 are plain CBC without padding
+        let aligned = &msg[..32];
+        let cbc = AES_128_CBC::from_iv(&iv).encrypt(&key, aligned);
+        let cs2 = AES_128_CBC_CTS::from_iv(&iv, Variant::CS2).encrypt(&key, aligned);
+        let cs3 = AES_128_CBC_CTS::from_iv(&iv, Variant::CS3).encrypt(&key, aligned);
+        assert_eq!(cs2, cbc[..48].to_vec());
+        assert_eq!(cs3[16..32], cbc[32..48]);
+    }
+}
@@ -1,24 +1,88 @@
-use super::Cipher;
-use openssl::symm::{self, Cipher as SslCipher};
+use super::{
+    padding::{self, Padding},
+    Cipher, IvCipher,
+};
+use openssl::symm::{Cipher as SslCipher, Crypter as SslCrypter, Mode};
 
 #[allow(non_camel_case_types)]
 #[derive(Default)]
-pub struct AES_128_ECB {}
+pub struct AES_128_ECB {
+    padding: padding::Scheme,
+}
 
 impl AES_128_ECB {
     pub fn new() -> AES_128_ECB {
-        AES_128_ECB {}
+        AES_128_ECB {
+            padding: padding::Scheme::default(),
+        }
+    }
+
+    /// Replace the default PKCS#7 padding scheme
+    pub fn with_padding(mut self, padding: padding::Scheme) -> AES_128_ECB {
+        self.padding = padding;
+        self
+    }
+
+    /// Raw AES-128 encryption of a single 16-byte block, without any padding.
+    /// Building block for modes implemented on top of AES (CTR, GCM, etc.)
+    pub fn encrypt_block(key: &[u8], block: &[u8]) -> Vec<u8> {
+        if block.len() != 16 {
+            panic!("Invalid block, not 128-bit block");
+        }
+        Self::process_blocks(key, block, Mode::Encrypt)
+    }
+
+    /// Raw AES-128 decryption of a single 16-byte block, without any padding.
+    pub fn decrypt_block(key: &[u8], block: &[u8]) -> Vec<u8> {
+        if block.len() != 16 {
+            panic!("Invalid block, not 128-bit block");
+        }
+        Self::process_blocks(key, block, Mode::Decrypt)
+    }
+
+    // AES on each 16-byte block independently, `blocks` should be a multiple of the block size
+    fn process_blocks(key: &[u8], blocks: &[u8], mode: Mode) -> Vec<u8> {
+        let mut crypter = SslCrypter::new(SslCipher::aes_128_ecb(), mode, key, None).unwrap();
+        crypter.pad(false);
+
+        let mut out = vec![0; blocks.len() + 16];
+        let mut count = crypter.update(blocks, &mut out).unwrap();
+        count += crypter.finalize(&mut out[count..]).unwrap();
+        out.truncate(count);
+        out
     }
 }
 
 impl Cipher for AES_128_ECB {
     // NOTE: ideally the msg shall be read-only `&[u8]`, but that would mismatch the Cipher trait
     fn encrypt(&self, key: &[u8], msg: &[u8]) -> Vec<u8> {
-        symm::encrypt(SslCipher::aes_128_ecb(), key, None, msg).unwrap()
+        match self.padding.pad(msg, 16) {
+            Ok(padded_msg) => Self::process_blocks(key, &padded_msg, Mode::Encrypt),
+            Err(e) => panic!("failed to add padding: {}", e),
+        }
     }
 
+    // NOTE: returns an empty plaintext on malformed ciphertext or invalid padding
     fn decrypt(&self, key: &[u8], ct: &[u8]) -> Vec<u8> {
-        symm::decrypt(SslCipher::aes_128_ecb(), key, None, &ct[..]).unwrap_or_default()
+        self.try_decrypt(key, ct).unwrap_or_default()
+    }
+
+    fn try_decrypt(&self, key: &[u8], ct: &[u8]) -> Result<Vec<u8>, padding::PaddingError> {
+        if ct.is_empty() || !ct.len().is_multiple_of(16) {
+            return Err(padding::PaddingError::InvalidBlockSize);
+        }
+        let pt = Self::process_blocks(key, ct, Mode::Decrypt);
+        self.padding.unpad(&pt, 16)
+    }
+}
+
+impl IvCipher for AES_128_ECB {
+    fn iv_len(&self) -> usize {
+        0
+    }
+
+    fn with_iv(&self, _iv: &[u8]) -> AES_128_ECB {
+        AES_128_ECB::new().with_padding(self.padding)
     }
 }
 
@@ -0,0 +1,278 @@
+use super::{ctr, ecb::AES_128_ECB, ghash, random_bytes_array, Cipher, IvCipher};
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum GcmError {
+    InvalidLength,
+    InvalidTag,
+}
+
+impl fmt::Display for GcmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            GcmError::InvalidLength => write!(f, "Ciphertext shorter than the authentication tag"),
+            GcmError::InvalidTag => write!(f, "Invalid authentication tag"),
+        }
+    }
+}
+impl Error for GcmError {}
+
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone)]
+pub struct AES_128_GCM {
+    nonce: Vec<u8>,
+    tag_len: usize,
+}
+
+impl Default for AES_128_GCM {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AES_128_GCM {
+    /// Instantiate a new `AES_128_GCM` cipher with a random 96-bit nonce and a full 128-bit tag.
+    pub fn new() -> AES_128_GCM {
+        let mut nonce = [0; 12];
+        random_bytes_array(&mut nonce);
+        AES_128_GCM {
+            nonce: nonce.to_vec(),
+            tag_len: 16,
+        }
+    }
+
+    /// Instantiate with a chosen nonce, 96-bit is recommended, other lengths are GHASH-ed into J0
+    pub fn new_with_nonce(nonce: &[u8]) -> AES_128_GCM {
+        if nonce.is_empty() {
+            panic!("Invalid nonce, should not be empty");
+        }
+        AES_128_GCM {
+            nonce: nonce.to_vec(),
+            tag_len: 16,
+        }
+    }
+
+    /// Truncate the authentication tag to `tag_len` bytes, allowed values are 4, 8 and 12~16
+    pub fn with_tag_len(mut self, tag_len: usize) -> AES_128_GCM {
+        if !(tag_len == 4 || tag_len == 8 || (12..=16).contains(&tag_len)) {
+            panic!("Invalid tag length, should be one of 4, 8, 12, 13, 14, 15, 16 bytes");
+        }
+        self.tag_len = tag_len;
+        self
+    }
+
+    pub fn nonce(&self) -> &[u8] {
+        &self.nonce
+    }
+
+    pub fn tag_len(&self) -> usize {
+        self.tag_len
+    }
+
+    /// Returns the GHASH authentication key H = E(k, 0^128)
+    pub fn auth_key(key: &[u8]) -> u128 {
+        ghash::to_element(&AES_128_ECB::encrypt_block(key, &[0; 16]))
+    }
+
+    /// Pre-counter block J0 derived from the nonce
+    fn pre_counter(&self, h: u128) -> Vec<u8> {
+        if self.nonce.len() == 12 {
+            [&self.nonce[..], &[0, 0, 0, 1]].concat()
+        } else {
+            ghash::from_element(ghash::ghash(h, &[], &self.nonce)).to_vec()
+        }
+    }
+
+    /// Computes the full (untruncated) tag: GHASH_H(A, C) ^ E(k, J0)
+    fn compute_tag(key: &[u8], h: u128, j0: &[u8], aad: &[u8], ct: &[u8]) -> Vec<u8> {
+        let s = ghash::ghash(h, aad, ct);
+        ctr::apply_keystream(key, &ghash::from_element(s), |_| j0.to_vec())
+    }
+
+    /// Authenticated encryption of `msg` with associated data `aad`, returns ct || tag
+    pub fn seal(&self, key: &[u8], aad: &[u8], msg: &[u8]) -> Vec<u8> {
+        if key.len() != 16 {
+            panic!("Invalid key length, should be 16 bytes");
+        }
+        let h = Self::auth_key(key);
+        let j0 = self.pre_counter(h);
+        let ct = ctr::apply_keystream(key, msg, |i| ghash::inc32(&j0, i as u32 + 1));
+
+        let mut tag = Self::compute_tag(key, h, &j0, aad, &ct);
+        tag.truncate(self.tag_len);
+        [ct, tag].concat()
+    }
+
+    /// Authenticated decryption of ct || tag, the tag is verified before any decryption
+    pub fn open(&self, key: &[u8], aad: &[u8], ct: &[u8]) -> Result<Vec<u8>, GcmError> {
+        if key.len() != 16 {
+            panic!("Invalid key length, should be 16 bytes");
+        }
+        if ct.len() < self.tag_len {
+            return Err(GcmError::InvalidLength);
+        }
+        let (ct, tag) = ct.split_at(ct.len() - self.tag_len);
+
+        let h = Self::auth_key(key);
+        let j0 = self.pre_counter(h);
+        let expected_tag = Self::compute_tag(key, h, &j0, aad, ct);
+        if !constant_time_eq(&expected_tag[..self.tag_len], tag) {
+            return Err(GcmError::InvalidTag);
+        }
+
+        Ok(ctr::apply_keystream(key, ct, |i| ghash::inc32(&j0, i as u32 + 1)))
+    }
+}
+
+impl Cipher for AES_128_GCM {
+    fn encrypt(&self, key: &[u8], msg: &[u8]) -> Vec<u8> {
+        self.seal(key, &[], msg)
+    }
+
+    // NOTE: an empty plaintext is returned if the tag fails to verify, use `open` to tell them apart
+    fn decrypt(&self, key: &[u8], ct: &[u8]) -> Vec<u8> {
+        self.open(key, &[], ct).unwrap_or_default()
+    }
+
+    fn iv(&self) -> Vec<u8> {
+        self.nonce.clone()
+    }
+}
+
+impl IvCipher for AES_128_GCM {
+    fn iv_len(&self) -> usize {
+        self.nonce.len()
+    }
+
+    fn with_iv(&self, iv: &[u8]) -> AES_128_GCM {
+        AES_128_GCM::new_with_nonce(iv).with_tag_len(self.tag_len)
+    }
+}
+
+/// Compare two byte slices without short-circuiting on the first mismatch
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b.iter()).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use encoding::hex::hexstr_to_bytes as hex;
+
+    // (key, plaintext, aad, nonce, ciphertext, tag)
+    // source: Test Case 1~6 in "The Galois/Counter Mode of Operation (GCM)", McGrew and Viega
+    fn nist_test_vectors() -> Vec<[&'static str; 6]> {
+        let k = "feffe9928665731c6d6a8f9467308308";
+        let p = "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a72\
+                 1c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b391aafd255";
+        let p_short = &p[..120];
+        let a = "feedfacedeadbeeffeedfacedeadbeefabaddad2";
+        vec![
+            [
+                "00000000000000000000000000000000",
+                "",
+                "",
+                "000000000000000000000000",
+                "",
+                "58e2fccefa7e3061367f1d57a4e7455a",
+            ],
+            [
+                "00000000000000000000000000000000",
+                "00000000000000000000000000000000",
+                "",
+                "000000000000000000000000",
+                "0388dace60b6a392f328c2b971b2fe78",
+                "ab6e47d42cec13bdf53a67b21257bddf",
+            ],
+            [
+                k,
+                p,
+                "",
+                "cafebabefacedbaddecaf888",
+                "42831ec2217774244b7221b784d0d49ce3aa212f2c02a4e035c17e2329aca12e\
+                 21d514b25466931c7d8f6a5aac84aa051ba30b396a0aac973d58e091473f5985",
+                "4d5c2af327cd64a62cf35abd2ba6fab4",
+            ],
+            [
+                k,
+                p_short,
+                a,
+                "cafebabefacedbaddecaf888",
+                "42831ec2217774244b7221b784d0d49ce3aa212f2c02a4e035c17e2329aca12e\
+                 21d514b25466931c7d8f6a5aac84aa051ba30b396a0aac973d58e091",
+                "5bc94fbc3221a5db94fae95ae7121a47",
+            ],
+            [
+                k,
+                p_short,
+                a,
+                "cafebabefacedbad",
+                "61353b4c2806934a777ff51fa22a4755699b2a714fcdc6f83766e5f97b6c7423\
+                 73806900e49f24b22b097544d4896b424989b5e1ebac0f07c23f4598",
+                "3612d2e79e3b0785561be14aaca2fccb",
+            ],
+            [
+                k,
+                p_short,
+                a,
+                "9313225df88406e555909c5aff5269aa6a7a9538534f7da1e4c303d2a318a728\
+                 c3c0c95156809539fcf0e2429a6b525416aedbf5a0de6a57a637b39b",
+                "8ce24998625615b603a033aca13fb894be9112a5c3a211a8ba262a3cca7e2ca7\
+                 01e4a9a4fba43c90ccdcb281d48c7c6fd62875d2aca417034c34aee5",
+                "619cc5aefffe0bfa462af43c1699d050",
+            ],
+        ]
+    }
+
+    #[test]
+    fn gcm_nist_vectors() {
+        for [k, p, a, iv, c, t] in nist_test_vectors().iter() {
+            let (key, pt, aad) = (hex(k).unwrap(), hex(p).unwrap(), hex(a).unwrap());
+            let cipher = AES_128_GCM::new_with_nonce(&hex(iv).unwrap());
+            let expected = [hex(c).unwrap(), hex(t).unwrap()].concat();
+
+            assert_eq!(cipher.seal(&key, &aad, &pt), expected);
+            assert_eq!(cipher.open(&key, &aad, &expected).unwrap(), pt);
+        }
+    }
+
+    #[test]
+    fn gcm_rejects_forgery() {
+        let cipher = AES_128_GCM::new();
+        let key = b"i am pied piper!".to_vec();
+        let mut ct = cipher.seal(&key, b"header", b"Privacy is necessary");
+
+        assert_eq!(cipher.open(&key, b"footer", &ct), Err(GcmError::InvalidTag));
+        ct[0] ^= 1;
+        assert_eq!(cipher.open(&key, b"header", &ct), Err(GcmError::InvalidTag));
+        assert_eq!(
+            cipher.open(&key, b"header", &ct[..8]),
+            Err(GcmError::InvalidLength)
+        );
+    }
+
+    #[test]
+    fn gcm_truncated_tag() {
+        let cipher = AES_128_GCM::new().with_tag_len(12);
+        let full = AES_128_GCM::new_with_nonce(cipher.nonce());
+        let key = b"i am pied piper!".to_vec();
+        let msg = b"Privacy".to_vec();
+
+        let ct = cipher.encrypt(&key, &msg);
+        assert_eq!(ct.len(), msg.len() + 12);
+        assert_eq!(ct[..], full.encrypt(&key, &msg)[..msg.len() + 12]);
+        assert_eq!(cipher.decrypt(&key, &ct), msg);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid key length")]
+    fn gcm_open_checks_key_length() {
+        let cipher = AES_128_GCM::new();
+        let ct = cipher.seal(b"i am pied piper!", b"", b"Privacy");
+        let _ = cipher.open(b"i am pied piper", b"", &ct);
+    }
+}
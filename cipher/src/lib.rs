@@ -1,20 +1,76 @@
 #![deny(clippy::all)]
+pub mod aead;
+pub mod builder;
 pub mod cbc;
+pub mod cfb;
+pub mod chacha20;
 pub mod ctr;
+pub mod cts;
 pub mod ecb;
+pub mod gcm;
+pub mod ghash;
+pub mod ofb;
 pub mod padding;
+pub mod pcbc;
+pub mod rc4;
+pub mod xts;
 
-#[derive(Debug)]
+pub use builder::{CipherBuilder, KeyedCipher};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Mode {
     ECB,
     CBC,
     CTR,
+    GCM,
+    /// CFB with 1-bit feedback
+    CFB1,
+    /// CFB with 8-bit feedback
+    CFB8,
+    /// CFB with full 128-bit block feedback
+    CFB128,
+    OFB,
+    PCBC,
+    /// XTS takes a 32-byte key, the first half for data and the second half for the tweak
+    XTS,
+    /// CBC with ciphertext stealing (CS3 variant)
+    CTS,
 }
 
 /// Represents a cipher
 pub trait Cipher {
     fn encrypt(&self, key: &[u8], msg: &[u8]) -> Vec<u8>;
     fn decrypt(&self, key: &[u8], ct: &[u8]) -> Vec<u8>;
+    /// Returns the IV/nonce (or tweak) the cipher is instantiated with, empty for ECB
+    fn iv(&self) -> Vec<u8> {
+        vec![]
+    }
+    /// Like `decrypt`, but reports an invalid padding instead of panicking (or returning empty)
+    fn try_decrypt(&self, key: &[u8], ct: &[u8]) -> Result<Vec<u8>, padding::PaddingError> {
+        Ok(self.decrypt(key, ct))
+    }
+}
+
+/// A cipher which can be re-instantiated under another IV/nonce (a tweak for XTS), so that
+/// authenticated compositions encrypt every message under a fresh one
+pub trait IvCipher: Cipher + Sized {
+    /// Length of the IV/nonce in bytes, 0 for ECB
+    fn iv_len(&self) -> usize;
+    /// The same cipher (padding, segment size...) under `iv`
+    fn with_iv(&self, iv: &[u8]) -> Self;
+    /// Whether ciphertexts already start with the IV (CBC, PCBC, CFB, OFB, CTS)
+    fn prepends_iv(&self) -> bool {
+        false
+    }
+}
+
+/// Represents a stream cipher, encryption and decryption both XOR the message with the keystream
+pub trait StreamCipher {
+    /// Returns the first `len` bytes of the keystream under `key`
+    fn key_stream(&self, key: &[u8], len: usize) -> Vec<u8>;
+    fn apply_key_stream(&self, key: &[u8], msg: &[u8]) -> Vec<u8> {
+        xor::xor(&self.key_stream(key, msg.len()), msg).unwrap()
+    }
 }
 
 /// Instantiate a new cipher provided a specific mode and default initialization vector/nonce
@@ -23,6 +79,14 @@ pub fn new(mode: Mode) -> Box<dyn Cipher> {
         Mode::CBC => Box::from(cbc::AES_128_CBC::new()),
         Mode::ECB => Box::from(ecb::AES_128_ECB::new()),
         Mode::CTR => Box::from(ctr::AES_128_CTR::new()),
+        Mode::GCM => Box::from(gcm::AES_128_GCM::new()),
+        Mode::CFB1 => Box::from(cfb::AES_128_CFB::new(1)),
+        Mode::CFB8 => Box::from(cfb::AES_128_CFB::new(8)),
+        Mode::CFB128 => Box::from(cfb::AES_128_CFB::new(128)),
+        Mode::OFB => Box::from(ofb::AES_128_OFB::new()),
+        Mode::PCBC => Box::from(pcbc::AES_128_PCBC::new()),
+        Mode::XTS => Box::from(xts::AES_128_XTS::new()),
+        Mode::CTS => Box::from(cts::AES_128_CBC_CTS::new(cts::Variant::CS3)),
     }
 }
 
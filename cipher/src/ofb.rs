@@ -0,0 +1,109 @@
+use super::{ecb::AES_128_ECB, padding, random_bytes_array, Cipher, IvCipher};
+use std::convert::TryInto;
+
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Default)]
+pub struct AES_128_OFB {
+    iv: [u8; 16],
+}
+
+impl AES_128_OFB {
+    /// Instantiate a new `AES_128_OFB` cipher with a random `iv`.
+    pub fn new() -> AES_128_OFB {
+        let mut iv = [0; 16];
+        random_bytes_array(&mut iv);
+        AES_128_OFB { iv }
+    }
+
+    pub fn from_iv(iv: &[u8]) -> AES_128_OFB {
+        if iv.len() != 16 {
+            panic!("Invalid iv value, should be a [u8; 16]");
+        }
+        AES_128_OFB {
+            iv: iv.try_into().unwrap(),
+        }
+    }
+
+    /// Keystream O_i = E(k, O_{i-1}) with O_0 = iv, independent of the plaintext
+    pub fn key_stream(&self, key: &[u8], len: usize) -> Vec<u8> {
+        let mut key_stream: Vec<u8> = vec![];
+        let mut last = self.iv.to_vec();
+        while key_stream.len() < len {
+            last = AES_128_ECB::encrypt_block(key, &last);
+            key_stream.extend_from_slice(&last);
+        }
+        key_stream.truncate(len);
+        key_stream
+    }
+}
+
+impl Cipher for AES_128_OFB {
+    fn encrypt(&self, key: &[u8], msg: &[u8]) -> Vec<u8> {
+        let ct = xor::xor(&self.key_stream(key, msg.len()), msg).unwrap();
+        [self.iv.to_vec(), ct].concat()
+    }
+
+    fn decrypt(&self, key: &[u8], ct: &[u8]) -> Vec<u8> {
+        if ct.len() < 16 {
+            panic!("Invalid ciphertext, should be prefixed with a 16-byte iv");
+        }
+        let cipher = Self::from_iv(&ct[..16]);
+        xor::xor(&cipher.key_stream(key, ct.len() - 16), &ct[16..]).unwrap()
+    }
+
+    fn try_decrypt(&self, key: &[u8], ct: &[u8]) -> Result<Vec<u8>, padding::PaddingError> {
+        if ct.len() < 16 {
+            return Err(padding::PaddingError::InvalidBlockSize);
+        }
+        Ok(self.decrypt(key, ct))
+    }
+
+    fn iv(&self) -> Vec<u8> {
+        self.iv.to_vec()
+    }
+}
+
+impl IvCipher for AES_128_OFB {
+    fn iv_len(&self) -> usize {
+        16
+    }
+
+    fn with_iv(&self, iv: &[u8]) -> AES_128_OFB {
+        AES_128_OFB::from_iv(iv)
+    }
+
+    fn prepends_iv(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use openssl::symm::{self, Cipher as SslCipher};
+
+    #[test]
+    fn ofb_correctness() {
+        let cipher = AES_128_OFB::new();
+        let msg = b"Privacy is necessary for an open society in the electronic age".to_vec();
+        let key = b"i am pied piper!".to_vec();
+
+        let ct = cipher.encrypt(&key, &msg);
+        let expected = symm::encrypt(SslCipher::aes_128_ofb(), &key, Some(&ct[..16]), &msg).unwrap();
+        assert_eq!(ct[16..], expected[..]);
+        assert_eq!(cipher.decrypt(&key, &ct), msg);
+    }
+
+    #[test]
+    fn ofb_keystream_reuse() {
+        // reusing an iv leaks the XOR of the plaintexts
+        let cipher = AES_128_OFB::new();
+        let key = b"i am pied piper!".to_vec();
+        let (msg1, msg2) = (b"attack at dawn!!".to_vec(), b"retreat at dusk!".to_vec());
+        let (ct1, ct2) = (cipher.encrypt(&key, &msg1), cipher.encrypt(&key, &msg2));
+        assert_eq!(
+            xor::xor(&ct1[16..], &ct2[16..]).unwrap(),
+            xor::xor(&msg1, &msg2).unwrap()
+        );
+    }
+}
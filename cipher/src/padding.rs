@@ -1,4 +1,4 @@
-use super::into_blocks;
+use super::{into_blocks, random_bytes_array};
 use std::error::Error;
 use std::fmt;
 
@@ -11,7 +11,7 @@ pub enum PaddingError {
 impl fmt::Display for PaddingError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match *self {
-            PaddingError::InvalidPadding => write!(f, "Invalid PKCS#7 padding"),
+            PaddingError::InvalidPadding => write!(f, "Invalid padding"),
             PaddingError::InvalidBlockSize => write!(f, "Some blocks are not of the block size"),
         }
     }
@@ -19,11 +19,186 @@ impl fmt::Display for PaddingError {
 impl Error for PaddingError {
     fn description(&self) -> &str {
         match *self {
-            PaddingError::InvalidPadding => "Invalid PKCS#7 padding",
+            PaddingError::InvalidPadding => "Invalid padding",
             PaddingError::InvalidBlockSize => "Some blocks are not of the block size",
         }
     }
 }
+/// A padding scheme on flat buffers of any block size
+pub trait Padding {
+    /// Returns `msg` padded to a multiple of `block_size`
+    fn pad(&self, msg: &[u8], block_size: u8) -> Result<Vec<u8>, PaddingError>;
+    /// Returns `pt` with its trailing padding stripped
+    fn unpad(&self, pt: &[u8], block_size: u8) -> Result<Vec<u8>, PaddingError>;
+    /// Validate padding of a decrypted ciphertext
+    fn is_valid(&self, pt: &[u8], block_size: u8) -> bool {
+        self.unpad(pt, block_size).is_ok()
+    }
+}
+
+/// PKCS#7: n bytes of value n
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Pkcs7;
+
+/// ANSI X.923: n-1 zero bytes followed by the byte n
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AnsiX923;
+
+/// ISO 10126: n-1 random bytes followed by the byte n
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Iso10126;
+
+/// ISO/IEC 7816-4: a single 0x80 byte followed by zero bytes
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Iso7816;
+
+/// Zero padding, only applied to incomplete blocks and ambiguous on trailing zeros in the message
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ZeroPadding;
+
+// number of padding bytes to append, always in [1, block_size]
+fn pad_len(msg: &[u8], block_size: u8) -> Result<usize, PaddingError> {
+    if block_size == 0 {
+        return Err(PaddingError::InvalidBlockSize);
+    }
+    Ok(block_size as usize - msg.len() % block_size as usize)
+}
+
+// precondition of all unpad: non-empty and a multiple of block size
+fn check_unpad_len(pt: &[u8], block_size: u8) -> Result<(), PaddingError> {
+    if block_size == 0 || pt.is_empty() || !pt.len().is_multiple_of(block_size as usize) {
+        return Err(PaddingError::InvalidBlockSize);
+    }
+    Ok(())
+}
+
+// Shared by the schemes ending with a length byte, `check` validates the filler bytes
+fn unpad_length_byte<F>(pt: &[u8], block_size: u8, check: F) -> Result<Vec<u8>, PaddingError>
+where
+    F: Fn(&[u8], u8) -> bool,
+{
+    check_unpad_len(pt, block_size)?;
+    let n = pt[pt.len() - 1];
+    if n == 0 || n > block_size {
+        return Err(PaddingError::InvalidPadding);
+    }
+    let (msg, padding) = pt.split_at(pt.len() - n as usize);
+    if !check(&padding[..padding.len() - 1], n) {
+        return Err(PaddingError::InvalidPadding);
+    }
+    Ok(msg.to_vec())
+}
+
+impl Padding for Pkcs7 {
+    fn pad(&self, msg: &[u8], block_size: u8) -> Result<Vec<u8>, PaddingError> {
+        let n = pad_len(msg, block_size)?;
+        Ok([msg, &vec![n as u8; n]].concat())
+    }
+
+    fn unpad(&self, pt: &[u8], block_size: u8) -> Result<Vec<u8>, PaddingError> {
+        unpad_length_byte(pt, block_size, |filler, n| filler.iter().all(|b| *b == n))
+    }
+}
+
+impl Padding for AnsiX923 {
+    fn pad(&self, msg: &[u8], block_size: u8) -> Result<Vec<u8>, PaddingError> {
+        let n = pad_len(msg, block_size)?;
+        Ok([msg, &vec![0; n - 1], &[n as u8]].concat())
+    }
+
+    fn unpad(&self, pt: &[u8], block_size: u8) -> Result<Vec<u8>, PaddingError> {
+        unpad_length_byte(pt, block_size, |filler, _| filler.iter().all(|b| *b == 0))
+    }
+}
+
+impl Padding for Iso10126 {
+    fn pad(&self, msg: &[u8], block_size: u8) -> Result<Vec<u8>, PaddingError> {
+        let n = pad_len(msg, block_size)?;
+        let mut filler = vec![0; n - 1];
+        random_bytes_array(&mut filler);
+        Ok([msg, &filler, &[n as u8]].concat())
+    }
+
+    fn unpad(&self, pt: &[u8], block_size: u8) -> Result<Vec<u8>, PaddingError> {
+        unpad_length_byte(pt, block_size, |_, _| true)
+    }
+}
+
+impl Padding for Iso7816 {
+    fn pad(&self, msg: &[u8], block_size: u8) -> Result<Vec<u8>, PaddingError> {
+        let n = pad_len(msg, block_size)?;
+        Ok([msg, &[0x80], &vec![0; n - 1]].concat())
+    }
+
+    fn unpad(&self, pt: &[u8], block_size: u8) -> Result<Vec<u8>, PaddingError> {
+        check_unpad_len(pt, block_size)?;
+        // the marker must be within the last block
+        let last_block = &pt[pt.len() - block_size as usize..];
+        match last_block.iter().rposition(|b| *b != 0) {
+            Some(i) if last_block[i] == 0x80 => Ok(pt[..pt.len() - block_size as usize + i].to_vec()),
+            _ => Err(PaddingError::InvalidPadding),
+        }
+    }
+}
+
+impl Padding for ZeroPadding {
+    fn pad(&self, msg: &[u8], block_size: u8) -> Result<Vec<u8>, PaddingError> {
+        let n = pad_len(msg, block_size)?;
+        Ok([msg, &vec![0; n % block_size as usize]].concat())
+    }
+
+    fn unpad(&self, pt: &[u8], block_size: u8) -> Result<Vec<u8>, PaddingError> {
+        if block_size == 0 || !pt.len().is_multiple_of(block_size as usize) {
+            return Err(PaddingError::InvalidBlockSize);
+        }
+        let len = pt.iter().rposition(|b| *b != 0).map_or(0, |i| i + 1);
+        Ok(pt[..len].to_vec())
+    }
+}
+
+/// Padding scheme applied by block cipher modes before encryption
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Scheme {
+    #[default]
+    Pkcs7,
+    AnsiX923,
+    Iso10126,
+    Iso7816,
+    ZeroPadding,
+    /// No padding, the message must be an exact multiple of the block size
+    NoPadding,
+}
+
+impl Padding for Scheme {
+    fn pad(&self, msg: &[u8], block_size: u8) -> Result<Vec<u8>, PaddingError> {
+        match self {
+            Scheme::Pkcs7 => Pkcs7.pad(msg, block_size),
+            Scheme::AnsiX923 => AnsiX923.pad(msg, block_size),
+            Scheme::Iso10126 => Iso10126.pad(msg, block_size),
+            Scheme::Iso7816 => Iso7816.pad(msg, block_size),
+            Scheme::ZeroPadding => ZeroPadding.pad(msg, block_size),
+            Scheme::NoPadding => {
+                check_unpad_len(msg, block_size)?;
+                Ok(msg.to_vec())
+            }
+        }
+    }
+
+    fn unpad(&self, pt: &[u8], block_size: u8) -> Result<Vec<u8>, PaddingError> {
+        match self {
+            Scheme::Pkcs7 => Pkcs7.unpad(pt, block_size),
+            Scheme::AnsiX923 => AnsiX923.unpad(pt, block_size),
+            Scheme::Iso10126 => Iso10126.unpad(pt, block_size),
+            Scheme::Iso7816 => Iso7816.unpad(pt, block_size),
+            Scheme::ZeroPadding => ZeroPadding.unpad(pt, block_size),
+            Scheme::NoPadding => {
+                check_unpad_len(pt, block_size)?;
+                Ok(pt.to_vec())
+            }
+        }
+    }
+}
+
 pub fn add(blocks: &mut Vec<Vec<u8>>, size: u8) -> Result<(), PaddingError> {
     if !is_valid_nonpad(blocks, size) {
         return Err(PaddingError::InvalidBlockSize);
@@ -61,9 +236,12 @@ pub fn validate_padding(pt: &[u8], block_size: u8) -> bool {
 
 // @dev: this is internal core logic to validate padding with a 2D vector parameter
 fn is_valid_padding(blocks: &[Vec<u8>], size: u8) -> bool {
-    let pad_len: u8 = *blocks.last().unwrap().last().unwrap();
+    let pad_len: u8 = match blocks.last().and_then(|block| block.last()) {
+        Some(pad_len) => *pad_len,
+        None => return false,
+    };
 
-    if !is_exact_multiple(&blocks, size) || pad_len == 0 || pad_len > 16 {
+    if !is_exact_multiple(&blocks, size) || pad_len == 0 || pad_len > size {
         return false;
     }
 
@@ -73,14 +251,11 @@ fn is_valid_padding(blocks: &[Vec<u8>], size: u8) -> bool {
             return false;
         }
     }
-    if last_block.pop() == Some(pad_len) {
-        return false;
-    }
     true
 }
 
 fn is_valid_nonpad(blocks: &[Vec<u8>], size: u8) -> bool {
-    for block in blocks.iter().take(blocks.len() - 1) {
+    for block in blocks.iter().take(blocks.len().saturating_sub(1)) {
         if block.len() != size as usize {
             return false;
         }
@@ -127,5 +302,54 @@ mod tests {
         assert!(!validate_padding(&b"yellow submarine\x00".to_vec(), 16));
         assert!(!validate_padding(&b"ICE ICE BABY\x03\x03\x03".to_vec(), 16));
         assert!(!validate_padding(&b"ICE ICE BABY".to_vec(), 16));
+        // the byte before the padding may equal the padding length
+        let padded = Pkcs7.pad(b"ICE ICE BABY\x03", 16).unwrap();
+        assert_eq!(padded, b"ICE ICE BABY\x03\x03\x03\x03");
+        assert!(validate_padding(&padded, 16));
+        let mut blocks = into_blocks(&padded, 16);
+        assert!(remove(&mut blocks, 16).is_ok());
+        assert_eq!(blocks[0], b"ICE ICE BABY\x03");
+    }
+
+    #[test]
+    fn padding_schemes() {
+        let msg = b"YELLOW SUBMARINE".to_vec();
+        let cases: Vec<(Box<dyn Padding>, &[u8])> = vec![
+            (Box::new(Pkcs7), b"\x04\x04\x04\x04"),
+            (Box::new(AnsiX923), b"\x00\x00\x00\x04"),
+            (Box::new(Iso7816), b"\x80\x00\x00\x00"),
+            (Box::new(ZeroPadding), b"\x00\x00\x00\x00"),
+        ];
+        for (scheme, expected) in cases.iter() {
+            let padded = scheme.pad(&msg, 20).unwrap();
+            assert_eq!(padded, [&msg[..], expected].concat());
+            assert_eq!(scheme.unpad(&padded, 20).unwrap(), msg);
+            assert!(!scheme.is_valid(&msg[..15], 20));
+        }
+
+        let padded = Iso10126.pad(&msg, 20).unwrap();
+        assert_eq!(padded.len(), 20);
+        assert_eq!(padded[19], 4);
+        assert_eq!(Iso10126.unpad(&padded, 20).unwrap(), msg);
+
+        // a full block of padding is added on block-aligned messages
+        assert_eq!(Pkcs7.pad(&msg, 8).unwrap().len(), 24);
+        assert_eq!(Iso7816.pad(&msg, 8).unwrap().len(), 24);
+        assert_eq!(ZeroPadding.pad(&msg, 8).unwrap().len(), 16);
+    }
+
+    #[test]
+    fn invalid_padding() {
+        assert!(!Pkcs7.is_valid(&[], 16));
+        assert!(!validate_padding(&[], 16));
+        assert!(!Pkcs7.is_valid(b"ICE ICE BABY\x00\x00\x00\x00", 16));
+        assert!(!Pkcs7.is_valid(b"ICE ICE BABY\x05\x05\x05\x05", 16));
+        assert!(!AnsiX923.is_valid(b"ICE ICE BABY\x00\x01\x00\x04", 16));
+        assert!(!Iso7816.is_valid(b"ICE ICE BABY\x00\x00\x00\x00", 16));
+        assert!(!Iso7816.is_valid(b"ICE ICE BABY\x80\x00\x00\x01", 16));
+        // padding longer than 16 bytes is valid for larger block sizes
+        let padded = Pkcs7.pad(b"ICE", 32).unwrap();
+        assert!(validate_padding(&padded, 32));
+        assert!(Pkcs7.is_valid(&padded, 32));
     }
 }
@@ -0,0 +1,143 @@
+use super::{
+    ecb::AES_128_ECB,
+    from_blocks, into_blocks,
+    padding::{self, Padding},
+    random_bytes_array, Cipher, IvCipher,
+};
+use std::convert::TryInto;
+
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Default)]
+pub struct AES_128_PCBC {
+    iv: [u8; 16],
+    padding: padding::Scheme,
+}
+
+impl AES_128_PCBC {
+    /// Instantiate a new `AES_128_PCBC` cipher with a random `iv`.
+    pub fn new() -> AES_128_PCBC {
+        let mut iv = [0; 16];
+        random_bytes_array(&mut iv);
+        Self::from_iv(&iv)
+    }
+
+    pub fn from_iv(iv: &[u8]) -> AES_128_PCBC {
+        if iv.len() != 16 {
+            panic!("Invalid iv value, should be a [u8; 16]");
+        }
+        AES_128_PCBC {
+            iv: iv.try_into().unwrap(),
+            padding: padding::Scheme::default(),
+        }
+    }
+
+    /// Replace the default PKCS#7 padding scheme
+    pub fn with_padding(mut self, padding: padding::Scheme) -> AES_128_PCBC {
+        self.padding = padding;
+        self
+    }
+}
+
+impl Cipher for AES_128_PCBC {
+    // c[i] = E(k, m[i] ^ m[i-1] ^ c[i-1]), where m[0] ^ c[0] = iv
+    fn encrypt(&self, key: &[u8], msg: &[u8]) -> Vec<u8> {
+        let msg_blocks = match self.padding.pad(msg, 16) {
+            Ok(padded_msg) => into_blocks(&padded_msg, 16),
+            Err(e) => panic!("failed to add padding: {}", e),
+        };
+
+        let mut ct: Vec<Vec<u8>> = vec![];
+        let mut last = self.iv.to_vec();
+        for block in msg_blocks.iter() {
+            let c = AES_128_ECB::encrypt_block(key, &xor::xor(&last, block).unwrap());
+            last = xor::xor(block, &c).unwrap();
+            ct.push(c);
+        }
+        [self.iv.to_vec(), from_blocks(&ct)].concat()
+    }
+
+    fn decrypt(&self, key: &[u8], ct: &[u8]) -> Vec<u8> {
+        match self.try_decrypt(key, ct) {
+            Ok(pt) => pt,
+            Err(e) => panic!("failed to remove padding: {}", e),
+        }
+    }
+
+    // m[i] = D(k, c[i]) ^ m[i-1] ^ c[i-1]
+    fn try_decrypt(&self, key: &[u8], ct: &[u8]) -> Result<Vec<u8>, padding::PaddingError> {
+        if ct.len() < 16 || !ct.len().is_multiple_of(16) {
+            return Err(padding::PaddingError::InvalidBlockSize);
+        }
+        let mut iv_ct_blocks = into_blocks(ct, 16);
+        let ct_blocks = iv_ct_blocks.split_off(1);
+
+        let mut pt: Vec<Vec<u8>> = vec![];
+        let mut last = iv_ct_blocks[0].clone();
+        for block in ct_blocks.iter() {
+            let m = xor::xor(&AES_128_ECB::decrypt_block(key, block), &last).unwrap();
+            last = xor::xor(&m, block).unwrap();
+            pt.push(m);
+        }
+        self.padding.unpad(&from_blocks(&pt), 16)
+    }
+
+    fn iv(&self) -> Vec<u8> {
+        self.iv.to_vec()
+    }
+}
+
+impl IvCipher for AES_128_PCBC {
+    fn iv_len(&self) -> usize {
+        16
+    }
+
+    fn with_iv(&self, iv: &[u8]) -> AES_128_PCBC {
+        AES_128_PCBC::from_iv(iv).with_padding(self.padding)
+    }
+
+    fn prepends_iv(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pcbc_correctness() {
+        let cipher = AES_128_PCBC::new();
+        let msg1 = b"Privacy".to_vec();
+        let msg2 = b"Privacy is necessary for an open society in the electronic age".to_vec();
+        let key = b"i am pied piper!".to_vec();
+
+        assert_eq!(cipher.decrypt(&key, &cipher.encrypt(&key, &msg1)), msg1);
+        assert_eq!(cipher.decrypt(&key, &cipher.encrypt(&key, &msg2)), msg2);
+    }
+
+    #[test]
+    fn pcbc_block_swap() {
+        // known weakness: swapping two adjacent ciphertext blocks leaves all later blocks intact
+        let cipher = AES_128_PCBC::new();
+        let msg = b"Privacy is necessary for an open society in the electronic age".to_vec();
+        let key = b"i am pied piper!".to_vec();
+
+        let mut ct = cipher.encrypt(&key, &msg);
+        let (c1, c2) = (ct[16..32].to_vec(), ct[32..48].to_vec());
+        ct.splice(16..48, [c2, c1].concat());
+        let pt = cipher.decrypt(&key, &ct);
+        assert_ne!(pt[..32], msg[..32]);
+        assert_eq!(pt[32..], msg[32..]);
+    }
+
+    #[test]
+    fn malformed_ciphertext() {
+        let cipher = AES_128_PCBC::new();
+        let key = b"i am pied piper!".to_vec();
+        let ct = cipher.encrypt(&key, b"Privacy is necessary");
+        let invalid = Err(padding::PaddingError::InvalidBlockSize);
+        assert_eq!(cipher.try_decrypt(&key, &ct[..ct.len() - 1]), invalid);
+        assert_eq!(cipher.try_decrypt(&key, &[]), invalid);
+        assert_eq!(cipher.try_decrypt(&key, &ct[..15]), invalid);
+    }
+}
@@ -0,0 +1,106 @@
+use super::{Cipher, StreamCipher};
+
+/// RC4 stream cipher, optionally discarding the first `drop` keystream bytes (RC4-drop[N]) which
+/// carry the strongest biases.
+#[derive(Debug, Clone, Default)]
+pub struct RC4 {
+    drop: usize,
+}
+
+impl RC4 {
+    pub fn new() -> RC4 {
+        RC4 { drop: 0 }
+    }
+
+    /// RC4-drop[n], e.g. 768 or 3072 as recommended against the initial biases
+    pub fn with_drop(drop: usize) -> RC4 {
+        RC4 { drop }
+    }
+
+    pub fn drop(&self) -> usize {
+        self.drop
+    }
+}
+
+impl StreamCipher for RC4 {
+    fn key_stream(&self, key: &[u8], len: usize) -> Vec<u8> {
+        let mut state = Rc4State::new(key);
+        (0..self.drop).for_each(|_| {
+            state.next_byte();
+        });
+        (0..len).map(|_| state.next_byte()).collect()
+    }
+}
+
+impl Cipher for RC4 {
+    fn encrypt(&self, key: &[u8], msg: &[u8]) -> Vec<u8> {
+        self.apply_key_stream(key, msg)
+    }
+
+    fn decrypt(&self, key: &[u8], ct: &[u8]) -> Vec<u8> {
+        self.apply_key_stream(key, ct)
+    }
+}
+
+/// Internal state (permutation S and indices i, j) of RC4 after the key schedule
+#[derive(Clone)]
+pub struct Rc4State {
+    s: [u8; 256],
+    i: u8,
+    j: u8,
+}
+
+impl Rc4State {
+    /// Key-scheduling algorithm (KSA), the key should be 1 to 256 bytes
+    pub fn new(key: &[u8]) -> Rc4State {
+        if key.is_empty() || key.len() > 256 {
+            panic!("Invalid key length, should be 1 to 256 bytes");
+        }
+        let mut s = [0; 256];
+        for (i, item) in s.iter_mut().enumerate() {
+            *item = i as u8;
+        }
+        let mut j: u8 = 0;
+        for i in 0..256 {
+            j = j.wrapping_add(s[i]).wrapping_add(key[i % key.len()]);
+            s.swap(i, j as usize);
+        }
+        Rc4State { s, i: 0, j: 0 }
+    }
+
+    /// Pseudo-random generation algorithm (PRGA), outputs the next keystream byte
+    pub fn next_byte(&mut self) -> u8 {
+        self.i = self.i.wrapping_add(1);
+        self.j = self.j.wrapping_add(self.s[self.i as usize]);
+        self.s.swap(self.i as usize, self.j as usize);
+        self.s[self.s[self.i as usize].wrapping_add(self.s[self.j as usize]) as usize]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use encoding::hex::hexstr_to_bytes as hex;
+
+    #[test]
+    fn rc4_test_vectors() {
+        // source: RFC 6229, 40-bit key, keystream at offsets 0 and 16
+        let key_stream = RC4::new().key_stream(&hex("0102030405").unwrap(), 32);
+        assert_eq!(
+            key_stream,
+            hex("b2396305f03dc027ccc3524a0a1118a86982944f18fc82d589c403a47a0d0919").unwrap()
+        );
+
+        let cipher = RC4::new();
+        let ct = cipher.encrypt(b"Key", b"Plaintext");
+        assert_eq!(ct, hex("bbf316e8d940af0ad3").unwrap());
+        assert_eq!(cipher.decrypt(b"Key", &ct), b"Plaintext".to_vec());
+    }
+
+    #[test]
+    fn rc4_drop() {
+        let key = b"i am pied piper!".to_vec();
+        let full = RC4::new().key_stream(&key, 768 + 64);
+        assert_eq!(RC4::with_drop(768).key_stream(&key, 64), full[768..].to_vec());
+    }
+}
@@ -0,0 +1,149 @@
+use super::{ecb::AES_128_ECB, padding, Cipher, IvCipher};
+use std::convert::TryInto;
+
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Default)]
+pub struct AES_128_XTS {
+    sector: u128,
+}
+
+impl AES_128_XTS {
+    /// Instantiate a new `AES_128_XTS` cipher with a random sector number as tweak
+    pub fn new() -> AES_128_XTS {
+        AES_128_XTS {
+            sector: rand::random::<u128>(),
+        }
+    }
+
+    /// Instantiate for a given data unit (sector) number
+    pub fn from_sector(sector: u128) -> AES_128_XTS {
+        AES_128_XTS { sector }
+    }
+
+    // the 256-bit key is split into the data key and the tweak key
+    fn split_key(key: &[u8]) -> (&[u8], &[u8]) {
+        if key.len() != 32 {
+            panic!("Invalid key length, XTS takes two 16-byte keys");
+        }
+        key.split_at(16)
+    }
+
+    // multiply the tweak by the primitive element α in GF(2^128), little-endian convention
+    fn mul_alpha(tweak: &mut [u8]) {
+        let mut carry = 0;
+        for byte in tweak.iter_mut() {
+            let next_carry = *byte >> 7;
+            *byte = (*byte << 1) | carry;
+            carry = next_carry;
+        }
+        if carry == 1 {
+            tweak[0] ^= 0x87;
+        }
+    }
+
+    // XEX of a single block: E(k, m ^ T) ^ T or D(k, c ^ T) ^ T
+    fn xex(key: &[u8], block: &[u8], tweak: &[u8], encrypt: bool) -> Vec<u8> {
+        let input = xor::xor(block, tweak).unwrap();
+        let output = if encrypt {
+            AES_128_ECB::encrypt_block(key, &input)
+        } else {
+            AES_128_ECB::decrypt_block(key, &input)
+        };
+        xor::xor(&output, tweak).unwrap()
+    }
+
+    // Returns the tweak for each (full or partial) block of a data unit of `len` bytes
+    fn tweaks(&self, key2: &[u8], len: usize) -> Vec<Vec<u8>> {
+        let mut tweak = AES_128_ECB::encrypt_block(key2, &self.sector.to_le_bytes());
+        let mut tweaks = vec![];
+        for _ in 0..len.div_ceil(16) {
+            tweaks.push(tweak.clone());
+            Self::mul_alpha(&mut tweak);
+        }
+        tweaks
+    }
+
+    // Applies XEX block by block, with ciphertext stealing for a trailing partial block
+    fn process(&self, key: &[u8], input: &[u8], encrypt: bool) -> Vec<u8> {
+        if input.len() < 16 {
+            panic!("Invalid data unit, XTS requires at least one full block");
+        }
+        let (key1, key2) = Self::split_key(key);
+        let tweaks = self.tweaks(key2, input.len());
+        let blocks: Vec<&[u8]> = input.chunks(16).collect();
+        let m = blocks.len();
+        let partial = input.len() % 16;
+
+        let full = if partial == 0 { m } else { m - 2 };
+        let mut output: Vec<u8> = vec![];
+        for j in 0..full {
+            output.extend(Self::xex(key1, blocks[j], &tweaks[j], encrypt));
+        }
+
+        if partial != 0 {
+            // when decrypting, the second last block was processed under the last tweak
+            let (first, second) = if encrypt { (m - 2, m - 1) } else { (m - 1, m - 2) };
+            let cc = Self::xex(key1, blocks[m - 2], &tweaks[first], encrypt);
+            let pp = [blocks[m - 1], &cc[partial..]].concat();
+            output.extend(Self::xex(key1, &pp, &tweaks[second], encrypt));
+            output.extend_from_slice(&cc[..partial]);
+        }
+        output
+    }
+}
+
+impl Cipher for AES_128_XTS {
+    fn encrypt(&self, key: &[u8], msg: &[u8]) -> Vec<u8> {
+        self.process(key, msg, true)
+    }
+
+    fn decrypt(&self, key: &[u8], ct: &[u8]) -> Vec<u8> {
+        self.process(key, ct, false)
+    }
+
+    fn try_decrypt(&self, key: &[u8], ct: &[u8]) -> Result<Vec<u8>, padding::PaddingError> {
+        // at least one block to steal from
+        if ct.len() < 16 {
+            return Err(padding::PaddingError::InvalidBlockSize);
+        }
+        Ok(self.decrypt(key, ct))
+    }
+
+    fn iv(&self) -> Vec<u8> {
+        self.sector.to_le_bytes().to_vec()
+    }
+}
+
+impl IvCipher for AES_128_XTS {
+    fn iv_len(&self) -> usize {
+        16
+    }
+
+    fn with_iv(&self, iv: &[u8]) -> AES_128_XTS {
+        AES_128_XTS::from_sector(u128::from_le_bytes(
+            iv.try_into().expect("Invalid tweak length, should be 16 bytes"),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use openssl::symm::{self, Cipher as SslCipher};
+
+    #[test]
+    fn xts_correctness() {
+        let cipher = AES_128_XTS::from_sector(0x1234);
+        let key = b"i am pied piper!yellow submarine".to_vec();
+        let msg = b"Privacy is necessary for an open society in the electronic age".to_vec();
+
+        // full blocks and ciphertext stealing
+        for len in [16, 32, 37, 61].iter() {
+            let ct = cipher.encrypt(&key, &msg[..*len]);
+            let tweak = 0x1234u128.to_le_bytes();
+            let expected = symm::encrypt(SslCipher::aes_128_xts(), &key, Some(&tweak), &msg[..*len]);
+            assert_eq!(ct, expected.unwrap());
+            assert_eq!(cipher.decrypt(&key, &ct), msg[..*len].to_vec());
+        }
+    }
+}
@@ -0,0 +1,397 @@
+// Toy Merkle-Damgård hashes with a truncated chaining value, and generic birthday collision search.
+//
+// With a `b`-bit chaining value, a collision of the compression function is expected after about
+// 2^(b/2) calls. A hash table finds it in that many calls but as much memory; cycle finding on
+// `x -> compress(state, block(x))` (Brent) needs constant memory for a few times more calls, and
+// distinguished points sit in between while parallelizing (van Oorschot and Wiener, 1999).
+use cipher::ecb::AES_128_ECB;
+use sha1::digest::generic_array::GenericArray;
+use std::cell::Cell;
+use std::collections::HashMap;
+
+const SHA1_IV: [u32; 5] = [0x6745_2301, 0xEFCD_AB89, 0x98BA_DCFE, 0x1032_5476, 0xC3D2_E1F0];
+const MD4_IV: [u32; 4] = [0x6745_2301, 0xEFCD_AB89, 0x98BA_DCFE, 0x1032_5476];
+
+/// A compression function on a chaining value of up to 64 bits
+pub trait Compression {
+    /// Block size in bytes
+    fn block_size(&self) -> usize;
+
+    /// Process `block` from the chaining value `state`, the output is truncated by the caller
+    fn compress(&self, state: u64, block: &[u8]) -> u64;
+}
+
+/// The compression of Cryptopals set 7: AES-128 of the block, keyed by the chaining value
+/// (little-endian, zero padded)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AesCompression;
+
+impl Compression for AesCompression {
+    fn block_size(&self) -> usize {
+        16
+    }
+
+    fn compress(&self, state: u64, block: &[u8]) -> u64 {
+        let mut key = [0u8; 16];
+        key[..8].copy_from_slice(&state.to_le_bytes());
+        let ct = AES_128_ECB::encrypt_block(&key, block);
+        u64::from_le_bytes([ct[0], ct[1], ct[2], ct[3], ct[4], ct[5], ct[6], ct[7]])
+    }
+}
+
+/// SHA-1 compression, with the chaining value XORed into the first two words of the IV
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Sha1Compression;
+
+impl Compression for Sha1Compression {
+    fn block_size(&self) -> usize {
+        64
+    }
+
+    fn compress(&self, state: u64, block: &[u8]) -> u64 {
+        let mut h = SHA1_IV;
+        h[0] ^= state as u32;
+        h[1] ^= (state >> 32) as u32;
+        sha1::compress(&mut h, GenericArray::from_slice(block));
+        h[0] as u64 | (h[1] as u64) << 32
+    }
+}
+
+/// MD4 compression, with the chaining value XORed into the first two words of the IV
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Md4Compression;
+
+impl Compression for Md4Compression {
+    fn block_size(&self) -> usize {
+        64
+    }
+
+    fn compress(&self, state: u64, block: &[u8]) -> u64 {
+        let mut h = MD4_IV;
+        h[0] ^= state as u32;
+        h[1] ^= (state >> 32) as u32;
+        md4::compress(&mut h, GenericArray::from_slice(block));
+        h[0] as u64 | (h[1] as u64) << 32
+    }
+}
+
+/// A Merkle-Damgård hash over `C` whose chaining value (and digest) is truncated to `bits` bits,
+/// counting compression calls
+#[derive(Debug)]
+pub struct TruncatedHash<C> {
+    compression: C,
+    bits: u32,
+    iv: u64,
+    calls: Cell<u64>,
+}
+
+impl<C: Compression> TruncatedHash<C> {
+    pub fn new(compression: C, bits: u32) -> TruncatedHash<C> {
+        if !(8..=64).contains(&bits) {
+            panic!("Invalid width, should be between 8 and 64 bits");
+        }
+        TruncatedHash {
+            compression,
+            bits,
+            iv: 0,
+            calls: Cell::new(0),
+        }
+    }
+
+    /// Replace the default (all zero) initial chaining value
+    pub fn with_iv(mut self, iv: u64) -> TruncatedHash<C> {
+        self.iv = iv & self.mask();
+        self
+    }
+
+    pub fn bits(&self) -> u32 {
+        self.bits
+    }
+
+    pub fn iv(&self) -> u64 {
+        self.iv
+    }
+
+    pub fn block_size(&self) -> usize {
+        self.compression.block_size()
+    }
+
+    fn mask(&self) -> u64 {
+        u64::MAX >> (64 - self.bits)
+    }
+
+    /// Number of compression calls so far
+    pub fn calls(&self) -> u64 {
+        self.calls.get()
+    }
+
+    pub fn reset_calls(&self) {
+        self.calls.set(0);
+    }
+
+    /// One truncated compression, `block` must be exactly one block
+    pub fn compress(&self, state: u64, block: &[u8]) -> u64 {
+        if block.len() != self.block_size() {
+            panic!("Invalid block, should be {} bytes", self.block_size());
+        }
+        self.calls.set(self.calls.get() + 1);
+        self.compression.compress(state, block) & self.mask()
+    }
+
+    /// Compress whole blocks from `state`, without padding
+    pub fn iterate(&self, state: u64, blocks: &[u8]) -> u64 {
+        if !blocks.len().is_multiple_of(self.block_size()) {
+            panic!("Invalid input, not a multiple of the block size");
+        }
+        blocks
+            .chunks(self.block_size())
+            .fold(state, |state, block| self.compress(state, block))
+    }
+
+    /// The padding appended to a message of `len` bytes: 0x80, zeros, then the 64-bit big-endian
+    /// length in bits
+    pub fn padding(&self, len: usize) -> Vec<u8> {
+        let bs = self.block_size();
+        let zeros = (2 * bs - len % bs - 9) % bs;
+        let mut padding = vec![0x80];
+        padding.resize(1 + zeros, 0);
+        padding.extend_from_slice(&(len as u64 * 8).to_be_bytes());
+        padding
+    }
+
+    /// Digest of `msg`, padded, from the initial chaining value
+    pub fn hash(&self, msg: &[u8]) -> u64 {
+        self.iterate(self.iv, &[msg, &self.padding(msg.len())].concat())
+    }
+
+    /// Deterministic block encoding `x`, for the search with `seed`
+    fn block(&self, seed: u64, x: u64) -> Vec<u8> {
+        let mut block = vec![0; self.block_size()];
+        block[..8].copy_from_slice(&seed.to_le_bytes());
+        block[8..16].copy_from_slice(&x.to_le_bytes());
+        block
+    }
+}
+
+/// How to search for a collision
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    /// Store every digest until one repeats
+    Table,
+    /// Brent's cycle finding on the walk `x -> compress(state, block(x))`, in constant memory
+    Brent,
+    /// Walks ending on digests whose `n` low bits are zero, storing only those end points
+    DistinguishedPoints(u32),
+}
+
+/// Two distinct blocks compressing to the same digest from `state`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Collision {
+    pub state: u64,
+    pub blocks: (Vec<u8>, Vec<u8>),
+    pub digest: u64,
+    /// Compression calls made by the search
+    pub calls: u64,
+    /// Digests held in memory at the end of the search
+    pub stored: usize,
+}
+
+/// Search a single-block collision of `hash` from the chaining value `state`. The search is
+/// deterministic in `seed`, and different seeds give different collisions.
+pub fn find_collision<C: Compression>(
+    hash: &TruncatedHash<C>,
+    state: u64,
+    method: Method,
+    seed: u64,
+) -> Collision {
+    let start = hash.calls();
+    let ((x, y), stored) = match method {
+        Method::Table => table(hash, state, seed),
+        Method::Brent => (brent(hash, state, seed), 0),
+        Method::DistinguishedPoints(n) => distinguished_points(hash, state, seed, n),
+    };
+    let blocks = (hash.block(seed, x), hash.block(seed, y));
+    let digest = hash.compress(state, &blocks.0);
+    Collision {
+        state,
+        blocks,
+        digest,
+        calls: hash.calls() - start - 1,
+        stored,
+    }
+}
+
+// returns the colliding walk inputs `x != y`
+fn table<C: Compression>(hash: &TruncatedHash<C>, state: u64, seed: u64) -> ((u64, u64), usize) {
+    let mut seen = HashMap::new();
+    for x in 0.. {
+        let digest = hash.compress(state, &hash.block(seed, x));
+        if let Some(&y) = seen.get(&digest) {
+            return ((x, y), seen.len());
+        }
+        seen.insert(digest, x);
+    }
+    unreachable!()
+}
+
+fn brent<C: Compression>(hash: &TruncatedHash<C>, state: u64, seed: u64) -> (u64, u64) {
+    let f = |x| hash.compress(state, &hash.block(seed, x));
+    let mask = hash.mask();
+    for x0 in 0.. {
+        let x0 = x0 & mask;
+        // cycle length `lambda`
+        let (mut power, mut lambda) = (1u64, 1u64);
+        let (mut tortoise, mut hare) = (x0, f(x0));
+        while tortoise != hare {
+            if power == lambda {
+                tortoise = hare;
+                power *= 2;
+                lambda = 0;
+            }
+            hare = f(hare);
+            lambda += 1;
+        }
+
+        // walking from `x0` and `lambda` steps ahead, the points before the two meet collide
+        let (mut tortoise, mut hare) = (x0, x0);
+        for _ in 0..lambda {
+            hare = f(hare);
+        }
+        if tortoise == hare {
+            // `x0` is on the cycle, there is no tail to collide with
+            continue;
+        }
+        loop {
+            let (t, h) = (f(tortoise), f(hare));
+            if t == h {
+                return (tortoise, hare);
+            }
+            tortoise = t;
+            hare = h;
+        }
+    }
+    unreachable!()
+}
+
+fn distinguished_points<C: Compression>(
+    hash: &TruncatedHash<C>,
+    state: u64,
+    seed: u64,
+    n: u32,
+) -> ((u64, u64), usize) {
+    if n >= hash.bits() {
+        panic!("Invalid distinguishing property, should be below the width");
+    }
+    let f = |x| hash.compress(state, &hash.block(seed, x));
+    let distinguished = |x: u64| x.trailing_zeros() >= n;
+    // walks longer than this are likely stuck in a cycle without distinguished points
+    let max_len = 20u64 << n;
+    // distinguished point -> (start, length) of the walk that reached it
+    let mut ends: HashMap<u64, (u64, u64)> = HashMap::new();
+
+    for start in 0.. {
+        let (mut x, mut len) = (f(start), 1);
+        while !distinguished(x) && len < max_len {
+            x = f(x);
+            len += 1;
+        }
+        if !distinguished(x) {
+            continue;
+        }
+        let (other, other_len) = match ends.get(&x) {
+            Some(&end) => end,
+            None => {
+                ends.insert(x, (start, len));
+                continue;
+            }
+        };
+
+        // align both walks at the same distance from the point, then step until they merge
+        let (mut a, mut b) = (start, other);
+        let (mut a_len, mut b_len) = (len, other_len);
+        while a_len > b_len {
+            a = f(a);
+            a_len -= 1;
+        }
+        while b_len > a_len {
+            b = f(b);
+            b_len -= 1;
+        }
+        if a == b {
+            // one walk started on the other
+            continue;
+        }
+        loop {
+            let (fa, fb) = (f(a), f(b));
+            if fa == fb {
+                return ((a, b), ends.len());
+            }
+            a = fa;
+            b = fb;
+        }
+    }
+    unreachable!()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check<C: Compression>(hash: &TruncatedHash<C>, collision: &Collision) {
+        let (b1, b2) = &collision.blocks;
+        assert_ne!(b1, b2);
+        assert_eq!(hash.compress(collision.state, b1), collision.digest);
+        assert_eq!(hash.compress(collision.state, b2), collision.digest);
+        assert!(collision.digest < 1 << hash.bits());
+    }
+
+    #[test]
+    fn truncated_hash() {
+        let hash = TruncatedHash::new(AesCompression, 16).with_iv(0x1234);
+        for len in 0..40 {
+            assert_eq!((len + hash.padding(len).len()) % 16, 0);
+        }
+        hash.reset_calls();
+        assert!(hash.hash(b"YELLOW SUBMARINE") < 1 << 16);
+        assert_eq!(hash.calls(), 2);
+        assert_eq!(
+            hash.hash(b"YELLOW SUBMARINE"),
+            hash.iterate(0x1234, &[&b"YELLOW SUBMARINE"[..], &hash.padding(16)].concat())
+        );
+
+        let full = TruncatedHash::new(Sha1Compression, 64);
+        let truncated = TruncatedHash::new(Sha1Compression, 24);
+        // a single block is truncated only once, at the end
+        assert_eq!(full.hash(b"abc") & 0xff_ffff, truncated.hash(b"abc"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn width_is_bounded() {
+        TruncatedHash::new(Md4Compression, 65);
+    }
+
+    #[test]
+    fn finds_collisions() {
+        let aes = TruncatedHash::new(AesCompression, 20);
+        let methods = [Method::Table, Method::Brent, Method::DistinguishedPoints(4)];
+        for &method in methods.iter() {
+            let collision = find_collision(&aes, 0xbeef, method, 1);
+            check(&aes, &collision);
+            assert!(collision.calls < 1 << 14);
+        }
+        // a table stores every digest, distinguished points a fraction
+        let table = find_collision(&aes, 0, Method::Table, 2);
+        let dp = find_collision(&aes, 0, Method::DistinguishedPoints(4), 2);
+        assert_eq!(find_collision(&aes, 0, Method::Brent, 2).stored, 0);
+        assert!(dp.stored < table.stored);
+
+        let sha1 = TruncatedHash::new(Sha1Compression, 32);
+        check(
+            &sha1,
+            &find_collision(&sha1, 0, Method::DistinguishedPoints(8), 1),
+        );
+        let md4 = TruncatedHash::new(Md4Compression, 32);
+        check(&md4, &find_collision(&md4, 0, Method::Brent, 1));
+    }
+}
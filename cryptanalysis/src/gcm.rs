@@ -0,0 +1,72 @@
+// "Forbidden attack" on AES-GCM: recover the GHASH key H from two messages sharing a nonce.
+//
+// Under the same (key, nonce), T_i = GHASH_H(A_i, C_i) ^ E(k, J0), so T_1 ^ T_2 cancels the mask
+// and H is a root of the polynomial GHASH_H(A_1, C_1) ^ T_1 ^ GHASH_H(A_2, C_2) ^ T_2.
+// NOTE: all ciphertexts are expected as `ct || tag` with a full 16-byte tag.
+use super::gf128::{roots, Poly};
+use cipher::ghash;
+
+const TAG_LEN: usize = 16;
+
+/// Polynomial in H whose evaluation is GHASH_H(A, C) ^ T
+fn tag_poly(aad: &[u8], ct_with_tag: &[u8]) -> Poly {
+    let (ct, tag) = split_tag(ct_with_tag);
+    // GHASH = X_1 * H^m ^ ... ^ X_m * H, the coefficient of H^i is X_{m-i+1}
+    let mut coeffs = vec![ghash::to_element(tag)];
+    coeffs.extend(ghash::blocks(aad, ct).iter().rev());
+    Poly::new(&coeffs)
+}
+
+fn split_tag(ct_with_tag: &[u8]) -> (&[u8], &[u8]) {
+    if ct_with_tag.len() < TAG_LEN {
+        panic!("Invalid ciphertext, should be followed by a 16-byte tag");
+    }
+    ct_with_tag.split_at(ct_with_tag.len() - TAG_LEN)
+}
+
+/// Returns all candidate authentication keys H given two (aad, ct || tag) pairs encrypted under
+/// the same key and nonce. A third message can be used to rule out false candidates.
+pub fn recover_auth_keys(aad1: &[u8], ct1: &[u8], aad2: &[u8], ct2: &[u8]) -> Vec<u128> {
+    let diff = tag_poly(aad1, ct1).add(&tag_poly(aad2, ct2));
+    roots(&diff)
+}
+
+/// Returns the tag mask E(k, J0) given a candidate H and a known (aad, ct || tag) pair
+pub fn tag_mask(h: u128, aad: &[u8], ct_with_tag: &[u8]) -> u128 {
+    let (ct, tag) = split_tag(ct_with_tag);
+    ghash::ghash(h, aad, ct) ^ ghash::to_element(tag)
+}
+
+/// Forge `ct || tag` for an arbitrary (aad, ct) under the reused nonce, given the recovered H and
+/// a known (aad, ct || tag) pair
+pub fn forge(h: u128, known_aad: &[u8], known_ct: &[u8], aad: &[u8], ct: &[u8]) -> Vec<u8> {
+    let tag = ghash::ghash(h, aad, ct) ^ tag_mask(h, known_aad, known_ct);
+    [ct, &ghash::from_element(tag)[..]].concat()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cipher::gcm::AES_128_GCM;
+
+    #[test]
+    fn forbidden_attack() {
+        let key = b"i am pied piper!".to_vec();
+        let gcm = AES_128_GCM::new();
+        let ct1 = gcm.seal(&key, b"header", b"attack at dawn, not at dusk please");
+        let ct2 = gcm.seal(&key, b"", b"retreat at dusk!");
+
+        let candidates = recover_auth_keys(b"header", &ct1, b"", &ct2);
+        assert!(candidates.contains(&AES_128_GCM::auth_key(&key)));
+
+        // a third message disambiguates H, then arbitrary ciphertexts can be authenticated
+        let ct3 = gcm.seal(&key, b"footer", b"third message");
+        let h = *candidates
+            .iter()
+            .find(|h| forge(**h, b"header", &ct1, b"footer", &ct3[..ct3.len() - 16]) == ct3)
+            .unwrap();
+
+        let forged = forge(h, b"header", &ct1, b"admin", b"any bytes I want");
+        assert!(gcm.open(&key, b"admin", &forged).is_ok());
+    }
+}
@@ -0,0 +1,257 @@
+// Arithmetic over GF(2^128) with GCM's bit ordering and polynomials with coefficients in it.
+//
+// Field elements share the representation of `cipher::ghash`, thus the multiplicative identity
+// is `1 << 127` rather than `1`.
+use cipher::ghash::gf_mul;
+
+pub const ZERO: u128 = 0;
+pub const ONE: u128 = 1 << 127;
+
+/// returns a^e in GF(2^128)
+pub fn pow(a: u128, mut e: u128) -> u128 {
+    let mut result = ONE;
+    let mut base = a;
+    while e > 0 {
+        if e & 1 == 1 {
+            result = gf_mul(result, base);
+        }
+        base = gf_mul(base, base);
+        e >>= 1;
+    }
+    result
+}
+
+/// returns a^-1 = a^(2^128 - 2), panics on zero
+pub fn inv(a: u128) -> u128 {
+    if a == ZERO {
+        panic!("Zero has no multiplicative inverse");
+    }
+    pow(a, u128::MAX - 1)
+}
+
+/// Polynomial over GF(2^128), coefficients are stored from the lowest degree to the highest
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Poly {
+    coeffs: Vec<u128>,
+}
+
+impl Poly {
+    /// Instantiate from coefficients, `coeffs[i]` is the coefficient of x^i
+    pub fn new(coeffs: &[u128]) -> Poly {
+        let mut p = Poly {
+            coeffs: coeffs.to_vec(),
+        };
+        p.trim();
+        p
+    }
+
+    pub fn zero() -> Poly {
+        Poly { coeffs: vec![] }
+    }
+
+    pub fn one() -> Poly {
+        Poly { coeffs: vec![ONE] }
+    }
+
+    /// returns the polynomial "x"
+    pub fn x() -> Poly {
+        Poly {
+            coeffs: vec![ZERO, ONE],
+        }
+    }
+
+    pub fn coeffs(&self) -> &[u128] {
+        &self.coeffs
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.coeffs.is_empty()
+    }
+
+    /// Degree of the polynomial, `None` for the zero polynomial
+    pub fn degree(&self) -> Option<usize> {
+        self.coeffs.len().checked_sub(1)
+    }
+
+    pub fn leading_coeff(&self) -> u128 {
+        *self.coeffs.last().unwrap_or(&ZERO)
+    }
+
+    /// Evaluate the polynomial at `a` using Horner's rule
+    pub fn eval(&self, a: u128) -> u128 {
+        self.coeffs.iter().rev().fold(ZERO, |acc, c| gf_mul(acc, a) ^ c)
+    }
+
+    pub fn add(&self, other: &Poly) -> Poly {
+        let mut coeffs = vec![ZERO; self.coeffs.len().max(other.coeffs.len())];
+        for (i, c) in self.coeffs.iter().enumerate() {
+            coeffs[i] ^= c;
+        }
+        for (i, c) in other.coeffs.iter().enumerate() {
+            coeffs[i] ^= c;
+        }
+        Poly::new(&coeffs)
+    }
+
+    pub fn mul(&self, other: &Poly) -> Poly {
+        if self.is_zero() || other.is_zero() {
+            return Poly::zero();
+        }
+        let mut coeffs = vec![ZERO; self.coeffs.len() + other.coeffs.len() - 1];
+        for (i, a) in self.coeffs.iter().enumerate() {
+            for (j, b) in other.coeffs.iter().enumerate() {
+                coeffs[i + j] ^= gf_mul(*a, *b);
+            }
+        }
+        Poly::new(&coeffs)
+    }
+
+    /// Multiply every coefficient by the scalar `a`
+    pub fn scale(&self, a: u128) -> Poly {
+        Poly::new(&self.coeffs.iter().map(|c| gf_mul(*c, a)).collect::<Vec<_>>())
+    }
+
+    /// Scale the polynomial so that its leading coefficient is one
+    pub fn monic(&self) -> Poly {
+        if self.is_zero() {
+            return Poly::zero();
+        }
+        self.scale(inv(self.leading_coeff()))
+    }
+
+    /// Long division, returns (quotient, remainder)
+    pub fn div_rem(&self, divisor: &Poly) -> (Poly, Poly) {
+        let d = divisor.degree().expect("Division by zero polynomial");
+        let lead_inv = inv(divisor.leading_coeff());
+
+        let mut rem = self.coeffs.clone();
+        let mut quot = vec![ZERO; self.coeffs.len().saturating_sub(d)];
+        while rem.len() > d {
+            let shift = rem.len() - 1 - d;
+            let factor = gf_mul(*rem.last().unwrap(), lead_inv);
+            quot[shift] = factor;
+            for (i, c) in divisor.coeffs.iter().enumerate() {
+                rem[shift + i] ^= gf_mul(*c, factor);
+            }
+            rem.pop();
+            while rem.last() == Some(&ZERO) {
+                rem.pop();
+            }
+        }
+        (Poly::new(&quot), Poly::new(&rem))
+    }
+
+    pub fn rem(&self, modulus: &Poly) -> Poly {
+        self.div_rem(modulus).1
+    }
+
+    /// Monic greatest common divisor
+    pub fn gcd(&self, other: &Poly) -> Poly {
+        let mut a = self.clone();
+        let mut b = other.clone();
+        while !b.is_zero() {
+            let r = a.rem(&b);
+            a = b;
+            b = r;
+        }
+        a.monic()
+    }
+
+    fn trim(&mut self) {
+        while self.coeffs.last() == Some(&ZERO) {
+            self.coeffs.pop();
+        }
+    }
+}
+
+/// Returns all distinct roots of `f` in GF(2^128) using Cantor-Zassenhaus
+pub fn roots(f: &Poly) -> Vec<u128> {
+    if f.degree().unwrap_or(0) == 0 {
+        return vec![];
+    }
+    let f = f.monic();
+
+    // x^(2^128) - x vanishes on every field element, thus the gcd retains exactly the distinct
+    // linear factors of f
+    let mut x_pow = Poly::x();
+    for _ in 0..128 {
+        x_pow = x_pow.mul(&x_pow).rem(&f);
+    }
+    let linear = f.gcd(&x_pow.add(&Poly::x()));
+
+    let mut roots = vec![];
+    split_linear(&linear, &mut roots);
+    roots.sort_unstable();
+    roots
+}
+
+// Equal-degree splitting of a product of distinct linear factors.
+// In characteristic 2, the trace map Tr(a*x) = sum (a*x)^(2^i) takes values in {0, 1} at every root,
+// so gcd(g, Tr(a*x) mod g) separates the roots by their trace. Sweeping `a` over the basis
+// {x^0, ..., x^127} is guaranteed to separate any two distinct roots.
+fn split_linear(g: &Poly, roots: &mut Vec<u128>) {
+    match g.degree() {
+        None | Some(0) => {}
+        Some(1) => roots.push(gf_mul(g.coeffs[0], inv(g.coeffs[1]))),
+        Some(d) => {
+            for k in 0..128 {
+                let ax = Poly::new(&[ZERO, 1 << k]).rem(g);
+                let mut term = ax.clone();
+                let mut trace = ax;
+                for _ in 1..128 {
+                    term = term.mul(&term).rem(g);
+                    trace = trace.add(&term);
+                }
+                let factor = g.gcd(&trace);
+                if let Some(fd) = factor.degree() {
+                    if fd > 0 && fd < d {
+                        split_linear(&factor, roots);
+                        split_linear(&g.div_rem(&factor).0, roots);
+                        return;
+                    }
+                }
+            }
+            panic!("Failed to split a product of distinct linear factors, internal bug");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn field_inverse() {
+        for a in [ONE, 1, 0xdeadbeef, 0x66e94bd4ef8a2c3b884cfa59ca342b2e].iter() {
+            assert_eq!(gf_mul(*a, inv(*a)), ONE);
+        }
+    }
+
+    #[test]
+    fn poly_div_rem() {
+        let a = Poly::new(&[3, 5, ONE, 7]);
+        let b = Poly::new(&[11, ONE]);
+        let (q, r) = a.div_rem(&b);
+        assert_eq!(q.mul(&b).add(&r), a);
+        assert!(r.degree().unwrap_or(0) < b.degree().unwrap());
+    }
+
+    #[test]
+    fn find_roots() {
+        let expected: Vec<u128> = vec![0x1234, 0xcafebabe << 64, u128::MAX];
+        // (x - r1)(x - r2)(x - r3)^2 scaled by an arbitrary constant
+        let mut f = Poly::one();
+        for r in expected.iter() {
+            f = f.mul(&Poly::new(&[*r, ONE]));
+        }
+        f = f.mul(&Poly::new(&[*expected.last().unwrap(), ONE])); // repeated root
+        let mut roots = roots(&f.scale(0xabcdef));
+        roots.sort_unstable();
+        let mut sorted = expected.clone();
+        sorted.sort_unstable();
+        assert_eq!(roots, sorted);
+        for r in roots.iter() {
+            assert_eq!(f.eval(*r), ZERO);
+        }
+    }
+}
@@ -0,0 +1,295 @@
+// Length extension attack on Merkle-Damgård hashes.
+//
+// The digest of `secret || message` is the chaining value after its last (padding) block, so the
+// compression can be resumed from it: without knowing the secret, only its length, one can compute
+// the digest of `secret || message || glue || suffix` where `glue` is the original padding.
+use md4::Md4;
+use md5::Md5;
+use sha1::digest::generic_array::GenericArray;
+use sha1::Sha1;
+use sha2::{Sha256, Sha512};
+use std::convert::TryInto;
+use std::marker::PhantomData;
+use std::ops::RangeInclusive;
+
+/// A Merkle-Damgård hash whose digest is its full chaining value
+pub trait MerkleDamgard {
+    /// Block size in bytes
+    const BLOCK_SIZE: usize;
+    /// Digest size in bytes
+    const DIGEST_SIZE: usize;
+    /// Size in bytes of the message length (in bits) ending the padding
+    const LENGTH_SIZE: usize;
+    /// Byte order of the length field and of the state words
+    const BIG_ENDIAN: bool;
+
+    /// Process one block, with the chaining value given (and updated) in its digest encoding
+    fn compress(digest: &mut [u8], block: &[u8]);
+}
+
+impl MerkleDamgard for Sha1 {
+    const BLOCK_SIZE: usize = 64;
+    const DIGEST_SIZE: usize = 20;
+    const LENGTH_SIZE: usize = 8;
+    const BIG_ENDIAN: bool = true;
+
+    fn compress(digest: &mut [u8], block: &[u8]) {
+        let mut state = [0u32; 5];
+        read_u32s(digest, &mut state, true);
+        sha1::compress(&mut state, GenericArray::from_slice(block));
+        write_u32s(&state, digest, true);
+    }
+}
+
+impl MerkleDamgard for Md4 {
+    const BLOCK_SIZE: usize = 64;
+    const DIGEST_SIZE: usize = 16;
+    const LENGTH_SIZE: usize = 8;
+    const BIG_ENDIAN: bool = false;
+
+    fn compress(digest: &mut [u8], block: &[u8]) {
+        let mut state = [0u32; 4];
+        read_u32s(digest, &mut state, false);
+        md4::compress(&mut state, GenericArray::from_slice(block));
+        write_u32s(&state, digest, false);
+    }
+}
+
+impl MerkleDamgard for Md5 {
+    const BLOCK_SIZE: usize = 64;
+    const DIGEST_SIZE: usize = 16;
+    const LENGTH_SIZE: usize = 8;
+    const BIG_ENDIAN: bool = false;
+
+    fn compress(digest: &mut [u8], block: &[u8]) {
+        let mut state = [0u32; 4];
+        read_u32s(digest, &mut state, false);
+        md5::compress(&mut state, GenericArray::from_slice(block));
+        write_u32s(&state, digest, false);
+    }
+}
+
+impl MerkleDamgard for Sha256 {
+    const BLOCK_SIZE: usize = 64;
+    const DIGEST_SIZE: usize = 32;
+    const LENGTH_SIZE: usize = 8;
+    const BIG_ENDIAN: bool = true;
+
+    fn compress(digest: &mut [u8], block: &[u8]) {
+        let mut state = [0u32; 8];
+        read_u32s(digest, &mut state, true);
+        sha2::compress256(&mut state, block.try_into().unwrap());
+        write_u32s(&state, digest, true);
+    }
+}
+
+impl MerkleDamgard for Sha512 {
+    const BLOCK_SIZE: usize = 128;
+    const DIGEST_SIZE: usize = 64;
+    const LENGTH_SIZE: usize = 16;
+    const BIG_ENDIAN: bool = true;
+
+    fn compress(digest: &mut [u8], block: &[u8]) {
+        let mut state = [0u64; 8];
+        for (word, bytes) in state.iter_mut().zip(digest.chunks_exact(8)) {
+            *word = u64::from_be_bytes(bytes.try_into().unwrap());
+        }
+        sha2::compress512(&mut state, block.try_into().unwrap());
+        for (word, bytes) in state.iter().zip(digest.chunks_exact_mut(8)) {
+            bytes.copy_from_slice(&word.to_be_bytes());
+        }
+    }
+}
+
+fn read_u32s(bytes: &[u8], words: &mut [u32], big_endian: bool) {
+    for (word, chunk) in words.iter_mut().zip(bytes.chunks_exact(4)) {
+        let chunk = chunk.try_into().unwrap();
+        *word = if big_endian {
+            u32::from_be_bytes(chunk)
+        } else {
+            u32::from_le_bytes(chunk)
+        };
+    }
+}
+
+fn write_u32s(words: &[u32], bytes: &mut [u8], big_endian: bool) {
+    for (word, chunk) in words.iter().zip(bytes.chunks_exact_mut(4)) {
+        if big_endian {
+            chunk.copy_from_slice(&word.to_be_bytes());
+        } else {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+    }
+}
+
+/// The padding `H` appends to a message of `len` bytes
+pub fn md_padding<H: MerkleDamgard>(len: usize) -> Vec<u8> {
+    let bits = len as u128 * 8;
+    let zeros = (2 * H::BLOCK_SIZE - len % H::BLOCK_SIZE - 1 - H::LENGTH_SIZE) % H::BLOCK_SIZE;
+    let mut padding = vec![0x80];
+    padding.resize(1 + zeros, 0);
+    if H::BIG_ENDIAN {
+        padding.extend_from_slice(&bits.to_be_bytes()[16 - H::LENGTH_SIZE..]);
+    } else {
+        padding.extend_from_slice(&bits.to_le_bytes()[..H::LENGTH_SIZE]);
+    }
+    padding
+}
+
+/// Forges digests of `secret || message || glue || suffix` out of the digest of
+/// `secret || message`
+#[derive(Debug, Clone)]
+pub struct LengthExtension<H> {
+    digest: Vec<u8>,
+    suffix: Vec<u8>,
+    hash: PhantomData<H>,
+}
+
+impl<H: MerkleDamgard> LengthExtension<H> {
+    pub fn new(digest: &[u8], suffix: &[u8]) -> LengthExtension<H> {
+        if digest.len() != H::DIGEST_SIZE {
+            panic!("Invalid digest, should be {} bytes", H::DIGEST_SIZE);
+        }
+        LengthExtension {
+            digest: digest.to_vec(),
+            suffix: suffix.to_vec(),
+            hash: PhantomData,
+        }
+    }
+
+    /// What to append to the original message (`glue || suffix`), given the length of
+    /// `secret || message`
+    pub fn extension(&self, len: usize) -> Vec<u8> {
+        [md_padding::<H>(len), self.suffix.clone()].concat()
+    }
+
+    /// Digest of `secret || message || extension(len)`, given the length of `secret || message`
+    pub fn digest(&self, len: usize) -> Vec<u8> {
+        let processed = len + md_padding::<H>(len).len() + self.suffix.len();
+        let tail = [self.suffix.clone(), md_padding::<H>(processed)].concat();
+        let mut digest = self.digest.clone();
+        for block in tail.chunks(H::BLOCK_SIZE) {
+            H::compress(&mut digest, block);
+        }
+        digest
+    }
+
+    /// The forged message and its digest for a secret of `secret_len` bytes
+    pub fn forge(&self, message: &[u8], secret_len: usize) -> (Vec<u8>, Vec<u8>) {
+        let len = secret_len + message.len();
+        ([message, &self.extension(len)].concat(), self.digest(len))
+    }
+
+    /// `forge` for each candidate secret length, in order
+    pub fn forge_all(&self, message: &[u8], secret_lens: RangeInclusive<usize>) -> Vec<(Vec<u8>, Vec<u8>)> {
+        secret_lens
+            .map(|secret_len| self.forge(message, secret_len))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use blake2::Blake2b;
+    use encoding::hex::{bytes_to_hexstr, hexstr_to_bytes};
+    use sha1::Digest;
+    use sha3::{Sha3_256, Sponge};
+
+    fn forge_and_check<H: MerkleDamgard + Digest>() {
+        let secret = b"YELLOW SUBMARINE, NOW WITH A MUCH LONGER KEY";
+        let message = b"comment1=cooking%20MCs;userdata=foo";
+        let digest = H::digest(&[&secret[..], message].concat());
+
+        let attack = LengthExtension::<H>::new(&digest, b";admin=true");
+        let (forged_msg, forged_digest) = attack.forge(message, secret.len());
+        assert!(forged_msg.ends_with(b";admin=true"));
+        assert_eq!(
+            H::digest(&[&secret[..], &forged_msg].concat()).to_vec(),
+            forged_digest
+        );
+
+        let candidates = attack.forge_all(message, 0..=63);
+        let valid: Vec<usize> = (0..candidates.len())
+            .filter(|&i| {
+                let (msg, digest) = &candidates[i];
+                H::digest(&[&secret[..], msg].concat()).to_vec() == *digest
+            })
+            .collect();
+        assert_eq!(valid, vec![secret.len()]);
+    }
+
+    #[test]
+    fn padding_completes_blocks() {
+        for len in 0..300 {
+            assert_eq!((len + md_padding::<Sha1>(len).len()) % 64, 0);
+            assert_eq!((len + md_padding::<Sha512>(len).len()) % 128, 0);
+        }
+        assert_eq!(md_padding::<Sha1>(3)[57..], [0, 0, 0, 24]);
+        assert_eq!(md_padding::<Md4>(3)[53..57], [24, 0, 0, 0]);
+    }
+
+    #[test]
+    fn sponge_and_blake2_resist() {
+        let secret = b"YELLOW SUBMARINE";
+        let message = b"comment1=cooking%20MCs;userdata=foo";
+        let data = [&secret[..], message].concat();
+
+        // SHA3-256: the digest is 4 of the 25 lanes, resuming with a guessed (zero) capacity
+        // gives the digest of nothing that was actually hashed
+        let tag = Sha3_256::digest(&data);
+        let mut state = [0u64; 25];
+        for (lane, bytes) in state.iter_mut().zip(tag.chunks(8)) {
+            *lane = u64::from_le_bytes(bytes.try_into().unwrap());
+        }
+        let mut sponge = Sponge::from_state(state, 136);
+        sponge.absorb(b";admin=true");
+        sponge.pad(0x06);
+        let mut forged = [0u8; 32];
+        sponge.squeeze(&mut forged);
+        let glue = [0x06].iter().chain([0u8; 83].iter()).chain([0x80].iter());
+        let extended = [&data[..], &glue.cloned().collect::<Vec<u8>>(), b";admin=true"].concat();
+        assert_ne!(Sha3_256::digest(&extended)[..], forged);
+        assert_ne!(
+            Sha3_256::digest(&[&data[..], b";admin=true"].concat())[..],
+            forged
+        );
+
+        // BLAKE2b: the digest is the chaining value after a block flagged as last, compressing
+        // more blocks from it matches no message
+        let tag = Blake2b::digest(&data);
+        let mut h = [0u64; 8];
+        for (word, bytes) in h.iter_mut().zip(tag.chunks(8)) {
+            *word = u64::from_le_bytes(bytes.try_into().unwrap());
+        }
+        let mut block = [0u8; 128];
+        block[..11].copy_from_slice(b";admin=true");
+        blake2::compress_b(&mut h, &block, 128 + 11, true);
+        let extended = [&data[..], &[0u8; 128 - 51][..], b";admin=true"].concat();
+        let forged: Vec<u8> = h.iter().flat_map(|w| w.to_le_bytes().to_vec()).collect();
+        assert_ne!(Blake2b::digest(&extended).to_vec(), forged);
+    }
+
+    #[test]
+    fn md5_extension_known_answer() {
+        // digests of the original and forged messages computed independently (Python's hashlib)
+        let message = b"comment1=cooking%20MCs;userdata=foo";
+        let digest = hexstr_to_bytes("c88ca7da6d3d4c3e76253f3e18d716d6").unwrap();
+        let attack = LengthExtension::<Md5>::new(&digest, b";admin=true");
+        let (forged_msg, forged_digest) = attack.forge(message, 16);
+        assert_eq!(forged_msg.len(), message.len() + 13 + 11);
+        assert_eq!(
+            bytes_to_hexstr(&forged_digest),
+            "d46e5004000433178b5201d72cdb47dc"
+        );
+    }
+
+    #[test]
+    fn length_extension() {
+        forge_and_check::<Sha1>();
+        forge_and_check::<Md4>();
+        forge_and_check::<Md5>();
+        forge_and_check::<Sha256>();
+        forge_and_check::<Sha512>();
+    }
+}
@@ -1,5 +1,13 @@
 #![deny(clippy::all)]
+pub mod birthday;
 pub mod freq_analysis;
+pub mod gcm;
+pub mod gf128;
+pub mod length_extension;
+pub mod multicollision;
+pub mod padding_oracle;
+pub mod password;
+pub mod rc4_bias;
 pub mod vigenere;
 
 // TODO: add precondition contract to check all_equal_length for the input
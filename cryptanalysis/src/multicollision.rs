@@ -0,0 +1,173 @@
+// Joux multicollisions (Joux, 2004) and the collision attack on cascaded hashes `f(m) || g(m)`.
+//
+// Chaining `n` single-block collisions of an iterated hash gives 2^n messages with the same digest
+// for the price of `n` birthday searches. With 2^(b/2) such messages for the cheap `f`, a `b`-bit
+// `g` is expected to collide among them: the cascade is barely stronger than its strongest part.
+use super::birthday::{find_collision, Compression, Method, TruncatedHash};
+use std::collections::HashMap;
+
+/// 2^n messages of `n` blocks with the same chaining value: the i-th message takes, for each
+/// block j, the second block of the j-th pair if bit j of i is set, the first otherwise
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Multicollision {
+    /// The common chaining value after all the blocks
+    pub state: u64,
+    pub pairs: Vec<(Vec<u8>, Vec<u8>)>,
+    /// Compression calls made to find the pairs
+    pub calls: u64,
+}
+
+impl Multicollision {
+    /// An empty multicollision (a single, empty, message) from `state`
+    pub fn new(state: u64) -> Multicollision {
+        Multicollision {
+            state,
+            pairs: vec![],
+            calls: 0,
+        }
+    }
+
+    /// Number of colliding messages
+    pub fn count(&self) -> u64 {
+        1 << self.pairs.len()
+    }
+
+    pub fn message(&self, i: u64) -> Vec<u8> {
+        if i >= self.count() {
+            panic!("Invalid index, only {} messages", self.count());
+        }
+        self.pairs
+            .iter()
+            .enumerate()
+            .flat_map(|(j, (b0, b1))| if (i >> j) & 1 == 1 { b1 } else { b0 }.clone())
+            .collect()
+    }
+
+    /// Double the number of messages, with a collision from the current chaining value
+    pub fn extend<C: Compression>(&mut self, hash: &TruncatedHash<C>, seed: u64) {
+        let collision = find_collision(hash, self.state, Method::Table, seed);
+        self.state = collision.digest;
+        self.pairs.push(collision.blocks);
+        self.calls += collision.calls;
+    }
+}
+
+/// A 2^n-way multicollision of `hash` from its IV, deterministic in `seed`
+pub fn joux<C: Compression>(hash: &TruncatedHash<C>, n: usize, seed: u64) -> Multicollision {
+    let mut multicollision = Multicollision::new(hash.iv());
+    for j in 0..n {
+        multicollision.extend(hash, seed.wrapping_add(j as u64));
+    }
+    multicollision
+}
+
+/// Two messages with the same `f` and `g` digests
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CascadeCollision {
+    pub messages: (Vec<u8>, Vec<u8>),
+    pub digests: (u64, u64),
+    /// Compression calls to `f`, building the multicollision
+    pub f_calls: u64,
+    /// Compression calls to `g`, hashing the colliding messages
+    pub g_calls: u64,
+}
+
+/// Collide `f(m) || g(m)`, where `f` is the cheaper hash: `g` hashes a multicollision of `f` of
+/// 2^(b/2) messages for a `b`-bit `g`, doubled until two of them collide. Both hashes need the
+/// same block size.
+pub fn cascade_collision<F: Compression, G: Compression>(
+    f: &TruncatedHash<F>,
+    g: &TruncatedHash<G>,
+    seed: u64,
+) -> CascadeCollision {
+    if f.block_size() != g.block_size() {
+        panic!("Invalid hashes, should have the same block size");
+    }
+    let (f_start, g_start) = (f.calls(), g.calls());
+
+    let n = g.bits().div_ceil(2) as usize;
+    let mut multicollision = joux(f, n, seed);
+    // `g` chaining values of every message so far, by message index, sharing common prefixes
+    let mut states = vec![g.iv()];
+    for (b0, b1) in multicollision.pairs.iter() {
+        states = extend_states(g, &states, b0, b1);
+    }
+
+    let collision = loop {
+        // all messages have the same length, hence the same padding
+        let padding = g.padding(multicollision.pairs.len() * g.block_size());
+        let mut seen = HashMap::new();
+        let found = states.iter().enumerate().find_map(|(i, &state)| {
+            let digest = g.iterate(state, &padding);
+            seen.insert(digest, i).map(|j| (j as u64, i as u64, digest))
+        });
+        if let Some(collision) = found {
+            break collision;
+        }
+
+        let j = multicollision.pairs.len() as u64;
+        multicollision.extend(f, seed.wrapping_add(j));
+        let (b0, b1) = multicollision.pairs.last().unwrap();
+        states = extend_states(g, &states, b0, b1);
+    };
+
+    let (i, j, g_digest) = collision;
+    let messages = (multicollision.message(i), multicollision.message(j));
+    let f_calls = f.calls() - f_start;
+    let f_digest = f.iterate(multicollision.state, &f.padding(messages.0.len()));
+    CascadeCollision {
+        messages,
+        digests: (f_digest, g_digest),
+        f_calls,
+        g_calls: g.calls() - g_start,
+    }
+}
+
+// chaining values after one more block, the second block giving the upper half of the indices
+fn extend_states<G: Compression>(g: &TruncatedHash<G>, states: &[u64], b0: &[u8], b1: &[u8]) -> Vec<u64> {
+    let mut next: Vec<u64> = states.iter().map(|&s| g.compress(s, b0)).collect();
+    next.extend(states.iter().map(|&s| g.compress(s, b1)));
+    next
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::birthday::{AesCompression, Md4Compression};
+
+    #[test]
+    fn joux_multicollision() {
+        let hash = TruncatedHash::new(AesCompression, 16).with_iv(0xabcd);
+        let multicollision = joux(&hash, 4, 1);
+        assert_eq!(multicollision.count(), 16);
+        assert!(multicollision.calls >= 4);
+
+        let digest = hash.hash(&multicollision.message(0));
+        let messages: Vec<Vec<u8>> = (0..16).map(|i| multicollision.message(i)).collect();
+        for (i, msg) in messages.iter().enumerate() {
+            assert_eq!(msg.len(), 4 * 16);
+            assert_eq!(hash.iterate(hash.iv(), msg), multicollision.state);
+            assert_eq!(hash.hash(msg), digest);
+            assert!(messages[..i].iter().all(|other| other != msg));
+        }
+    }
+
+    #[test]
+    fn cascade() {
+        let f = TruncatedHash::new(AesCompression, 12);
+        let g = TruncatedHash::new(AesCompression, 24).with_iv(0x5eed);
+        let collision = cascade_collision(&f, &g, 1);
+        let (m1, m2) = &collision.messages;
+        assert_ne!(m1, m2);
+        assert_eq!(f.hash(m1), f.hash(m2));
+        assert_eq!(g.hash(m1), g.hash(m2));
+        assert_eq!(collision.digests, (f.hash(m1), g.hash(m1)));
+        // most of the work is hashing the multicollision with `g`
+        assert!(collision.f_calls < collision.g_calls);
+        assert!(collision.g_calls < 1 << 20);
+
+        let md4 = TruncatedHash::new(Md4Compression, 16);
+        let collision = cascade_collision(&TruncatedHash::new(Md4Compression, 8), &md4, 2);
+        assert_eq!(md4.hash(&collision.messages.0), md4.hash(&collision.messages.1));
+    }
+}
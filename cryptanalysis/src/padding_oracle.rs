@@ -0,0 +1,299 @@
+// CBC padding oracle attacks, generalized from the PKCS#7 attack of challenge 17.
+//
+// An oracle takes `iv || ct` and tells whether it decrypts to a validly padded plaintext. By
+// controlling the block preceding a target block C, we learn the intermediate state D(k, C) byte
+// by byte from the end, which decrypts C (XOR with the real preceding block) or lets us choose
+// what C decrypts to (CBC-R).
+use cipher::padding::{Padding, Scheme};
+use std::time::{Duration, Instant};
+
+const BLOCK_SIZE: usize = 16;
+
+// The last `n` bytes of a validly padded block with `n` bytes of padding
+fn pad_pattern(scheme: Scheme, n: usize) -> Vec<u8> {
+    match scheme {
+        Scheme::Pkcs7 | Scheme::AnsiX923 | Scheme::Iso7816 => {
+            let padded = scheme.pad(&vec![0; BLOCK_SIZE - n], BLOCK_SIZE as u8).unwrap();
+            padded[BLOCK_SIZE - n..].to_vec()
+        }
+        _ => panic!(
+            "{:?} can't be attacked byte by byte with a padding oracle",
+            scheme
+        ),
+    }
+}
+
+/// Returns the intermediate state D(k, block) using the padding oracle
+pub fn intermediate<F>(oracle: &F, scheme: Scheme, block: &[u8]) -> Vec<u8>
+where
+    F: Fn(&[u8]) -> bool,
+{
+    let mut inter = vec![0; BLOCK_SIZE];
+    for i in (0..BLOCK_SIZE).rev() {
+        let pattern = pad_pattern(scheme, BLOCK_SIZE - i);
+        let mut forged = vec![0; BLOCK_SIZE];
+        for j in i + 1..BLOCK_SIZE {
+            forged[j] = inter[j] ^ pattern[j - i];
+        }
+
+        let mut found = false;
+        for guess in 0..=255 {
+            forged[i] = guess;
+            if !oracle(&[&forged[..], block].concat()) {
+                continue;
+            }
+            // A false positive relies on bytes before `i` (e.g. "\x02\x02" when looking for
+            // "\x01"), flipping all of them rules it out while a true positive stays valid.
+            let mut flipped = forged.clone();
+            flipped[..i].iter_mut().for_each(|b| *b ^= 0xff);
+            if i == 0 || oracle(&[&flipped[..], block].concat()) {
+                inter[i] = guess ^ pattern[0];
+                found = true;
+                break;
+            }
+        }
+        if !found {
+            panic!("No valid padding found, is the oracle using {:?}?", scheme);
+        }
+    }
+    inter
+}
+
+/// Decrypt `iv || ct` with the padding oracle, returns the plaintext without padding
+pub fn decrypt<F>(oracle: &F, scheme: Scheme, ct: &[u8]) -> Vec<u8>
+where
+    F: Fn(&[u8]) -> bool,
+{
+    if ct.len() < 2 * BLOCK_SIZE || !ct.len().is_multiple_of(BLOCK_SIZE) {
+        panic!("Invalid ciphertext, should be iv || ct in 16-byte blocks");
+    }
+    let mut pt: Vec<u8> = vec![];
+    for blocks in ct.windows(2 * BLOCK_SIZE).step_by(BLOCK_SIZE) {
+        let (prev, block) = blocks.split_at(BLOCK_SIZE);
+        pt.extend(xor::xor(&intermediate(oracle, scheme, block), prev).unwrap());
+    }
+    scheme.unpad(&pt, BLOCK_SIZE as u8).unwrap_or(pt)
+}
+
+/// Against a lenient oracle that only checks the length byte is within [1, 16] (ISO 10126 or ANSI
+/// implementations skipping the filler), returns the last plaintext byte of every block.
+pub fn decrypt_last_bytes<F>(oracle: &F, ct: &[u8]) -> Vec<u8>
+where
+    F: Fn(&[u8]) -> bool,
+{
+    let mut last_bytes = vec![];
+    for blocks in ct.windows(2 * BLOCK_SIZE).step_by(BLOCK_SIZE) {
+        let (prev, block) = blocks.split_at(BLOCK_SIZE);
+        let mut forged = [0; BLOCK_SIZE];
+        let valid: Vec<bool> = (0..=255)
+            .map(|guess| {
+                forged[BLOCK_SIZE - 1] = guess;
+                oracle(&[&forged[..], block].concat())
+            })
+            .collect();
+
+        // the only intermediate byte I such that the guess g is valid iff I ^ g is in [1, 16]
+        let inter = (0..=255u8)
+            .find(|i| (0..=255u8).all(|g| valid[g as usize] == (1..=16).contains(&(i ^ g))))
+            .expect("Oracle is not checking the length byte");
+        last_bytes.push(inter ^ prev[BLOCK_SIZE - 1]);
+    }
+    last_bytes
+}
+
+/// CBC-R: forge `iv || ct` that decrypts to the chosen `pt` (padded with `scheme`) without the
+/// key, each block costs a full intermediate state recovery.
+pub fn cbc_r<F>(oracle: &F, scheme: Scheme, pt: &[u8]) -> Vec<u8>
+where
+    F: Fn(&[u8]) -> bool,
+{
+    let padded = scheme.pad(pt, BLOCK_SIZE as u8).unwrap();
+    // any last block works, then walk backward: C[i-1] = D(k, C[i]) ^ P[i]
+    let mut forged = vec![0; BLOCK_SIZE];
+    for block in padded.rchunks(BLOCK_SIZE) {
+        let inter = intermediate(oracle, scheme, &forged[..BLOCK_SIZE]);
+        forged.splice(0..0, xor::xor(&inter, block).unwrap());
+    }
+    forged
+}
+
+/// Turns an oracle that only leaks through its running time into a boolean padding oracle, e.g. a
+/// MAC-then-encrypt server always returning the same error but only computing the MAC on validly
+/// padded plaintexts.
+pub struct TimingOracle<F>
+where
+    F: Fn(&[u8]),
+{
+    oracle: F,
+    threshold: Duration,
+    samples: usize,
+    clock: Box<dyn Fn() -> Duration>,
+}
+
+impl<F> TimingOracle<F>
+where
+    F: Fn(&[u8]),
+{
+    pub fn new(oracle: F, threshold: Duration, samples: usize) -> TimingOracle<F> {
+        let origin = Instant::now();
+        TimingOracle {
+            oracle,
+            threshold,
+            samples,
+            clock: Box::new(move || origin.elapsed()),
+        }
+    }
+
+    /// Replace the monotonic clock, returning the time elapsed since any fixed origin, e.g. with a
+    /// simulated one advanced by the oracle
+    pub fn with_clock<C: Fn() -> Duration + 'static>(mut self, clock: C) -> TimingOracle<F> {
+        self.clock = Box::new(clock);
+        self
+    }
+
+    /// `calibrated` oracle with the monotonic clock
+    pub fn calibrate(oracle: F, valid_ct: &[u8], samples: usize) -> TimingOracle<F> {
+        Self::new(oracle, Duration::default(), samples).calibrated(valid_ct)
+    }
+
+    /// Set the threshold between the timing of a known validly padded `iv || ct` (e.g. the
+    /// intercepted one) and of tampered ciphertexts, which are invalid for all but a few
+    pub fn calibrated(mut self, valid_ct: &[u8]) -> TimingOracle<F> {
+        if valid_ct.len() < 2 * BLOCK_SIZE {
+            panic!("Invalid ciphertext, should be an iv followed by at least one block");
+        }
+        let valid = self.time(valid_ct);
+
+        let mut tampered = valid_ct.to_vec();
+        let index = valid_ct.len() - BLOCK_SIZE - 1;
+        let mut invalid: Vec<Duration> = (1..=32)
+            .map(|delta| {
+                tampered[index] = valid_ct[index] ^ delta;
+                self.time(&tampered)
+            })
+            .collect();
+        invalid.sort();
+
+        self.threshold = (valid + invalid[invalid.len() / 2]) / 2;
+        self
+    }
+
+    // minimal running time over the samples, the least affected by noise
+    fn time(&self, ct: &[u8]) -> Duration {
+        (0..self.samples)
+            .map(|_| {
+                let start = (self.clock)();
+                (self.oracle)(ct);
+                (self.clock)() - start
+            })
+            .min()
+            .unwrap_or_default()
+    }
+
+    pub fn threshold(&self) -> Duration {
+        self.threshold
+    }
+
+    pub fn is_valid(&self, ct: &[u8]) -> bool {
+        self.time(ct) > self.threshold
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cipher::{cbc::AES_128_CBC, Cipher};
+    use std::cell::Cell;
+    use std::rc::Rc;
+    use std::thread;
+
+    const KEY: &[u8; 16] = b"i am pied piper!";
+    const MSG: &[u8] = b"Privacy is necessary for an open society in the electronic age.";
+
+    fn oracle_for(scheme: Scheme) -> impl Fn(&[u8]) -> bool {
+        move |ct: &[u8]| scheme.is_valid(&AES_128_CBC::decrypt_with_padding(KEY, ct), 16)
+    }
+
+    #[test]
+    fn padding_oracle_schemes() {
+        for scheme in [Scheme::Pkcs7, Scheme::AnsiX923, Scheme::Iso7816].iter() {
+            let ct = AES_128_CBC::new().with_padding(*scheme).encrypt(KEY, MSG);
+            assert_eq!(decrypt(&oracle_for(*scheme), *scheme, &ct), MSG.to_vec());
+        }
+    }
+
+    #[test]
+    fn lenient_padding_oracle() {
+        let oracle = |ct: &[u8]| {
+            let pt = AES_128_CBC::decrypt_with_padding(KEY, ct);
+            (1..=16).contains(&pt[pt.len() - 1])
+        };
+        let ct = AES_128_CBC::new().encrypt(KEY, MSG);
+        let expected: Vec<u8> = vec![MSG[15], MSG[31], MSG[47], 1];
+        assert_eq!(decrypt_last_bytes(&oracle, &ct), expected);
+    }
+
+    #[test]
+    fn cbc_r_forgery() {
+        let chosen = b"comment1=cooking;admin=true;comment2=like a pound of bacon";
+        for scheme in [Scheme::Pkcs7, Scheme::Iso7816].iter() {
+            let forged = cbc_r(&oracle_for(*scheme), *scheme, chosen);
+            let cipher = AES_128_CBC::new().with_padding(*scheme);
+            assert_eq!(cipher.decrypt(KEY, &forged), chosen.to_vec());
+        }
+    }
+
+    #[test]
+    #[ignore] // wall-clock timing, slow and sensitive to the load of the machine
+    fn mac_then_encrypt_timing_oracle() {
+        // the server always fails with the same error, but only validly padded plaintexts go
+        // through the (slow) MAC verification
+        let server = |ct: &[u8]| {
+            let pt = AES_128_CBC::decrypt_with_padding(KEY, ct);
+            if Scheme::Pkcs7.is_valid(&pt, 16) {
+                thread::sleep(Duration::from_millis(20));
+            }
+        };
+        // the minimum over a few samples filters out scheduling delays of invalid queries
+        let ct = AES_128_CBC::new().encrypt(KEY, &MSG[..20]);
+        let timing_oracle = TimingOracle::calibrate(server, &ct, 5);
+        assert!(timing_oracle.threshold() > Duration::default());
+
+        let oracle = |ct: &[u8]| timing_oracle.is_valid(ct);
+        assert_eq!(decrypt(&oracle, Scheme::Pkcs7, &ct), MSG[..20].to_vec());
+    }
+
+    #[test]
+    fn timing_oracle_simulated_clock() {
+        // the server advances a simulated clock instead of taking time, with noise on invalid
+        // queries below the MAC cost
+        let now = Rc::new(Cell::new(Duration::default()));
+        let queries = Cell::new(0u32);
+        let clock = now.clone();
+        let server = |ct: &[u8]| {
+            let pt = AES_128_CBC::decrypt_with_padding(KEY, ct);
+            queries.set(queries.get() + 1);
+            let elapsed = if Scheme::Pkcs7.is_valid(&pt, 16) {
+                Duration::from_micros(300)
+            } else {
+                Duration::from_micros(10 + (queries.get() % 7) as u64 * 20)
+            };
+            now.set(now.get() + elapsed);
+        };
+        let ct = AES_128_CBC::new().encrypt(KEY, MSG);
+        let timing_oracle = TimingOracle::new(server, Duration::default(), 1)
+            .with_clock(move || clock.get())
+            .calibrated(&ct);
+        assert!(timing_oracle.threshold() > Duration::from_micros(130));
+        assert!(timing_oracle.threshold() < Duration::from_micros(300));
+
+        let oracle = |ct: &[u8]| timing_oracle.is_valid(ct);
+        assert_eq!(decrypt(&oracle, Scheme::Pkcs7, &ct), MSG.to_vec());
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one block")]
+    fn timing_oracle_short_ciphertext() {
+        TimingOracle::new(|_: &[u8]| {}, Duration::default(), 1).calibrated(&[0; 16]);
+    }
+}
@@ -0,0 +1,364 @@
+// Offline password cracking against a verifier, e.g. an HMAC tag captured during a simplified-SRP
+// MITM or a PBKDF2 hash.
+//
+// Candidates come from a wordlist expanded by mangling rules, or from masks (brute force being a
+// mask with the same charset at every position). Every candidate space is indexed so that threads
+// can share it by striding over the indices.
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub const LOWER: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
+pub const UPPER: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+pub const DIGITS: &[u8] = b"0123456789";
+pub const SYMBOLS: &[u8] = b" !\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~";
+
+/// Mangling rule turning a dictionary word into candidate passwords
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Rule {
+    /// The word as is
+    Identity,
+    Lowercase,
+    Uppercase,
+    /// Uppercase the first letter and lowercase the rest
+    Capitalize,
+    /// Substitute a->4, e->3, i->1, o->0, s->5, t->7
+    Leetspeak,
+    /// Append every digit string of 1 up to `n` digits
+    AppendDigits(usize),
+    /// Apply the rules in sequence, e.g. capitalize then append digits
+    Chain(Vec<Rule>),
+}
+
+impl Rule {
+    pub fn apply(&self, word: &[u8]) -> Vec<Vec<u8>> {
+        match self {
+            Rule::Identity => vec![word.to_vec()],
+            Rule::Lowercase => vec![word.to_ascii_lowercase()],
+            Rule::Uppercase => vec![word.to_ascii_uppercase()],
+            Rule::Capitalize => {
+                let mut capitalized = word.to_ascii_lowercase();
+                if let Some(first) = capitalized.first_mut() {
+                    first.make_ascii_uppercase();
+                }
+                vec![capitalized]
+            }
+            Rule::Leetspeak => vec![word
+                .iter()
+                .map(|c| match c.to_ascii_lowercase() {
+                    b'a' => b'4',
+                    b'e' => b'3',
+                    b'i' => b'1',
+                    b'o' => b'0',
+                    b's' => b'5',
+                    b't' => b'7',
+                    _ => *c,
+                })
+                .collect()],
+            Rule::AppendDigits(n) => (1..=*n)
+                .flat_map(|len| Mask::brute_force(DIGITS, len).candidates())
+                .map(|digits| [word, &digits[..]].concat())
+                .collect(),
+            Rule::Chain(rules) => rules.iter().fold(vec![word.to_vec()], |words, rule| {
+                words.iter().flat_map(|w| rule.apply(w)).collect()
+            }),
+        }
+    }
+
+    /// A reasonable default set: the word, its case variants, leetspeak and up to 4 appended digits
+    pub fn defaults() -> Vec<Rule> {
+        vec![
+            Rule::Identity,
+            Rule::Lowercase,
+            Rule::Uppercase,
+            Rule::Capitalize,
+            Rule::Leetspeak,
+            Rule::AppendDigits(4),
+            Rule::Chain(vec![Rule::Capitalize, Rule::AppendDigits(4)]),
+        ]
+    }
+}
+
+/// A charset per position, e.g. "?u?l?l?l?d?d" for a capitalized 4-letter word and two digits
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mask {
+    charsets: Vec<Vec<u8>>,
+}
+
+impl Mask {
+    /// Parse a hashcat-style mask: ?l, ?u, ?d, ?s and ?a (all printable) for charsets, "??" for a
+    /// literal '?', any other character stands for itself
+    pub fn new(pattern: &str) -> Mask {
+        let mut charsets = vec![];
+        let mut chars = pattern.bytes();
+        while let Some(c) = chars.next() {
+            if c != b'?' {
+                charsets.push(vec![c]);
+                continue;
+            }
+            charsets.push(match chars.next() {
+                Some(b'l') => LOWER.to_vec(),
+                Some(b'u') => UPPER.to_vec(),
+                Some(b'd') => DIGITS.to_vec(),
+                Some(b's') => SYMBOLS.to_vec(),
+                Some(b'a') => [LOWER, UPPER, DIGITS, SYMBOLS].concat(),
+                Some(b'?') => vec![b'?'],
+                _ => panic!("Invalid mask, expecting one of ?l ?u ?d ?s ?a ?? in {}", pattern),
+            });
+        }
+        Mask { charsets }
+    }
+
+    /// All strings of exactly `len` characters from `charset`
+    pub fn brute_force(charset: &[u8], len: usize) -> Mask {
+        if charset.is_empty() {
+            panic!("Invalid charset, should not be empty");
+        }
+        Mask {
+            charsets: vec![charset.to_vec(); len],
+        }
+    }
+
+    /// Number of candidates, saturating at u64::MAX
+    pub fn keyspace(&self) -> u64 {
+        self.charsets
+            .iter()
+            .fold(1u64, |acc, c| acc.saturating_mul(c.len() as u64))
+    }
+
+    /// The `index`-th candidate, reading the index in mixed radix with the last position varying
+    /// the fastest
+    pub fn candidate(&self, mut index: u64) -> Vec<u8> {
+        let mut candidate = vec![0; self.charsets.len()];
+        for (c, charset) in candidate.iter_mut().zip(self.charsets.iter()).rev() {
+            let radix = charset.len() as u64;
+            *c = charset[(index % radix) as usize];
+            index /= radix;
+        }
+        candidate
+    }
+
+    pub fn candidates(&self) -> Vec<Vec<u8>> {
+        (0..self.keyspace()).map(|i| self.candidate(i)).collect()
+    }
+}
+
+/// Outcome of a cracking session
+#[derive(Debug, Clone)]
+pub struct Report {
+    pub password: Option<Vec<u8>>,
+    /// Number of candidates passed to the verifier
+    pub tried: u64,
+    pub elapsed: Duration,
+}
+
+impl Report {
+    /// Throughput in candidates per second
+    pub fn rate(&self) -> f64 {
+        self.tried as f64 / self.elapsed.as_secs_f64().max(1e-9)
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.password {
+            Some(pwd) => write!(f, "found \"{}\"", String::from_utf8_lossy(pwd))?,
+            None => write!(f, "exhausted")?,
+        }
+        write!(
+            f,
+            " after {} candidates in {:.2?} ({:.0} c/s)",
+            self.tried,
+            self.elapsed,
+            self.rate()
+        )
+    }
+}
+
+/// Multi-threaded cracker, stops as soon as the verifier accepts a candidate
+pub struct Cracker<F>
+where
+    F: Fn(&[u8]) -> bool + Sync,
+{
+    verifier: F,
+    rules: Vec<Rule>,
+    threads: usize,
+}
+
+impl<F> Cracker<F>
+where
+    F: Fn(&[u8]) -> bool + Sync,
+{
+    /// Instantiate with the words tried as is and as many threads as available cores
+    pub fn new(verifier: F) -> Cracker<F> {
+        Cracker {
+            verifier,
+            rules: vec![Rule::Identity],
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+        }
+    }
+
+    /// Mangling rules applied to every dictionary word
+    pub fn rules(mut self, rules: Vec<Rule>) -> Cracker<F> {
+        self.rules = rules;
+        self
+    }
+
+    pub fn threads(mut self, threads: usize) -> Cracker<F> {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Dictionary attack with a wordlist file, one word per line
+    pub fn wordlist<P: AsRef<Path>>(&self, path: P) -> io::Result<Report> {
+        let content = fs::read(path)?;
+        let words: Vec<&[u8]> = content
+            .split(|c| *c == b'\n')
+            .map(|line| line.strip_suffix(b"\r").unwrap_or(line))
+            .filter(|line| !line.is_empty())
+            .collect();
+        Ok(self.words(&words))
+    }
+
+    /// Dictionary attack with in-memory words, every word goes through a rule before the next rule
+    /// is tried so that cheap rules are exhausted first
+    pub fn words<W: AsRef<[u8]> + Sync>(&self, words: &[W]) -> Report {
+        let n = words.len() as u64;
+        self.search(n * self.rules.len() as u64, |i| {
+            self.rules[(i / n) as usize].apply(words[(i % n) as usize].as_ref())
+        })
+    }
+
+    pub fn mask(&self, mask: &Mask) -> Report {
+        self.search(mask.keyspace(), |i| vec![mask.candidate(i)])
+    }
+
+    /// Exhaustive search over `charset` for every length from `min_len` to `max_len`
+    pub fn brute_force(&self, charset: &[u8], min_len: usize, max_len: usize) -> Report {
+        let mut total = Report {
+            password: None,
+            tried: 0,
+            elapsed: Duration::default(),
+        };
+        for len in min_len..=max_len {
+            let report = self.mask(&Mask::brute_force(charset, len));
+            total.tried += report.tried;
+            total.elapsed += report.elapsed;
+            if report.password.is_some() {
+                total.password = report.password;
+                break;
+            }
+        }
+        total
+    }
+
+    // Threads stride over the indices of the candidate space, `candidates(i)` expanding index i
+    fn search<G>(&self, total: u64, candidates: G) -> Report
+    where
+        G: Fn(u64) -> Vec<Vec<u8>> + Sync,
+    {
+        let start = Instant::now();
+        let found = AtomicBool::new(false);
+        let tried = AtomicU64::new(0);
+        let password: Mutex<Option<Vec<u8>>> = Mutex::new(None);
+
+        thread::scope(|s| {
+            for t in 0..self.threads as u64 {
+                let (found, tried, password, candidates) = (&found, &tried, &password, &candidates);
+                s.spawn(move || {
+                    let mut index = t;
+                    while index < total && !found.load(Ordering::Relaxed) {
+                        for candidate in candidates(index) {
+                            if found.load(Ordering::Relaxed) {
+                                return;
+                            }
+                            tried.fetch_add(1, Ordering::Relaxed);
+                            if (self.verifier)(&candidate) {
+                                found.store(true, Ordering::Relaxed);
+                                *password.lock().unwrap() = Some(candidate);
+                                return;
+                            }
+                        }
+                        index += self.threads as u64;
+                    }
+                });
+            }
+        });
+
+        Report {
+            password: password.into_inner().unwrap(),
+            tried: tried.into_inner(),
+            elapsed: start.elapsed(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::process;
+
+    #[test]
+    fn mangling_rules() {
+        assert_eq!(Rule::Capitalize.apply(b"pASSword"), vec![b"Password".to_vec()]);
+        assert_eq!(Rule::Leetspeak.apply(b"Password"), vec![b"P455w0rd".to_vec()]);
+        let appended = Rule::AppendDigits(2).apply(b"pwd");
+        assert_eq!(appended.len(), 110);
+        assert_eq!(appended[0], b"pwd0".to_vec());
+        assert_eq!(appended[109], b"pwd99".to_vec());
+        let chained = Rule::Chain(vec![Rule::Uppercase, Rule::AppendDigits(1)]).apply(b"pwd");
+        assert_eq!(chained[7], b"PWD7".to_vec());
+    }
+
+    #[test]
+    fn mask_candidates() {
+        let mask = Mask::new("?u?d??x");
+        assert_eq!(mask.keyspace(), 260);
+        assert_eq!(mask.candidate(0), b"A0?x".to_vec());
+        assert_eq!(mask.candidate(259), b"Z9?x".to_vec());
+        assert_eq!(Mask::brute_force(b"ab", 3).candidates()[5], b"bab".to_vec());
+    }
+
+    #[test]
+    fn crack_wordlist_with_rules() {
+        let cracker = Cracker::new(|pwd: &[u8]| pwd == b"Sunshine42").threads(4);
+        assert!(cracker.words(&["sunshine"]).password.is_none());
+
+        // unique name, concurrent test runs may share the temporary directory
+        let name = format!(
+            "cryptanalysis_password_wordlist_{}_{:016x}.txt",
+            process::id(),
+            rand::random::<u64>()
+        );
+        let path = env::temp_dir().join(name);
+        fs::write(&path, "dad birthday\r\nusername\npassword\n\nsunshine\n").unwrap();
+
+        // removed before any assertion can fail
+        let report = cracker.rules(Rule::defaults()).wordlist(&path);
+        fs::remove_file(&path).unwrap();
+        let report = report.unwrap();
+        assert_eq!(report.password, Some(b"Sunshine42".to_vec()));
+        assert!(report.tried > 0 && report.rate() > 0.0);
+    }
+
+    #[test]
+    fn crack_mask_and_brute_force() {
+        let cracker = Cracker::new(|pwd: &[u8]| pwd == b"Abc12").threads(3);
+        assert_eq!(
+            cracker.mask(&Mask::new("?u?l?l?d?d")).password,
+            Some(b"Abc12".to_vec())
+        );
+
+        let cracker = Cracker::new(|pwd: &[u8]| pwd == b"zz").threads(2);
+        let report = cracker.brute_force(LOWER, 1, 3);
+        assert_eq!(report.password, Some(b"zz".to_vec()));
+        assert!(report.tried >= 26 + 26 * 26 - 1);
+        assert!(cracker.brute_force(DIGITS, 1, 2).password.is_none());
+    }
+}
@@ -0,0 +1,246 @@
+// RC4 single-byte bias attack (AlFardan, Bernstein, Paterson, Poettering and Schuldt, 2013).
+//
+// The first keystream bytes of RC4 are biased, e.g. Z16 leans toward 240 and Z32 toward 224. When
+// a secret is encrypted under many fresh keys at a position we control, the most frequent
+// ciphertext bytes there reveal it: we pick the plaintext byte maximizing the likelihood of the
+// observed ciphertext counts under the keystream distributions.
+use rand::RngCore;
+use std::thread;
+
+/// Keystream positions (1-indexed, i.e. Z16 and Z32) targeted by default
+pub const POSITIONS: [usize; 2] = [16, 32];
+
+/// Minimal RC4 keystream generator, kept allocation-free for the millions of keys we go through
+#[derive(Clone)]
+pub struct Rc4 {
+    s: [u8; 256],
+    i: u8,
+    j: u8,
+}
+
+impl Rc4 {
+    pub fn new(key: &[u8]) -> Rc4 {
+        if key.is_empty() {
+            panic!("Invalid key, should not be empty");
+        }
+        let mut s = [0; 256];
+        for (i, item) in s.iter_mut().enumerate() {
+            *item = i as u8;
+        }
+        let mut j: u8 = 0;
+        for i in 0..256 {
+            j = j.wrapping_add(s[i]).wrapping_add(key[i % key.len()]);
+            s.swap(i, j as usize);
+        }
+        Rc4 { s, i: 0, j: 0 }
+    }
+}
+
+impl Iterator for Rc4 {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        self.i = self.i.wrapping_add(1);
+        self.j = self.j.wrapping_add(self.s[self.i as usize]);
+        self.s.swap(self.i as usize, self.j as usize);
+        Some(self.s[self.s[self.i as usize].wrapping_add(self.s[self.j as usize]) as usize])
+    }
+}
+
+/// Cookie-bearing request oracle modeled on the compression oracle of challenge 51: the attacker
+/// controls the request prefix, every request is encrypted under a fresh random 128-bit key.
+pub fn request_oracle(cookie: &[u8]) -> impl Fn(&[u8]) -> Vec<u8> + Sync + '_ {
+    move |prefix: &[u8]| {
+        // step 1. format request
+        let req = [prefix, cookie].concat();
+        // step 2. encrypt under a fresh key
+        let mut key = [0; 16];
+        rand::thread_rng().fill_bytes(&mut key);
+        req.iter().zip(Rc4::new(&key)).map(|(p, z)| p ^ z).collect()
+    }
+}
+
+// Run `task(samples)` on `threads` threads splitting the samples, then sum the histograms
+fn parallel_counts<F>(samples: u64, threads: usize, task: F) -> Vec<[u64; 256]>
+where
+    F: Fn(u64) -> Vec<[u64; 256]> + Sync,
+{
+    let threads = threads.max(1) as u64;
+    let results: Vec<Vec<[u64; 256]>> = thread::scope(|s| {
+        let handles: Vec<_> = (0..threads)
+            .map(|t| {
+                let task = &task;
+                // spread the remainder over the first threads
+                let share = samples / threads + u64::from(t < samples % threads);
+                s.spawn(move || task(share))
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+
+    let mut total = results[0].clone();
+    for counts in results[1..].iter() {
+        for (acc, c) in total.iter_mut().zip(counts.iter()) {
+            acc.iter_mut().zip(c.iter()).for_each(|(a, b)| *a += b);
+        }
+    }
+    total
+}
+
+/// Empirical distributions of the keystream bytes at `positions` (1-indexed) over `samples` fresh
+/// random keys, computed on `threads` threads
+pub fn keystream_distributions(positions: &[usize], samples: u64, threads: usize) -> Vec<[f64; 256]> {
+    let len = *positions.iter().max().expect("No position given");
+    let counts = parallel_counts(samples, threads, |n| {
+        let mut rng = rand::thread_rng();
+        let mut counts = vec![[0; 256]; positions.len()];
+        let mut key = [0; 16];
+        let mut key_stream = [0; 256];
+        for _ in 0..n {
+            rng.fill_bytes(&mut key);
+            Rc4::new(&key)
+                .take(len)
+                .enumerate()
+                .for_each(|(i, z)| key_stream[i] = z);
+            for (count, p) in counts.iter_mut().zip(positions) {
+                count[key_stream[p - 1] as usize] += 1;
+            }
+        }
+        counts
+    });
+
+    counts
+        .iter()
+        .map(|count| {
+            let mut dist = [0.0; 256];
+            for (d, c) in dist.iter_mut().zip(count.iter()) {
+                // add-one smoothing, a zero probability would veto a candidate
+                *d = (*c as f64 + 1.0) / (samples as f64 + 256.0);
+            }
+            dist
+        })
+        .collect()
+}
+
+/// Histograms of the ciphertext bytes at `positions` (1-indexed) over `samples` requests with
+/// the given prefix
+pub fn ciphertext_counts<F>(
+    oracle: &F,
+    prefix: &[u8],
+    positions: &[usize],
+    samples: u64,
+    threads: usize,
+) -> Vec<[u64; 256]>
+where
+    F: Fn(&[u8]) -> Vec<u8> + Sync,
+{
+    parallel_counts(samples, threads, |n| {
+        let mut counts = vec![[0; 256]; positions.len()];
+        for _ in 0..n {
+            let ct = oracle(prefix);
+            for (count, p) in counts.iter_mut().zip(positions) {
+                if let Some(c) = ct.get(p - 1) {
+                    count[*c as usize] += 1;
+                }
+            }
+        }
+        counts
+    })
+}
+
+/// Maximum likelihood plaintext byte given pairs of (ciphertext counts, keystream distribution)
+/// observed at positions holding that same plaintext byte
+pub fn most_likely_byte(observations: &[(&[u64; 256], &[f64; 256])]) -> u8 {
+    let log_likelihood = |p: u8| -> f64 {
+        observations
+            .iter()
+            .map(|(counts, dist)| {
+                (0..256)
+                    .map(|c| counts[c] as f64 * dist[c ^ p as usize].ln())
+                    .sum::<f64>()
+            })
+            .sum()
+    };
+    (0..=255u8)
+        .max_by(|a, b| log_likelihood(*a).partial_cmp(&log_likelihood(*b)).unwrap())
+        .unwrap()
+}
+
+/// Recover the first `cookie_len` bytes following the attacker-controlled prefix. For every prefix
+/// length, `samples` requests are collected, so that each cookie byte lands on every position
+/// (1-indexed) of `positions` it can reach. Bytes beyond the largest position are not recoverable.
+pub fn recover_cookie<F>(
+    oracle: &F,
+    cookie_len: usize,
+    distributions: &[[f64; 256]],
+    positions: &[usize],
+    samples: u64,
+    threads: usize,
+) -> Vec<u8>
+where
+    F: Fn(&[u8]) -> Vec<u8> + Sync,
+{
+    let max_pos = *positions.iter().max().expect("No position given");
+    if cookie_len > max_pos {
+        panic!("Cookie bytes beyond position {} are out of reach", max_pos);
+    }
+
+    // counts[prefix_len][i] is the histogram at positions[i]
+    let counts: Vec<Vec<[u64; 256]>> = (0..max_pos)
+        .map(|prefix_len| ciphertext_counts(oracle, &vec![b'A'; prefix_len], positions, samples, threads))
+        .collect();
+
+    (0..cookie_len)
+        .map(|k| {
+            // cookie byte k is at position p when the prefix is p - 1 - k bytes long
+            let observations: Vec<_> = positions
+                .iter()
+                .enumerate()
+                .filter(|(_, p)| **p > k)
+                .map(|(i, p)| (&counts[p - 1 - k][i], &distributions[i]))
+                .collect();
+            most_likely_byte(&observations)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rc4_generator() {
+        // source: RFC 6229, 40-bit key, keystream at offset 0
+        let key_stream: Vec<u8> = Rc4::new(&[1, 2, 3, 4, 5]).take(8).collect();
+        assert_eq!(key_stream, vec![0xb2, 0x39, 0x63, 0x05, 0xf0, 0x3d, 0xc0, 0x27]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn rc4_empty_key() {
+        Rc4::new(&[]);
+    }
+
+    #[test]
+    fn keystream_biases() {
+        // Mantin-Shamir bias: Pr[Z2 = 0] = 2/256
+        let distributions = keystream_distributions(&[2, 16], 1 << 15, 4);
+        assert!(distributions[0][0] > 1.5 / 256.0);
+        assert!(distributions[1].iter().sum::<f64>() > 0.99);
+    }
+
+    #[test]
+    fn recover_cookie_from_biases() {
+        // the full attack on Z16/Z32 needs ~2^24 samples per byte and distributions estimated over
+        // far more keys, the much stronger Z2 bias exercises the same pipeline in a fraction of
+        // the time
+        let positions = [2];
+        let mut model = [(1.0 - 2.0 / 256.0) / 255.0; 256];
+        model[0] = 2.0 / 256.0;
+
+        let cookie = b"QkUg".to_vec();
+        let oracle = request_oracle(&cookie);
+        let recovered = recover_cookie(&oracle, 2, &[model], &positions, 1 << 14, 4);
+        assert_eq!(recovered, cookie[..2].to_vec());
+    }
+}
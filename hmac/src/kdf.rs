@@ -0,0 +1,269 @@
+//! Key derivation functions built on `Hmac`: PBKDF2 (RFC 8018), HKDF (RFC 5869) and scrypt
+//! (RFC 7914).
+use super::Hmac;
+use alloc::{vec, vec::Vec};
+use core::convert::TryInto;
+use crypto_mac::Mac;
+use digest::{
+    generic_array::{typenum::Unsigned, ArrayLength},
+    BlockInput, FixedOutput, Input, Reset,
+};
+use sha2::Sha256;
+
+fn hmac<D>(key: &[u8], data: &[&[u8]]) -> Vec<u8>
+where
+    D: Input + BlockInput + FixedOutput + Reset + Default + Clone,
+    D::BlockSize: ArrayLength<u8>,
+    D::OutputSize: ArrayLength<u8>,
+{
+    let mut mac = Hmac::<D>::new_varkey(key).expect("HMAC can take varkey");
+    for d in data {
+        mac.input(d);
+    }
+    mac.result().code().to_vec()
+}
+
+/// PBKDF2 with HMAC-`D` as PRF, derives `len` bytes from `password` and `salt`
+pub fn pbkdf2<D>(password: &[u8], salt: &[u8], rounds: u32, len: usize) -> Vec<u8>
+where
+    D: Input + BlockInput + FixedOutput + Reset + Default + Clone,
+    D::BlockSize: ArrayLength<u8>,
+    D::OutputSize: ArrayLength<u8>,
+{
+    if rounds == 0 {
+        panic!("Invalid iteration count, should be positive");
+    }
+    let mut derived: Vec<u8> = vec![];
+    let mut index: u32 = 1;
+    while derived.len() < len {
+        // T_i = U_1 ^ U_2 ^ ... ^ U_c, U_1 = PRF(P, S || INT(i)), U_j = PRF(P, U_{j-1})
+        let mut u = hmac::<D>(password, &[salt, &index.to_be_bytes()]);
+        let mut t = u.clone();
+        for _ in 1..rounds {
+            u = hmac::<D>(password, &[&u]);
+            t.iter_mut().zip(u.iter()).for_each(|(a, b)| *a ^= b);
+        }
+        derived.extend_from_slice(&t);
+        index += 1;
+    }
+    derived.truncate(len);
+    derived
+}
+
+/// HKDF-Extract: PRK = HMAC-`D`(salt, ikm), an empty salt stands for HashLen zero bytes
+pub fn hkdf_extract<D>(salt: &[u8], ikm: &[u8]) -> Vec<u8>
+where
+    D: Input + BlockInput + FixedOutput + Reset + Default + Clone,
+    D::BlockSize: ArrayLength<u8>,
+    D::OutputSize: ArrayLength<u8>,
+{
+    // HMAC pads the key with zeros anyway, thus the empty salt needs no special case
+    hmac::<D>(salt, &[ikm])
+}
+
+/// HKDF-Expand: derives `len` bytes from the pseudorandom key `prk` bound to the context `info`,
+/// panics if `len` exceeds 255 * HashLen
+pub fn hkdf_expand<D>(prk: &[u8], info: &[u8], len: usize) -> Vec<u8>
+where
+    D: Input + BlockInput + FixedOutput + Reset + Default + Clone,
+    D::BlockSize: ArrayLength<u8>,
+    D::OutputSize: ArrayLength<u8>,
+{
+    if len > 255 * D::OutputSize::to_usize() {
+        panic!("Invalid length, HKDF outputs at most 255 * HashLen bytes");
+    }
+    let mut okm: Vec<u8> = vec![];
+    let mut t: Vec<u8> = vec![];
+    for counter in 1..=255u8 {
+        if okm.len() >= len {
+            break;
+        }
+        // T(i) = HMAC(PRK, T(i-1) || info || i)
+        t = hmac::<D>(prk, &[&t, info, &[counter]]);
+        okm.extend_from_slice(&t);
+    }
+    okm.truncate(len);
+    okm
+}
+
+/// HKDF: extract then expand
+pub fn hkdf<D>(salt: &[u8], ikm: &[u8], info: &[u8], len: usize) -> Vec<u8>
+where
+    D: Input + BlockInput + FixedOutput + Reset + Default + Clone,
+    D::BlockSize: ArrayLength<u8>,
+    D::OutputSize: ArrayLength<u8>,
+{
+    hkdf_expand::<D>(&hkdf_extract::<D>(salt, ikm), info, len)
+}
+
+/// scrypt with cost `n` (a power of 2 greater than 1), block size `r` and parallelization `p`
+pub fn scrypt(password: &[u8], salt: &[u8], n: usize, r: usize, p: usize, len: usize) -> Vec<u8> {
+    if n < 2 || !n.is_power_of_two() {
+        panic!("Invalid cost parameter, should be a power of 2 greater than 1");
+    }
+    if r == 0 || p == 0 {
+        panic!("Invalid block size or parallelization parameter, should be positive");
+    }
+    let block_len = 128 * r;
+    let mut b = pbkdf2::<Sha256>(password, salt, 1, p * block_len);
+    for block in b.chunks_mut(block_len) {
+        ro_mix(block, n);
+    }
+    pbkdf2::<Sha256>(password, &b, 1, len)
+}
+
+// Salsa20/8 core on a 64-byte block, in place
+fn salsa20_8(block: &mut [u8]) {
+    let mut x = [0u32; 16];
+    for (w, chunk) in x.iter_mut().zip(block.chunks(4)) {
+        *w = u32::from_le_bytes(chunk.try_into().unwrap());
+    }
+    let input = x;
+    let quarter = |x: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize| {
+        x[b] ^= x[a].wrapping_add(x[d]).rotate_left(7);
+        x[c] ^= x[b].wrapping_add(x[a]).rotate_left(9);
+        x[d] ^= x[c].wrapping_add(x[b]).rotate_left(13);
+        x[a] ^= x[d].wrapping_add(x[c]).rotate_left(18);
+    };
+    for _ in 0..4 {
+        // column round then row round
+        quarter(&mut x, 0, 4, 8, 12);
+        quarter(&mut x, 5, 9, 13, 1);
+        quarter(&mut x, 10, 14, 2, 6);
+        quarter(&mut x, 15, 3, 7, 11);
+        quarter(&mut x, 0, 1, 2, 3);
+        quarter(&mut x, 5, 6, 7, 4);
+        quarter(&mut x, 10, 11, 8, 9);
+        quarter(&mut x, 15, 12, 13, 14);
+    }
+    for (i, chunk) in block.chunks_mut(4).enumerate() {
+        chunk.copy_from_slice(&x[i].wrapping_add(input[i]).to_le_bytes());
+    }
+}
+
+// scryptBlockMix on 2r 64-byte blocks: Y_i = Salsa(Y_{i-1} ^ B_i), output even then odd Y_i
+fn block_mix(b: &[u8]) -> Vec<u8> {
+    let blocks = b.len() / 64;
+    let mut x = b[b.len() - 64..].to_vec();
+    let mut y = vec![0; b.len()];
+    for i in 0..blocks {
+        x.iter_mut().zip(b[i * 64..].iter()).for_each(|(a, b)| *a ^= b);
+        salsa20_8(&mut x);
+        let dest = (i / 2 + (i % 2) * blocks / 2) * 64;
+        y[dest..dest + 64].copy_from_slice(&x);
+    }
+    y
+}
+
+// scryptROMix, in place
+fn ro_mix(b: &mut [u8], n: usize) {
+    let mut x = b.to_vec();
+    let mut v = Vec::with_capacity(n);
+    for _ in 0..n {
+        v.push(x.clone());
+        x = block_mix(&x);
+    }
+    for _ in 0..n {
+        // Integerify: the last 64-byte block read as a little endian integer, modulo n
+        let j = u64::from_le_bytes(x[x.len() - 64..x.len() - 56].try_into().unwrap()) as usize & (n - 1);
+        x.iter_mut().zip(v[j].iter()).for_each(|(a, b)| *a ^= b);
+        x = block_mix(&x);
+    }
+    b.copy_from_slice(&x);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use encoding::hex::hexstr_to_bytes as hex;
+    use sha1::Sha1;
+
+    #[test]
+    fn pbkdf2_rfc6070_vectors() {
+        let vectors: [(&[u8], &[u8], u32, &str); 5] = [
+            (
+                b"password",
+                b"salt",
+                1,
+                "0c60c80f961f0e71f3a9b524af6012062fe037a6",
+            ),
+            (
+                b"password",
+                b"salt",
+                2,
+                "ea6c014dc72d6f8ccd1ed92ace1d41f0d8de8957",
+            ),
+            (
+                b"password",
+                b"salt",
+                4096,
+                "4b007901b765489abead49d926f721d065a429c1",
+            ),
+            (
+                b"passwordPASSWORDpassword",
+                b"saltSALTsaltSALTsaltSALTsaltSALTsalt",
+                4096,
+                "3d2eec4fe41c849b80c8d83662c0e44a8b291a964cf2f07038",
+            ),
+            (b"pass\0word", b"sa\0lt", 4096, "56fa6aa75548099dcc37d7f03425e0c3"),
+        ];
+        for (password, salt, rounds, expected) in vectors.iter() {
+            let expected = hex(expected).unwrap();
+            assert_eq!(pbkdf2::<Sha1>(password, salt, *rounds, expected.len()), expected);
+        }
+    }
+
+    #[test]
+    fn hkdf_rfc5869_vectors() {
+        // Test Case 1 (SHA-256)
+        let prk = hkdf_extract::<Sha256>(&hex("000102030405060708090a0b0c").unwrap(), &[0x0b; 22]);
+        assert_eq!(
+            prk,
+            hex("077709362c2e32df0ddc3f0dc47bba6390b6c73bb50f9c3122ec844ad7c2b3e5").unwrap()
+        );
+        assert_eq!(
+            hkdf_expand::<Sha256>(&prk, &hex("f0f1f2f3f4f5f6f7f8f9").unwrap(), 42),
+            hex("3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf34007208d5b887185865")
+                .unwrap()
+        );
+        // Test Case 3 (SHA-256, empty salt and info)
+        assert_eq!(
+            hkdf::<Sha256>(&[], &[0x0b; 22], &[], 42),
+            hex("8da4e775a563c18f715f802a063c5a31b8a11f5c5ee1879ec3454e5f3c738d2d9d201395faa4b61a96c8")
+                .unwrap()
+        );
+        // Test Case 4 (SHA-1)
+        assert_eq!(
+            hkdf::<Sha1>(
+                &hex("000102030405060708090a0b0c").unwrap(),
+                &[0x0b; 11],
+                &hex("f0f1f2f3f4f5f6f7f8f9").unwrap(),
+                42
+            ),
+            hex("085a01ea1b10f36933068b56efa5ad81a4f14b822f5b091568a9cdd4f155fda2c22e422478d305f3f896")
+                .unwrap()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "at most 255 * HashLen")]
+    fn hkdf_max_length() {
+        hkdf::<Sha1>(&[], b"ikm", &[], 255 * 20 + 1);
+    }
+
+    #[test]
+    fn scrypt_rfc7914_vectors() {
+        assert_eq!(
+            scrypt(b"", b"", 16, 1, 1, 64),
+            hex("77d6576238657b203b19ca42c18a0497f16b4844e3074ae8dfdffa3fede21442\
+                 fcd0069ded0948f8326a753a0fc81f17e8d3e0fb2e0d3628cf35e20c38d18906")
+            .unwrap()
+        );
+        assert_eq!(
+            scrypt(b"password", b"NaCl", 1024, 8, 16, 64),
+            hex("fdbabe1c9d3472007856e7190d01e9fe7c6ad7cbc8237830e77376634b373162\
+                 2eaf30d92e22a3886ff109279d9830dac727afb94a83ee6d8360cbdfa2cc0640")
+            .unwrap()
+        );
+    }
+}
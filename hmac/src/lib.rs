@@ -1,4 +1,6 @@
 #![no_std]
+extern crate alloc;
+
 use core::{cmp::min, fmt};
 pub use crypto_mac::Mac;
 use crypto_mac::{InvalidKeyLength, MacResult};
@@ -8,6 +10,8 @@ use digest::{
     BlockInput, FixedOutput, Input, Reset,
 };
 
+pub mod kdf;
+
 const IPAD: u8 = 0x36;
 const OPAD: u8 = 0x5c;
 
@@ -118,3 +122,41 @@ where
             .finish()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+    use blake2::Blake2b;
+    use encoding::hex::hexstr_to_bytes as hex;
+    use sha3::Sha3_256;
+
+    fn hmac<D>(key: &[u8], msg: &[u8]) -> Vec<u8>
+    where
+        D: Input + BlockInput + FixedOutput + Reset + Default + Clone,
+        D::BlockSize: ArrayLength<u8>,
+        D::OutputSize: ArrayLength<u8>,
+    {
+        let mut mac = Hmac::<D>::new_varkey(key).unwrap();
+        mac.input(msg);
+        mac.result().code().to_vec()
+    }
+
+    #[test]
+    fn hmac_sponge_and_blake2() {
+        // Python: hmac.new(b"key", msg, "sha3_256") and hmac.new(b"key", msg, "blake2b")
+        let msg = b"The quick brown fox jumps over the lazy dog";
+        assert_eq!(
+            hmac::<Sha3_256>(b"key", msg),
+            hex("8c6e0683409427f8931711b10ca92a506eb1fafa48fadd66d76126f47ac2c333").unwrap()
+        );
+        assert_eq!(
+            hmac::<Blake2b>(b"key", msg),
+            hex(concat!(
+                "92294f92c0dfb9b00ec9ae8bd94d7e7d8a036b885a499f149dfe2fd2199394aa",
+                "af6b8894a1730cccb2cd050f9bcf5062a38b51b0dab33207f8ef35ae2c9df51b"
+            ))
+            .unwrap()
+        );
+    }
+}
@@ -0,0 +1,242 @@
+//! MD4 collisions with Wang et al.'s differential attack ("Cryptanalysis of the Hash Functions
+//! MD4 and RIPEMD", 2005).
+//!
+//! A block `M` and `M' = M + ΔM` (`m1 += 2^31`, `m2 += 2^31 - 2^28`, `m12 -= 2^16`) collide
+//! whenever the chaining values of `M` meet the sufficient conditions on their bits. The round 1
+//! conditions are all met by construction, rewriting each message word after fixing the bits of
+//! its step; the `a5` and `d5` conditions by flipping bits of `a1` and `a2`, then rewriting the
+//! next four words so that the rest of round 1 is unchanged. The remaining conditions are left
+//! to chance: a collision takes around 2^16 to 2^18 blocks.
+use super::Md4State;
+use digest::generic_array::GenericArray;
+
+const K2: u32 = 0x5A82_7999;
+const IV: [u32; 4] = [0x6745_2301, 0xEFCD_AB89, 0x98BA_DCFE, 0x1032_5476];
+
+// how a bit (0-indexed) of a chaining value is constrained: `Eq(n)` and `Ne(n)` refer to the
+// same bit in the value computed `n` steps before
+#[derive(Clone, Copy)]
+enum Cond {
+    Zero(u32),
+    One(u32),
+    Eq(u32, usize),
+    Ne(u32, usize),
+}
+
+use self::Cond::*;
+
+// sufficient conditions for the first 23 steps (a1, d1, c1, b1, a2... c6), from Wang's table
+// with 1-indexed bits shifted down by one
+#[rustfmt::skip]
+const CONDITIONS: [&[Cond]; 23] = [
+    /* a1 */ &[Eq(6, 1)],
+    /* d1 */ &[Zero(6), Eq(7, 1), Eq(10, 1)],
+    /* c1 */ &[One(6), One(7), Zero(10), Eq(25, 1)],
+    /* b1 */ &[One(6), Zero(7), Zero(10), Zero(25)],
+    /* a2 */ &[One(7), One(10), Zero(25), Eq(13, 1)],
+    /* d2 */ &[Zero(13), Eq(18, 1), Eq(19, 1), Eq(20, 1), Eq(21, 1), One(25)],
+    /* c2 */ &[Eq(12, 1), Zero(13), Eq(14, 1), Zero(18), Zero(19), One(20), Zero(21)],
+    /* b2 */ &[One(12), One(13), Zero(14), Eq(16, 1), Zero(18), Zero(19), Zero(20), Zero(21)],
+    /* a3 */ &[One(12), One(13), One(14), Zero(16), Zero(18), Zero(19), Zero(20), One(21),
+               Eq(22, 1), Eq(25, 1)],
+    /* d3 */ &[One(12), One(13), One(14), Zero(16), Zero(19), One(20), One(21), Zero(22),
+               One(25), Eq(29, 1)],
+    /* c3 */ &[One(16), Zero(19), Zero(20), Zero(21), Zero(22), Zero(25), One(29), Eq(31, 1)],
+    /* b3 */ &[Zero(19), One(20), One(21), Eq(22, 1), One(25), Zero(29), Zero(31)],
+    /* a4 */ &[Zero(22), Zero(25), Eq(26, 1), Eq(28, 1), One(29), Zero(31)],
+    /* d4 */ &[Zero(22), Zero(25), One(26), One(28), Zero(29), One(31)],
+    /* c4 */ &[Eq(18, 1), One(22), One(25), Zero(26), Zero(28), Zero(29)],
+    /* b4 */ &[Zero(18), One(25), One(26), One(28), Zero(29)],
+    /* a5 */ &[Eq(18, 2), One(25), Zero(26), One(28), One(31)],
+    /* d5 */ &[Eq(18, 1), Eq(25, 2), Eq(26, 2), Eq(28, 2), Eq(31, 2)],
+    /* c5 */ &[Eq(25, 1), Eq(26, 1), Eq(28, 1), Eq(29, 1), Eq(31, 1)],
+    /* b5 */ &[Eq(28, 1), One(29), Zero(31)],
+    /* a6 */ &[One(28), One(31)],
+    /* d6 */ &[Eq(28, 2)],
+    /* c6 */ &[Eq(28, 1), Ne(29, 1), Ne(31, 1)],
+];
+
+const SHIFTS_1: [u32; 4] = [3, 7, 11, 19];
+const SHIFTS_2: [u32; 4] = [3, 5, 9, 13];
+
+fn f(x: u32, y: u32, z: u32) -> u32 {
+    (x & y) | (!x & z)
+}
+
+fn g(x: u32, y: u32, z: u32) -> u32 {
+    (x & y) | (x & z) | (y & z)
+}
+
+// xorshift64*, so that searches are reproducible from a seed
+struct Rng(u64);
+
+impl Rng {
+    fn next_u32(&mut self) -> u32 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        (self.0.wrapping_mul(0x2545_F491_4F6C_DD1D) >> 32) as u32
+    }
+}
+
+// value computed `back` steps before step `i`, in `v` offset by the 4 initial values
+fn bit(v: &[u32], i: usize, back: usize, k: u32) -> u32 {
+    (v[i + 4 - back] >> k) & 1
+}
+
+fn satisfies(v: &[u32], i: usize) -> bool {
+    let x = v[i + 4];
+    CONDITIONS[i].iter().all(|cond| match *cond {
+        Zero(k) => (x >> k) & 1 == 0,
+        One(k) => (x >> k) & 1 == 1,
+        Eq(k, n) => (x >> k) & 1 == bit(v, i, n, k),
+        Ne(k, n) => (x >> k) & 1 != bit(v, i, n, k),
+    })
+}
+
+fn enforce(v: &[u32], i: usize, mut x: u32) -> u32 {
+    for cond in CONDITIONS[i].iter() {
+        let (k, value) = match *cond {
+            Zero(k) => (k, 0),
+            One(k) => (k, 1),
+            Eq(k, n) => (k, bit(v, i, n, k)),
+            Ne(k, n) => (k, bit(v, i, n, k) ^ 1),
+        };
+        x = (x & !(1 << k)) | (value << k);
+    }
+    x
+}
+
+// round 1 step `i` of chaining values `v`, for any message word
+fn step1(v: &[u32], i: usize, m: u32) -> u32 {
+    let (a, b, c, d) = (v[i], v[i + 3], v[i + 2], v[i + 1]);
+    a.wrapping_add(f(b, c, d))
+        .wrapping_add(m)
+        .rotate_left(SHIFTS_1[i % 4])
+}
+
+// the message word for which round 1 step `i` outputs `v[i + 4]`
+fn word1(v: &[u32], i: usize) -> u32 {
+    let (a, b, c, d) = (v[i], v[i + 3], v[i + 2], v[i + 1]);
+    v[i + 4]
+        .rotate_right(SHIFTS_1[i % 4])
+        .wrapping_sub(a)
+        .wrapping_sub(f(b, c, d))
+}
+
+// round 2 step `i` (16 to 21)
+fn step2(v: &[u32], i: usize, m: &[u32; 16]) -> u32 {
+    let j = i - 16;
+    let (a, b, c, d) = (v[i], v[i + 3], v[i + 2], v[i + 1]);
+    let k = (j % 4) * 4 + j / 4;
+    a.wrapping_add(g(b, c, d))
+        .wrapping_add(m[k])
+        .wrapping_add(K2)
+        .rotate_left(SHIFTS_2[j % 4])
+}
+
+// flip bit `k` of round 1 output `i` (0 or 4, i.e. a1 or a2), then rewrite the 5 message words
+// involved so that the following 4 chaining values stay the same
+fn flip_round1(v: &mut [u32], m: &mut [u32; 16], i: usize, k: u32) {
+    v[i + 4] ^= 1 << k;
+    for (j, word) in m.iter_mut().enumerate().skip(i).take(5) {
+        *word = word1(v, j);
+    }
+}
+
+/// Wang's message difference: `M' = M + ΔM`
+pub fn differential(m: &[u32; 16]) -> [u32; 16] {
+    let mut m2 = *m;
+    m2[1] = m2[1].wrapping_add(1 << 31);
+    m2[2] = m2[2].wrapping_add((1 << 31) - (1 << 28));
+    m2[12] = m2[12].wrapping_sub(1 << 16);
+    m2
+}
+
+// a random block meeting the round 1 conditions, and the `a5` and `d5` ones when the
+// modifications do not carry
+fn candidate(rng: &mut Rng) -> [u32; 16] {
+    let mut m = [0u32; 16];
+    let mut v = [0u32; 26];
+    v[..4].copy_from_slice(&[IV[0], IV[3], IV[2], IV[1]]);
+    for i in 0..16 {
+        let x = step1(&v, i, rng.next_u32());
+        v[i + 4] = enforce(&v, i, x);
+        m[i] = word1(&v, i);
+    }
+
+    // a5 and d5 use m0 and m4 with rotations 3 and 5, flipping bit k of a1 (resp. a2) flips
+    // bit k (resp. k + 2) of a5 (resp. d5), none of which is constrained in round 1
+    for &(i, step, shift) in [(0, 16, 0), (4, 17, 2)].iter() {
+        for _ in 0..4 {
+            v[step + 4] = step2(&v, step, &m);
+            if satisfies(&v, step) {
+                break;
+            }
+            let wrong = v[step + 4] ^ enforce(&v, step, v[step + 4]);
+            for k in 0..32 {
+                if (wrong >> k) & 1 == 1 {
+                    flip_round1(&mut v, &mut m, i, k - shift);
+                }
+            }
+        }
+    }
+    m
+}
+
+fn to_block(m: &[u32; 16]) -> [u8; 64] {
+    let mut block = [0u8; 64];
+    for (bytes, word) in block.chunks_mut(4).zip(m.iter()) {
+        bytes.copy_from_slice(&word.to_le_bytes());
+    }
+    block
+}
+
+fn compress(block: &[u8; 64]) -> [u32; 4] {
+    let mut state = Md4State::default();
+    state.process_block(GenericArray::from_slice(&block[..]));
+    state.words()
+}
+
+/// Two distinct 64-byte blocks with the same MD4 compression from the IV, hence the same MD4
+/// digest (as messages, or followed by any common suffix). The search is deterministic in `seed`.
+pub fn find_collision(seed: u64) -> ([u8; 64], [u8; 64]) {
+    let mut rng = Rng(seed.wrapping_mul(2).wrapping_add(1));
+    loop {
+        let m = candidate(&mut rng);
+        let (b1, b2) = (to_block(&m), to_block(&differential(&m)));
+        if compress(&b1) == compress(&b2) {
+            return (b1, b2);
+        }
+    }
+}
+
+/// Whether `m1` and `m2` are distinct blocks with the same MD4 compression from the IV
+pub fn verify(m1: &[u8; 64], m2: &[u8; 64]) -> bool {
+    m1 != m2 && compress(m1) == compress(m2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Digest;
+    use Md4;
+
+    #[test]
+    fn differential_is_wangs() {
+        let m2 = differential(&[0; 16]);
+        assert_eq!((m2[1], m2[2], m2[12]), (0x8000_0000, 0x7000_0000, 0xffff_0000));
+        assert_eq!(m2.iter().filter(|&&w| w != 0).count(), 3);
+    }
+
+    #[test]
+    fn finds_md4_collision() {
+        let (m1, m2) = find_collision(1);
+        assert!(verify(&m1, &m2));
+        assert_eq!(Md4::digest(&m1), Md4::digest(&m2));
+        assert_eq!(
+            Md4::digest(&[&m1[..], b"same suffix"].concat()),
+            Md4::digest(&[&m2[..], b"same suffix"].concat())
+        );
+    }
+}
@@ -44,13 +44,16 @@ pub use digest::Digest;
 use digest::{BlockInput, FixedOutput, Input, Reset};
 use simd::u32x4;
 
+pub mod collision;
+
 // initial values for Md4State
 const S: u32x4 = u32x4(0x6745_2301, 0xEFCD_AB89, 0x98BA_DCFE, 0x1032_5476);
 
 type Block = GenericArray<u8, U64>;
 
+/// The MD4 chaining value
 #[derive(Copy, Clone)]
-struct Md4State {
+pub struct Md4State {
     s: u32x4,
 }
 
@@ -63,7 +66,19 @@ pub struct Md4 {
 }
 
 impl Md4State {
-    fn process_block(&mut self, input: &Block) {
+    pub fn from_words(s: [u32; 4]) -> Md4State {
+        Md4State {
+            s: u32x4(s[0], s[1], s[2], s[3]),
+        }
+    }
+
+    pub fn words(&self) -> [u32; 4] {
+        let u32x4(a, b, c, d) = self.s;
+        [a, b, c, d]
+    }
+
+    /// The MD4 compression function, processing one block into the state
+    pub fn process_block(&mut self, input: &Block) {
         fn f(x: u32, y: u32, z: u32) -> u32 {
             (x & y) | (!x & z)
         }
@@ -131,6 +146,13 @@ impl Md4State {
     }
 }
 
+/// The MD4 compression function on plain words, same as `Md4State::process_block`
+pub fn compress(state: &mut [u32; 4], block: &Block) {
+    let mut md4_state = Md4State::from_words(*state);
+    md4_state.process_block(block);
+    *state = md4_state.words();
+}
+
 impl Default for Md4State {
     fn default() -> Self {
         Md4State { s: S }
@@ -145,14 +167,40 @@ impl Md4 {
             .len64_padding::<LE, _>(l, |d| state.process_block(d))
     }
 
-    pub fn result_without_padding(&mut self) -> GenericArray<u8, U16> {
-        let mut out = GenericArray::default();
-        LE::write_u32(&mut out[0..4], self.state.s.0);
-        LE::write_u32(&mut out[4..8], self.state.s.1);
-        LE::write_u32(&mut out[8..12], self.state.s.2);
-        LE::write_u32(&mut out[12..16], self.state.s.3);
+    /// Resume a computation from the chaining value `state` after `length_bytes` bytes of
+    /// input, e.g. from a digest for a length extension. `length_bytes` must be a multiple of
+    /// the block size (64).
+    ///
+    /// ```rust
+    /// # extern crate md4;
+    /// use md4::{Digest, Md4};
+    ///
+    /// let mut h = Md4::new();
+    /// h.input(&[0u8; 128][..]);
+    /// let mut resumed = Md4::from_state(h.state(), 128);
+    /// h.input(b"suffix");
+    /// resumed.input(b"suffix");
+    /// assert_eq!(h.result(), resumed.result());
+    /// ```
+    pub fn from_state(state: [u32; 4], length_bytes: u64) -> Md4 {
+        if !length_bytes.is_multiple_of(64) {
+            panic!("Invalid length, should be a multiple of the block size");
+        }
+        Md4 {
+            length_bytes,
+            buffer: Default::default(),
+            state: Md4State::from_words(state),
+        }
+    }
 
-        out
+    /// The current chaining value, i.e. the state after the last complete block of input
+    pub fn state(&self) -> [u32; 4] {
+        self.state.words()
+    }
+
+    /// Number of bytes of input so far
+    pub fn input_len(&self) -> u64 {
+        self.length_bytes
     }
 }
 
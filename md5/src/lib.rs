@@ -0,0 +1,225 @@
+//! An implementation of the [MD5][1] cryptographic hash algorithm.
+//!
+//! # Usage
+//!
+//! ```rust
+//! # #[macro_use] extern crate hex_literal;
+//! # extern crate md5;
+//! # fn main() {
+//! use md5::{Md5, Digest};
+//!
+//! // create a Md5 hasher instance
+//! let mut hasher = Md5::new();
+//!
+//! // process input message
+//! hasher.input(b"hello world");
+//!
+//! // acquire hash digest in the form of GenericArray,
+//! // which in this case is equivalent to [u8; 16]
+//! let result = hasher.result();
+//! assert_eq!(result[..], hex!("5eb63bbbe01eeed093cb22bb8f5acdc3"));
+//! # }
+//! ```
+//!
+//! Also see [RustCrypto/hashes][2] readme.
+//!
+//! [1]: https://en.wikipedia.org/wiki/MD5
+//! [2]: https://github.com/RustCrypto/hashes
+#![no_std]
+#![doc(html_logo_url = "https://raw.githubusercontent.com/RustCrypto/meta/master/logo_small.png")]
+#[macro_use]
+extern crate opaque_debug;
+#[macro_use]
+pub extern crate digest;
+extern crate block_buffer;
+#[cfg(feature = "std")]
+extern crate std;
+#[cfg(test)]
+#[macro_use]
+extern crate hex_literal;
+use block_buffer::byteorder::{ByteOrder, LE};
+use block_buffer::BlockBuffer;
+use digest::generic_array::typenum::{U16, U64};
+use digest::generic_array::GenericArray;
+pub use digest::Digest;
+use digest::{BlockInput, FixedOutput, Input, Reset};
+
+// initial chaining value
+const S: [u32; 4] = [0x6745_2301, 0xEFCD_AB89, 0x98BA_DCFE, 0x1032_5476];
+
+// floor(abs(sin(i + 1)) * 2^32)
+#[rustfmt::skip]
+const K: [u32; 64] = [
+    0xd76a_a478, 0xe8c7_b756, 0x2420_70db, 0xc1bd_ceee,
+    0xf57c_0faf, 0x4787_c62a, 0xa830_4613, 0xfd46_9501,
+    0x6980_98d8, 0x8b44_f7af, 0xffff_5bb1, 0x895c_d7be,
+    0x6b90_1122, 0xfd98_7193, 0xa679_438e, 0x49b4_0821,
+    0xf61e_2562, 0xc040_b340, 0x265e_5a51, 0xe9b6_c7aa,
+    0xd62f_105d, 0x0244_1453, 0xd8a1_e681, 0xe7d3_fbc8,
+    0x21e1_cde6, 0xc337_07d6, 0xf4d5_0d87, 0x455a_14ed,
+    0xa9e3_e905, 0xfcef_a3f8, 0x676f_02d9, 0x8d2a_4c8a,
+    0xfffa_3942, 0x8771_f681, 0x6d9d_6122, 0xfde5_380c,
+    0xa4be_ea44, 0x4bde_cfa9, 0xf6bb_4b60, 0xbebf_bc70,
+    0x289b_7ec6, 0xeaa1_27fa, 0xd4ef_3085, 0x0488_1d05,
+    0xd9d4_d039, 0xe6db_99e5, 0x1fa2_7cf8, 0xc4ac_5665,
+    0xf429_2244, 0x432a_ff97, 0xab94_23a7, 0xfc93_a039,
+    0x655b_59c3, 0x8f0c_cc92, 0xffef_f47d, 0x8584_5dd1,
+    0x6fa8_7e4f, 0xfe2c_e6e0, 0xa301_4314, 0x4e08_11a1,
+    0xf753_7e82, 0xbd3a_f235, 0x2ad7_d2bb, 0xeb86_d391,
+];
+
+// rotations, per round
+const R: [[u32; 4]; 4] = [[7, 12, 17, 22], [5, 9, 14, 20], [4, 11, 16, 23], [6, 10, 15, 21]];
+
+type Block = GenericArray<u8, U64>;
+
+/// The MD5 compression function, processing one block into the state
+pub fn compress(state: &mut [u32; 4], block: &Block) {
+    let mut data = [0u32; 16];
+    LE::read_u32_into(block, &mut data);
+
+    let (mut a, mut b, mut c, mut d) = (state[0], state[1], state[2], state[3]);
+    for i in 0..64 {
+        let (f, g) = match i / 16 {
+            0 => ((b & c) | (!b & d), i),
+            1 => ((b & d) | (c & !d), (5 * i + 1) % 16),
+            2 => (b ^ c ^ d, (3 * i + 5) % 16),
+            _ => (c ^ (b | !d), (7 * i) % 16),
+        };
+        let t = a
+            .wrapping_add(f)
+            .wrapping_add(K[i])
+            .wrapping_add(data[g])
+            .rotate_left(R[i / 16][i % 4]);
+        a = d;
+        d = c;
+        c = b;
+        b = b.wrapping_add(t);
+    }
+
+    state[0] = state[0].wrapping_add(a);
+    state[1] = state[1].wrapping_add(b);
+    state[2] = state[2].wrapping_add(c);
+    state[3] = state[3].wrapping_add(d);
+}
+
+/// The MD5 hasher
+#[derive(Clone)]
+pub struct Md5 {
+    length_bytes: u64,
+    buffer: BlockBuffer<U64>,
+    state: [u32; 4],
+}
+
+impl Default for Md5 {
+    fn default() -> Self {
+        Md5 {
+            length_bytes: 0,
+            buffer: Default::default(),
+            state: S,
+        }
+    }
+}
+
+impl Md5 {
+    /// Resume a computation from the chaining value `state` after `length_bytes` bytes of
+    /// input, e.g. from a digest for a length extension. `length_bytes` must be a multiple of
+    /// the block size (64).
+    ///
+    /// ```rust
+    /// # extern crate md5;
+    /// use md5::{Digest, Md5};
+    ///
+    /// let mut h = Md5::new();
+    /// h.input(&[0u8; 128][..]);
+    /// let mut resumed = Md5::from_state(h.state(), 128);
+    /// h.input(b"suffix");
+    /// resumed.input(b"suffix");
+    /// assert_eq!(h.result(), resumed.result());
+    /// ```
+    pub fn from_state(state: [u32; 4], length_bytes: u64) -> Md5 {
+        if !length_bytes.is_multiple_of(64) {
+            panic!("Invalid length, should be a multiple of the block size");
+        }
+        Md5 {
+            length_bytes,
+            buffer: Default::default(),
+            state,
+        }
+    }
+
+    /// The current chaining value, i.e. the state after the last complete block of input
+    pub fn state(&self) -> [u32; 4] {
+        self.state
+    }
+
+    /// Number of bytes of input so far
+    pub fn input_len(&self) -> u64 {
+        self.length_bytes
+    }
+}
+
+impl BlockInput for Md5 {
+    type BlockSize = U64;
+}
+
+impl Input for Md5 {
+    fn input<B: AsRef<[u8]>>(&mut self, input: B) {
+        let input = input.as_ref();
+        // the length is defined modulo 2^64, like MD4's
+        self.length_bytes = self.length_bytes.wrapping_add(input.len() as u64);
+        let state = &mut self.state;
+        self.buffer.input(input, |d: &Block| compress(state, d));
+    }
+}
+
+impl FixedOutput for Md5 {
+    type OutputSize = U16;
+
+    fn fixed_result(mut self) -> GenericArray<u8, Self::OutputSize> {
+        {
+            let state = &mut self.state;
+            let l = self.length_bytes << 3;
+            self.buffer.len64_padding::<LE, _>(l, |d| compress(state, d));
+        }
+        let mut out = GenericArray::default();
+        LE::write_u32_into(&self.state, &mut out);
+        out
+    }
+}
+
+impl Reset for Md5 {
+    fn reset(&mut self) {
+        self.state = S;
+        self.length_bytes = 0;
+        self.buffer.reset();
+    }
+}
+
+impl_opaque_debug!(Md5);
+impl_write!(Md5);
+
+#[cfg(test)]
+mod tests {
+    use super::{Digest, Md5};
+
+    #[test]
+    fn md5_reference_digests() {
+        assert_eq!(Md5::digest(b"")[..], hex!("d41d8cd98f00b204e9800998ecf8427e"));
+        assert_eq!(Md5::digest(b"abc")[..], hex!("900150983cd24fb0d6963f7d28e17f72"));
+        assert_eq!(
+            Md5::digest(&[b'a'; 200][..])[..],
+            hex!("887f30b43b2867f4a9accceee7d16e6c")
+        );
+    }
+
+    #[test]
+    fn resume_from_state() {
+        let mut h = Md5::new();
+        h.input(&[b'a'; 70][..]);
+        assert_eq!(h.input_len(), 70);
+        let mut resumed = Md5::from_state(h.state(), 64);
+        resumed.input(&[b'a'; 136][..]);
+        assert_eq!(resumed.result()[..], hex!("887f30b43b2867f4a9accceee7d16e6c"));
+    }
+}
@@ -0,0 +1,252 @@
+// State recovery of MT19937 from arbitrary bit leaks.
+//
+// The generator is linear over GF(2): every output bit is the XOR of some bits of 624 consecutive
+// words x[0..624] of the sequence x[k+n] = x[k+m] ^ A(upper(x[k]) | lower(x[k+1])). Only the top
+// bit of x[0] enters the sequence, thus the actual state is 1 + 623 * 32 = 19937 bits. Each leaked
+// bit (or parity of bits) of an output is a linear equation over those, and once the equations
+// reach full rank, Gaussian elimination gives the state back.
+use super::clone::Tempering;
+use super::gf2::{BitVec, Solver};
+use super::mt19937::{Coefficients, MT19937Rng, COEFF_32};
+
+// Symbolic word: the i-th entry is bit i of the word as a linear combination of the unknown bits
+type SymWord = Vec<BitVec>;
+
+/// Linear model of the MT19937 outputs, recovering the state from leaked output bits.
+///
+/// Observations must be added in stream order (equal indices are fine), as only the last 624
+/// symbolic words are kept around.
+pub struct Recovery {
+    // word index of the output at position 0
+    offset: usize,
+    // symbolic words x[next-n..next], x[k] at k % n
+    ring: Vec<SymWord>,
+    next: usize,
+    // bit i of the tempered word is the XOR of the bits b such that bit i of column b is set
+    temper_columns: Vec<u32>,
+    solver: Solver,
+}
+
+impl Default for Recovery {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Recovery {
+    /// The unknowns are the words about to be tempered, i.e. output i is temper(x[i])
+    pub fn new() -> Recovery {
+        Self::with_offset(0)
+    }
+
+    /// The unknowns are the seeded state, i.e. output i is temper(x[624 + i]) where x[0..624]
+    /// comes from `init(seed)`, which allows `seed` to invert the initialization
+    pub fn after_seeding() -> Recovery {
+        Self::with_offset(COEFF_32.n as usize)
+    }
+
+    fn with_offset(offset: usize) -> Recovery {
+        let (n, w) = (COEFF_32.n as usize, COEFF_32.w as usize);
+        let vars = n * w;
+        let tempering = Tempering::from(&COEFF_32);
+        Recovery {
+            offset,
+            ring: (0..n)
+                .map(|j| (0..w).map(|b| BitVec::unit(vars, w * j + b)).collect())
+                .collect(),
+            next: n,
+            temper_columns: (0..w).map(|b| tempering.temper(1 << b) as u32).collect(),
+            solver: Solver::new(vars),
+        }
+    }
+
+    // symbolic x[k], generating the sequence up to k
+    fn word(&mut self, k: usize) -> &SymWord {
+        let n = COEFF_32.n as usize;
+        if k + n < self.next {
+            panic!("Observations should be added in stream order");
+        }
+        while self.next <= k {
+            let p = self.next;
+            self.ring[p % n] = twist(
+                &self.ring[p % n],
+                &self.ring[(p + 1) % n],
+                &self.ring[(p + COEFF_32.m as usize) % n],
+                &COEFF_32,
+            );
+            self.next += 1;
+        }
+        &self.ring[k % n]
+    }
+
+    // bit i of the output at `index` as a linear combination of the unknowns
+    fn output_bit(&mut self, index: usize, i: u32) -> BitVec {
+        let columns = self.temper_columns.clone();
+        let word = self.word(index + self.offset);
+        let mut row = BitVec::new(word[0].len());
+        for (b, column) in columns.iter().enumerate() {
+            if column >> i & 1 == 1 {
+                row.xor_assign(&word[b]);
+            }
+        }
+        row
+    }
+
+    /// The bits of the output at `index` selected by `mask` are the ones of `value`, e.g. the top
+    /// byte with mask 0xff000000, or the low bit of `rand() % 6` with mask 1
+    pub fn observe_bits(&mut self, index: usize, mask: u32, value: u32) {
+        for i in (0..32).filter(|i| mask >> i & 1 == 1) {
+            let row = self.output_bit(index, i);
+            self.solver.add(row, value >> i & 1 == 1);
+        }
+    }
+
+    /// The XOR of the bits of the output at `index` selected by `mask` is `parity`
+    pub fn observe_parity(&mut self, index: usize, mask: u32, parity: bool) {
+        let mut row = BitVec::new(self.solver.vars());
+        for i in (0..32).filter(|i| mask >> i & 1 == 1) {
+            row.xor_assign(&self.output_bit(index, i));
+        }
+        self.solver.add(row, parity);
+    }
+
+    /// Number of independent equations so far, at most 19937 (plus 31 with `new` when the output
+    /// at index 0 is observed)
+    pub fn rank(&self) -> usize {
+        self.solver.rank()
+    }
+
+    /// Whether the observations contradict each other, i.e. they don't come from MT19937
+    pub fn is_inconsistent(&self) -> bool {
+        self.solver.is_inconsistent()
+    }
+
+    /// Whether the 19937-bit state is uniquely determined
+    pub fn is_determined(&self) -> bool {
+        self.solver.determines(COEFF_32.r as usize..self.solver.vars())
+    }
+
+    /// The recovered words x[0..624], the lower bits of x[0] being zero unless observed
+    pub fn state(&self) -> Option<Vec<u32>> {
+        if !self.is_determined() {
+            return None;
+        }
+        let x = self.solver.solve()?;
+        let w = COEFF_32.w as usize;
+        Some(
+            (0..COEFF_32.n as usize)
+                .map(|j| (0..w).fold(0, |acc, b| acc | (x.get(w * j + b) as u32) << b))
+                .collect(),
+        )
+    }
+
+    /// A clone whose next output is the one at index 0
+    pub fn rng(&self) -> Option<MT19937Rng> {
+        Some(MT19937Rng::from(&self.state()?, self.offset))
+    }
+
+    /// Invert the initialization of the recovered seeded state (see `after_seeding`), `None` if
+    /// it doesn't come from `MT19937Rng::new`
+    pub fn seed(&self) -> Option<u32> {
+        if self.offset != COEFF_32.n as usize {
+            return None;
+        }
+        let mt = self.state()?;
+        let f = COEFF_32.f;
+        // mt[1] = f * (seed ^ (seed >> 30)) + 1, f is odd hence invertible modulo 2^32
+        let f_inv = (0..5).fold(f, |inv, _| {
+            inv.wrapping_mul(2u32.wrapping_sub(f.wrapping_mul(inv)))
+        });
+        let y = mt[1].wrapping_sub(1).wrapping_mul(f_inv);
+        let seed = y ^ (y >> 30);
+
+        let mut expected = seed;
+        for (i, word) in mt.iter().enumerate().skip(1) {
+            expected = f
+                .wrapping_mul(expected ^ (expected >> (COEFF_32.w - 2)))
+                .wrapping_add(i as u32);
+            if *word != expected {
+                return None;
+            }
+        }
+        if seed >> 31 != mt[0] >> 31 {
+            return None;
+        }
+        Some(seed)
+    }
+}
+
+// Symbolic x[k+n] from x[k], x[k+1] and x[k+m]
+fn twist(x_k: &[BitVec], x_k1: &[BitVec], x_km: &[BitVec], coeff: &Coefficients<u32>) -> SymWord {
+    let (w, r) = (coeff.w as usize, coeff.r as usize);
+    // y = upper(x[k]) | lower(x[k+1])
+    let y: Vec<&BitVec> = (0..w).map(|b| if b >= r { &x_k[b] } else { &x_k1[b] }).collect();
+    (0..w)
+        .map(|b| {
+            // bit b of x[k+m] ^ (y >> 1) ^ (y & 1) * a
+            let mut bit = x_km[b].clone();
+            if b + 1 < w {
+                bit.xor_assign(y[b + 1]);
+            }
+            if coeff.a >> b & 1 == 1 {
+                bit.xor_assign(y[0]);
+            }
+            bit
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mt19937::RngCore;
+
+    #[test]
+    fn recover_seed_from_top_bytes() {
+        let mut origin = MT19937Rng::new(0xdead_beef);
+        let mut recovery = Recovery::after_seeding();
+        let mut index = 0;
+        while !recovery.is_determined() {
+            recovery.observe_bits(index, 0xff00_0000, origin.next_u32());
+            index += 1;
+        }
+        assert!(!recovery.is_inconsistent());
+        assert_eq!(recovery.seed(), Some(0xdead_beef));
+
+        let mut clone = recovery.rng().unwrap();
+        let mut origin = MT19937Rng::new(0xdead_beef);
+        for _ in 0..1000 {
+            assert_eq!(origin.next_u32(), clone.next_u32());
+        }
+    }
+
+    #[test]
+    fn recover_from_every_third_output() {
+        let mut origin = MT19937Rng::new(42);
+        let outputs: Vec<u32> = (0..2100).map(|_| origin.next_u32()).collect();
+        let mut recovery = Recovery::new();
+        for i in (0..outputs.len()).step_by(3) {
+            recovery.observe_bits(i, u32::MAX, outputs[i]);
+        }
+        let mut clone = recovery.rng().unwrap();
+        for output in outputs.iter() {
+            assert_eq!(*output, clone.next_u32());
+        }
+        // not a seeded state
+        assert_eq!(recovery.seed(), None);
+    }
+
+    #[test]
+    fn recover_from_dice_rolls() {
+        // rand() % 6 leaks the low bit of every output
+        let mut origin = MT19937Rng::new(2020);
+        let mut recovery = Recovery::after_seeding();
+        let mut index = 0;
+        while !recovery.is_determined() {
+            let roll = origin.next_u32() % 6;
+            recovery.observe_parity(index, 1, roll & 1 == 1);
+            index += 1;
+        }
+        assert_eq!(recovery.seed(), Some(2020));
+    }
+}
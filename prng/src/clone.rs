@@ -0,0 +1,250 @@
+// Cloning a Mersenne Twister from its outputs.
+//
+// Tempering is invertible, thus n consecutive outputs give n consecutive words of the sequence
+// x[k+n] = x[k+m] ^ A(upper(x[k]) | lower(x[k+1])), which is all the state there is. Missing or
+// truncated outputs go through the GF(2) model of `analysis`.
+use super::analysis::Recovery;
+use super::mt19937::RngCore;
+use super::{mt19937, mt19937_64};
+
+/// Tempering parameters of a Mersenne Twister, widened to u64 to serve both word sizes
+#[derive(Debug, Clone, Copy)]
+pub struct Tempering {
+    pub w: u8,
+    pub u: u32,
+    pub d: u64,
+    pub s: u32,
+    pub b: u64,
+    pub t: u32,
+    pub c: u64,
+    pub l: u32,
+}
+
+impl From<&mt19937::Coefficients<u32>> for Tempering {
+    fn from(coeff: &mt19937::Coefficients<u32>) -> Tempering {
+        Tempering {
+            w: coeff.w,
+            u: coeff.u,
+            d: coeff.d as u64,
+            s: coeff.s,
+            b: coeff.b as u64,
+            t: coeff.t,
+            c: coeff.c as u64,
+            l: coeff.l,
+        }
+    }
+}
+
+impl From<&mt19937_64::Coefficients> for Tempering {
+    fn from(coeff: &mt19937_64::Coefficients) -> Tempering {
+        Tempering {
+            w: coeff.w,
+            u: coeff.u as u32,
+            d: coeff.d as u64,
+            s: coeff.s as u32,
+            b: coeff.b as u64,
+            t: coeff.t as u32,
+            c: coeff.c as u64,
+            l: coeff.l as u32,
+        }
+    }
+}
+
+impl Tempering {
+    fn word_mask(&self) -> u64 {
+        u64::MAX >> (64 - self.w as u32)
+    }
+
+    pub fn temper(&self, x: u64) -> u64 {
+        let mut y = x & self.word_mask();
+        y ^= (y >> self.u) & self.d;
+        y ^= (y << self.s) & self.b;
+        y ^= (y << self.t) & self.c;
+        y ^= y >> self.l;
+        y & self.word_mask()
+    }
+
+    /// Inverse of `temper`, undoing each step in reverse order
+    pub fn untemper(&self, y: u64) -> u64 {
+        let mut x = y & self.word_mask();
+        x = self.unshift_right(x, self.l, self.word_mask());
+        x = self.unshift_left(x, self.t, self.c);
+        x = self.unshift_left(x, self.s, self.b);
+        x = self.unshift_right(x, self.u, self.d);
+        x
+    }
+
+    // invert y = x ^ ((x >> shift) & mask): each iteration recovers `shift` more top bits
+    fn unshift_right(&self, y: u64, shift: u32, mask: u64) -> u64 {
+        let mut x = y;
+        for _ in 0..self.w as u32 / shift {
+            x = y ^ ((x >> shift) & mask);
+        }
+        x
+    }
+
+    // invert y = x ^ ((x << shift) & mask): each iteration recovers `shift` more bottom bits
+    fn unshift_left(&self, y: u64, shift: u32, mask: u64) -> u64 {
+        let mut x = y;
+        for _ in 0..self.w as u32 / shift {
+            x = y ^ ((x << shift) & mask);
+        }
+        x & self.word_mask()
+    }
+}
+
+/// Clone a 32-bit MT19937 from at least 624 consecutive outputs, starting anywhere in the stream.
+/// The clone continues right after the last output.
+pub fn clone_from_outputs(outputs: &[u32]) -> mt19937::MT19937Rng {
+    let coeff = &mt19937::COEFF_32;
+    let n = coeff.n as usize;
+    if outputs.len() < n {
+        panic!("Not enough outputs, expecting at least {} consecutive ones", n);
+    }
+    let tempering = Tempering::from(coeff);
+    let mt: Vec<u32> = outputs[outputs.len() - n..]
+        .iter()
+        .map(|y| tempering.untemper(*y as u64) as u32)
+        .collect();
+    // the twist computes x[k+n] for k = 0..n in place, which holds for any alignment
+    mt19937::MT19937Rng::from(&mt, n)
+}
+
+/// Clone a 64-bit MT19937 from at least 312 consecutive outputs, see `clone_from_outputs`
+pub fn clone_from_outputs_64(outputs: &[u64]) -> mt19937_64::MT19937Rng {
+    let coeff = &mt19937_64::COEFF_64;
+    if outputs.len() < coeff.n {
+        panic!(
+            "Not enough outputs, expecting at least {} consecutive ones",
+            coeff.n
+        );
+    }
+    let tempering = Tempering::from(coeff);
+    let mt: Vec<usize> = outputs[outputs.len() - coeff.n..]
+        .iter()
+        .map(|y| tempering.untemper(*y) as usize)
+        .collect();
+    mt19937_64::MT19937Rng::from(&mt, coeff.n)
+}
+
+/// Partial knowledge of the 32-bit output at position `index` of the stream: the bits of `value`
+/// selected by `mask`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Observation {
+    pub index: usize,
+    pub value: u32,
+    pub mask: u32,
+}
+
+impl Observation {
+    pub fn full(index: usize, value: u32) -> Observation {
+        Observation {
+            index,
+            value,
+            mask: u32::MAX,
+        }
+    }
+
+    /// Only the top `bits` bits are known, e.g. `output >> 24` for the top byte
+    pub fn truncated(index: usize, top_bits: u32, bits: u32) -> Observation {
+        if bits == 0 || bits > 32 {
+            panic!("Invalid number of bits, should be within [1, 32]");
+        }
+        Observation {
+            index,
+            value: top_bits << (32 - bits),
+            mask: u32::MAX << (32 - bits),
+        }
+    }
+}
+
+/// Clone a 32-bit MT19937 from partial observations: gaps, unaligned positions and truncated
+/// outputs. Returns `None` if the observations don't determine the state or are inconsistent. The
+/// clone continues right after the last observed position.
+pub fn clone_from_observations(observations: &[Observation]) -> Option<mt19937::MT19937Rng> {
+    let base = observations.iter().map(|o| o.index).min()?;
+    let mut observations = observations.to_vec();
+    observations.sort_by_key(|o| o.index);
+
+    let mut recovery = Recovery::new();
+    for obs in observations.iter() {
+        recovery.observe_bits(obs.index - base, obs.mask, obs.value);
+    }
+    if recovery.is_inconsistent() {
+        return None;
+    }
+    let mut rng = recovery.rng()?;
+    for _ in base..=observations[observations.len() - 1].index {
+        rng.next_u32();
+    }
+    Some(rng)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mt19937::COEFF_32;
+
+    #[test]
+    fn untemper_inverts_temper() {
+        let (t32, t64) = (Tempering::from(&COEFF_32), Tempering::from(&mt19937_64::COEFF_64));
+        let mut rng = mt19937::MT19937Rng::new(42);
+        for _ in 0..100 {
+            let x = rng.next_u64();
+            assert_eq!(t32.untemper(t32.temper(x & 0xffff_ffff)), x & 0xffff_ffff);
+            assert_eq!(t64.untemper(t64.temper(x)), x);
+        }
+    }
+
+    #[test]
+    fn clone_unaligned_outputs() {
+        let mut origin = mt19937::MT19937Rng::new(5489);
+        (0..100).for_each(|_| {
+            origin.next_u32();
+        });
+        let outputs: Vec<u32> = (0..700).map(|_| origin.next_u32()).collect();
+        let mut clone = clone_from_outputs(&outputs);
+        for _ in 0..1000 {
+            assert_eq!(origin.next_u32(), clone.next_u32());
+        }
+
+        let mut origin = mt19937_64::MT19937Rng::new(5489);
+        origin.next_u64();
+        let outputs: Vec<u64> = (0..312).map(|_| origin.next_u64()).collect();
+        let mut clone = clone_from_outputs_64(&outputs);
+        for _ in 0..1000 {
+            assert_eq!(origin.next_u64(), clone.next_u64());
+        }
+    }
+
+    #[test]
+    fn clone_with_gaps() {
+        let mut origin = mt19937::MT19937Rng::new(1234);
+        let outputs: Vec<u32> = (0..1300).map(|_| origin.next_u32()).collect();
+        // every 10th output is missing
+        let observations: Vec<Observation> = (50..1300)
+            .filter(|i| i % 10 != 3)
+            .map(|i| Observation::full(i, outputs[i]))
+            .collect();
+        let mut clone = clone_from_observations(&observations).unwrap();
+        for _ in 0..1000 {
+            assert_eq!(origin.next_u32(), clone.next_u32());
+        }
+
+        // too few observations
+        assert!(clone_from_observations(&observations[..600]).is_none());
+    }
+
+    #[test]
+    fn clone_from_truncated_outputs() {
+        let mut origin = mt19937::MT19937Rng::new(4321);
+        // only the top 16 bits of each output leak
+        let observations: Vec<Observation> = (0..1350)
+            .map(|i| Observation::truncated(i, origin.next_u32() >> 16, 16))
+            .collect();
+        let mut clone = clone_from_observations(&observations).unwrap();
+        for _ in 0..1000 {
+            assert_eq!(origin.next_u32(), clone.next_u32());
+        }
+    }
+}
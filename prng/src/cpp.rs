@@ -0,0 +1,137 @@
+// C++ `<random>` as implemented by libstdc++ (GCC 11 and later), on top of `std::mt19937`.
+//
+// `std::mt19937` itself is `MT19937Rng`: same `init_genrand` seeding (default seed 5489) and raw
+// 32-bit outputs. What differs between standard libraries is how distributions consume them, and
+// the `std::seed_seq` seeding.
+use super::mt19937::{MT19937Rng, RngCore, COEFF_32};
+
+/// Same as `std::mt19937(std::seed_seq{...})`
+pub fn seed_seq(seeds: &[u32]) -> MT19937Rng {
+    let n = COEFF_32.n as usize;
+    let (s, t) = (seeds.len(), 11);
+    let (p, q) = ((n - t) / 2, (n - t) / 2 + t);
+    let scramble = |x: u32| x ^ (x >> 27);
+
+    let mut words = vec![0x8b8b_8b8b_u32; n];
+    for k in 0..(s + 1).max(n) {
+        let x = words[k % n] ^ words[(k + p) % n] ^ words[(k + n - 1) % n];
+        let r1 = scramble(x).wrapping_mul(1664525);
+        let r2 = r1.wrapping_add(match k {
+            0 => s as u32,
+            k if k <= s => ((k % n) as u32).wrapping_add(seeds[k - 1]),
+            k => (k % n) as u32,
+        });
+        words[(k + p) % n] = words[(k + p) % n].wrapping_add(r1);
+        words[(k + q) % n] = words[(k + q) % n].wrapping_add(r2);
+        words[k % n] = r2;
+    }
+    let m = (s + 1).max(n);
+    for k in m..m + n {
+        let r3 = scramble(
+            words[k % n]
+                .wrapping_add(words[(k + p) % n])
+                .wrapping_add(words[(k + n - 1) % n]),
+        )
+        .wrapping_mul(1566083941);
+        let r4 = r3.wrapping_sub((k % n) as u32);
+        words[(k + p) % n] ^= r3;
+        words[(k + q) % n] ^= r4;
+        words[k % n] = r4;
+    }
+    // an all-zero state (but the lower bits of the first word) is replaced
+    if words[0] >> COEFF_32.r == 0 && words[1..].iter().all(|w| *w == 0) {
+        words[0] = 1 << 31;
+    }
+    MT19937Rng::from(&words, n)
+}
+
+/// `std::uniform_int_distribution<T>(a, b)`, both ends included
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UniformInt {
+    a: i64,
+    b: i64,
+}
+
+impl UniformInt {
+    pub fn new(a: i64, b: i64) -> UniformInt {
+        if a > b {
+            panic!("Invalid range, a > b");
+        }
+        UniformInt { a, b }
+    }
+
+    pub fn sample<R: RngCore>(&self, rng: &mut R) -> i64 {
+        let urange = (self.b as u64).wrapping_sub(self.a as u64);
+        (self.a as u64).wrapping_add(Self::sample_below(rng, urange)) as i64
+    }
+
+    // uniform in [0, urange] out of 32-bit outputs
+    fn sample_below<R: RngCore>(rng: &mut R, urange: u64) -> u64 {
+        let urngrange = u32::MAX as u64;
+        if urange < urngrange {
+            // Lemire's nearly divisionless method
+            let range = urange as u32 + 1;
+            let mut product = rng.next_u32() as u64 * range as u64;
+            if (product as u32) < range {
+                let threshold = range.wrapping_neg() % range;
+                while (product as u32) < threshold {
+                    product = rng.next_u32() as u64 * range as u64;
+                }
+            }
+            product >> 32
+        } else if urange > urngrange {
+            // high part recursively, then one output for the low part
+            loop {
+                let high = Self::sample_below(rng, urange / (urngrange + 1));
+                let tmp = high.wrapping_mul(urngrange + 1);
+                let ret = tmp.wrapping_add(rng.next_u32() as u64);
+                if ret <= urange && ret >= tmp {
+                    break ret;
+                }
+            }
+        } else {
+            rng.next_u32() as u64
+        }
+    }
+}
+
+/// Same as `std::generate_canonical<double, 53>`, uniform in [0, 1) out of 2 outputs
+pub fn generate_canonical<R: RngCore>(rng: &mut R) -> f64 {
+    let r = 4294967296.0;
+    let sum = rng.next_u32() as f64 + rng.next_u32() as f64 * r;
+    let ret = sum / (r * r);
+    if ret >= 1.0 {
+        // largest double below 1
+        1.0 - f64::EPSILON / 2.0
+    } else {
+        ret
+    }
+}
+
+/// Same as `std::uniform_real_distribution<double>(a, b)`
+pub fn uniform_real<R: RngCore>(rng: &mut R, a: f64, b: f64) -> f64 {
+    generate_canonical(rng) * (b - a) + a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn libstdcxx_reference_outputs() {
+        // std::mt19937 g(42); std::uniform_int_distribution<int> d(1, 6)
+        let mut rng = MT19937Rng::new(42);
+        let dice = UniformInt::new(1, 6);
+        let rolls: Vec<i64> = (0..5).map(|_| dice.sample(&mut rng)).collect();
+        assert_eq!(rolls, vec![3, 5, 6, 2, 5]);
+        // then std::uniform_int_distribution<unsigned long long> big(0, 10000000000000ULL)
+        let big = UniformInt::new(0, 10_000_000_000_000);
+        let values: Vec<i64> = (0..3).map(|_| big.sample(&mut rng)).collect();
+        assert_eq!(values, vec![7797936860860, 5970674636390, 4458846044626]);
+        // then std::uniform_real_distribution<double> r(0.0, 1.0)
+        assert_eq!(uniform_real(&mut rng, 0.0, 1.0), 0.05808361109811229);
+
+        // std::seed_seq seq{1, 2, 3}; std::mt19937 s(seq); s()
+        assert_eq!(seed_seq(&[1, 2, 3]).next_u32(), 1710881851);
+    }
+}
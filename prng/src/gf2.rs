@@ -0,0 +1,204 @@
+// Linear algebra over GF(2) with bit-packed rows.
+//
+// The Mersenne Twister is linear over GF(2): every output bit is the XOR of some state bits, so
+// partial observations become linear equations over the state bits.
+
+/// Vector over GF(2), bit i is stored in bit (i % 64) of word (i / 64)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitVec {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl BitVec {
+    /// The zero vector of `len` bits
+    pub fn new(len: usize) -> BitVec {
+        BitVec {
+            words: vec![0; len.div_ceil(64)],
+            len,
+        }
+    }
+
+    /// The vector of `len` bits with only bit `i` set
+    pub fn unit(len: usize, i: usize) -> BitVec {
+        let mut v = BitVec::new(len);
+        v.set(i, true);
+        v
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, i: usize) -> bool {
+        self.words[i / 64] >> (i % 64) & 1 == 1
+    }
+
+    pub fn set(&mut self, i: usize, bit: bool) {
+        if bit {
+            self.words[i / 64] |= 1 << (i % 64);
+        } else {
+            self.words[i / 64] &= !(1 << (i % 64));
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.words.iter().all(|w| *w == 0)
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// Index of the lowest set bit
+    pub fn first_one(&self) -> Option<usize> {
+        self.first_one_from(0)
+    }
+
+    // lowest set bit, knowing that all words before `word` are zero
+    fn first_one_from(&self, word: usize) -> Option<usize> {
+        self.words[word..]
+            .iter()
+            .position(|w| *w != 0)
+            .map(|i| (word + i) * 64 + self.words[word + i].trailing_zeros() as usize)
+    }
+
+    pub fn xor_assign(&mut self, other: &BitVec) {
+        self.xor_from(other, 0);
+    }
+
+    // XOR starting at `word`, the words of `other` before are expected to be zero
+    fn xor_from(&mut self, other: &BitVec, word: usize) {
+        for (a, b) in self.words[word..].iter_mut().zip(other.words[word..].iter()) {
+            *a ^= b;
+        }
+    }
+
+    /// Inner product <self, other>
+    pub fn dot(&self, other: &BitVec) -> bool {
+        self.words
+            .iter()
+            .zip(other.words.iter())
+            .fold(0, |acc, (a, b)| acc ^ (a & b).count_ones())
+            & 1
+            == 1
+    }
+}
+
+/// Incremental Gaussian elimination: each equation <row, x> = rhs is reduced by the pivot rows
+/// found so far and kept as a new pivot (keyed by its lowest set bit) if it is independent.
+#[derive(Debug, Clone)]
+pub struct Solver {
+    vars: usize,
+    pivots: Vec<Option<(BitVec, bool)>>,
+    rank: usize,
+    inconsistent: bool,
+}
+
+impl Solver {
+    pub fn new(vars: usize) -> Solver {
+        Solver {
+            vars,
+            pivots: vec![None; vars],
+            rank: 0,
+            inconsistent: false,
+        }
+    }
+
+    pub fn vars(&self) -> usize {
+        self.vars
+    }
+
+    pub fn rank(&self) -> usize {
+        self.rank
+    }
+
+    /// Whether some equation contradicted the previous ones
+    pub fn is_inconsistent(&self) -> bool {
+        self.inconsistent
+    }
+
+    /// Add the equation <row, x> = rhs, returns whether it was linearly independent
+    pub fn add(&mut self, mut row: BitVec, mut rhs: bool) -> bool {
+        if row.len() != self.vars {
+            panic!("Invalid equation, expecting {} variables", self.vars);
+        }
+        let mut word = 0;
+        while let Some(p) = row.first_one_from(word) {
+            word = p / 64;
+            match &self.pivots[p] {
+                Some((pivot, pivot_rhs)) => {
+                    row.xor_from(pivot, word);
+                    rhs ^= pivot_rhs;
+                }
+                None => {
+                    self.pivots[p] = Some((row, rhs));
+                    self.rank += 1;
+                    return true;
+                }
+            }
+        }
+        // 0 = rhs
+        self.inconsistent |= rhs;
+        false
+    }
+
+    /// Whether variables `vars` are all pivots, i.e. uniquely determined given that all free
+    /// variables have lower indices
+    pub fn determines(&self, vars: std::ops::Range<usize>) -> bool {
+        self.pivots[vars].iter().all(|p| p.is_some())
+    }
+
+    /// A solution with all free variables set to zero, `None` if the system is inconsistent
+    pub fn solve(&self) -> Option<BitVec> {
+        if self.inconsistent {
+            return None;
+        }
+        // back substitution from the highest pivot, each pivot row only involves higher variables
+        let mut x = BitVec::new(self.vars);
+        for (p, pivot) in self.pivots.iter().enumerate().rev() {
+            if let Some((row, rhs)) = pivot {
+                x.set(p, rhs ^ row.dot(&x));
+            }
+        }
+        Some(x)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solve_linear_system() {
+        // x0 ^ x1 = 1, x1 ^ x2 = 0, x0 ^ x2 = 1 (dependent), x2 = 1, over 100 variables
+        let row = |vars: &[usize]| {
+            let mut r = BitVec::new(100);
+            vars.iter().for_each(|v| r.set(*v, true));
+            r
+        };
+        let mut solver = Solver::new(100);
+        assert!(solver.add(row(&[0, 1]), true));
+        assert!(solver.add(row(&[1, 2]), false));
+        assert!(!solver.add(row(&[0, 2]), true));
+        assert!(solver.add(row(&[2, 70]), true));
+        assert!(solver.add(row(&[70]), false));
+        assert_eq!(solver.rank(), 4);
+        assert!(solver.determines(0..3));
+        assert!(!solver.determines(0..4));
+
+        let x = solver.solve().unwrap();
+        assert_eq!(
+            (x.get(0), x.get(1), x.get(2), x.get(70)),
+            (false, true, true, false)
+        );
+
+        assert!(!solver.add(row(&[0, 70]), true));
+        assert!(solver.is_inconsistent());
+        assert!(solver.solve().is_none());
+    }
+}
@@ -0,0 +1,157 @@
+// glibc `random()` (and `rand()`) with the default TYPE_3 state: an additive lagged Fibonacci
+// generator r[i] = r[i-3] + r[i-31] (mod 2^32) outputting r[i] >> 1.
+//
+// The dropped low bits follow the same recurrence over GF(2), and an output that exceeds the sum
+// of the two lagged ones by 1 tells that both dropped bits were set (the carry). Enough of those
+// equations give back the full state.
+use super::gf2::{BitVec, Solver};
+use super::mt19937::{impls, Error, RngCore};
+
+const DEGREE: usize = 31;
+const SEPARATION: usize = 3;
+// outputs discarded after seeding
+const DISCARD: usize = 310;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GlibcRandom {
+    // r[i-31..i], r[k] at k % 31
+    r: Vec<u32>,
+    index: usize,
+}
+
+impl GlibcRandom {
+    /// Same as `srandom(seed)`
+    pub fn new(seed: u32) -> GlibcRandom {
+        let mut r = vec![if seed == 0 { 1 } else { seed }; DEGREE];
+        // r[i] = 16807 * r[i-1] % (2^31 - 1), computed without overflowing 31 bits
+        for i in 1..DEGREE {
+            let word = r[i - 1] as i32;
+            let (hi, lo) = (word / 127773, word % 127773);
+            let mut word = 16807 * lo - 2836 * hi;
+            if word < 0 {
+                word += 2147483647;
+            }
+            r[i] = word as u32;
+        }
+        // r[31..34] are copies of r[0..3], which is what the ring already holds
+        let mut rng = GlibcRandom {
+            r,
+            index: DEGREE + SEPARATION,
+        };
+        for _ in 0..DISCARD {
+            rng.step();
+        }
+        rng
+    }
+
+    fn step(&mut self) -> u32 {
+        let i = self.index;
+        let word = self.r[i % DEGREE].wrapping_add(self.r[(i - SEPARATION) % DEGREE]);
+        self.r[i % DEGREE] = word;
+        self.index += 1;
+        word
+    }
+
+    /// Same as `random()`, 31 bits
+    pub fn next_random(&mut self) -> u32 {
+        self.step() >> 1
+    }
+
+    /// Clone from consecutive `random()` outputs (a few hundred), continuing after the last one.
+    /// Returns `None` if they don't determine the state.
+    pub fn from_outputs(outputs: &[u32]) -> Option<GlibcRandom> {
+        if outputs.len() < DEGREE {
+            return None;
+        }
+        // the dropped low bits b[k] of r[k] = 2 * o[k] + b[k], over the unknowns b[0..31]
+        let mut low_bits: Vec<BitVec> = (0..DEGREE).map(|i| BitVec::unit(DEGREE, i)).collect();
+        let mut solver = Solver::new(DEGREE);
+        for k in DEGREE..outputs.len() {
+            let mut bit = low_bits[k - DEGREE].clone();
+            bit.xor_assign(&low_bits[k - SEPARATION]);
+            // o[k] = o[k-31] + o[k-3] + (b[k-31] & b[k-3]) (mod 2^31)
+            let sum = outputs[k - DEGREE].wrapping_add(outputs[k - SEPARATION]);
+            match outputs[k].wrapping_sub(sum) & 0x7fff_ffff {
+                0 => {}
+                1 => {
+                    solver.add(low_bits[k - DEGREE].clone(), true);
+                    solver.add(low_bits[k - SEPARATION].clone(), true);
+                }
+                _ => return None,
+            }
+            low_bits.push(bit);
+        }
+        if !solver.determines(0..DEGREE) {
+            return None;
+        }
+        let x = solver.solve()?;
+
+        let n = outputs.len();
+        let mut r = vec![0; DEGREE];
+        for k in n - DEGREE..n {
+            r[k % DEGREE] = outputs[k] << 1 | low_bits[k].dot(&x) as u32;
+        }
+        let rng = GlibcRandom { r, index: n };
+        // the first outputs only constrain the later ones, check them by replaying backward
+        let mut check = rng.clone();
+        for k in (0..n - DEGREE).rev() {
+            let i = k + DEGREE;
+            let word = check.r[i % DEGREE].wrapping_sub(check.r[(i - SEPARATION) % DEGREE]);
+            if word >> 1 != outputs[k] {
+                return None;
+            }
+            check.r[i % DEGREE] = word;
+        }
+        Some(rng)
+    }
+}
+
+impl RngCore for GlibcRandom {
+    /// The 31-bit output of `random()`
+    fn next_u32(&mut self) -> u32 {
+        self.next_random()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        impls::next_u64_via_u32(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        impls::fill_bytes_via_next(self, dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glibc_reference_outputs() {
+        let mut rng = GlibcRandom::new(1);
+        let outputs: Vec<u32> = (0..3).map(|_| rng.next_random()).collect();
+        assert_eq!(outputs, vec![1804289383, 846930886, 1681692777]);
+        let mut rng = GlibcRandom::new(12345);
+        let outputs: Vec<u32> = (0..3).map(|_| rng.next_random()).collect();
+        assert_eq!(outputs, vec![383100999, 858300821, 357768173]);
+    }
+
+    #[test]
+    fn recover_glibc_state() {
+        let mut rng = GlibcRandom::new(0xc0ffee);
+        let outputs: Vec<u32> = (0..400).map(|_| rng.next_random()).collect();
+        let mut clone = GlibcRandom::from_outputs(&outputs).unwrap();
+        for _ in 0..1000 {
+            assert_eq!(rng.next_random(), clone.next_random());
+        }
+
+        assert!(GlibcRandom::from_outputs(&outputs[..20]).is_none());
+        let mut tampered = outputs.clone();
+        tampered[100] ^= 0x1234;
+        assert!(GlibcRandom::from_outputs(&tampered).is_none());
+    }
+}
@@ -0,0 +1,158 @@
+// `java.util.Random`: a 48-bit LCG whose outputs are the top bits of the state.
+use super::mt19937::{Error, RngCore};
+
+const MULTIPLIER: u64 = 0x5_DEEC_E66D;
+const ADDEND: u64 = 0xB;
+const MASK: u64 = (1 << 48) - 1;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JavaRandom {
+    seed: u64,
+}
+
+impl JavaRandom {
+    /// Same as `new Random(seed)`
+    pub fn new(seed: i64) -> JavaRandom {
+        JavaRandom {
+            seed: (seed as u64 ^ MULTIPLIER) & MASK,
+        }
+    }
+
+    /// Instantiate from the internal 48-bit state
+    pub fn from_state(state: u64) -> JavaRandom {
+        JavaRandom { seed: state & MASK }
+    }
+
+    pub fn state(&self) -> u64 {
+        self.seed
+    }
+
+    /// The `seed` of `new Random(seed)` (modulo 2^48), valid for a generator that hasn't been used
+    /// yet or has been rewound to that point
+    pub fn initial_seed(&self) -> i64 {
+        ((self.seed ^ MULTIPLIER) & MASK) as i64
+    }
+
+    /// Step the generator back by `steps` calls to `next`
+    pub fn rewind(&mut self, steps: u64) {
+        // the multiplier is odd, Newton's iteration doubles the number of correct bits each time
+        let inverse = (0..6).fold(MULTIPLIER, |inv, _| {
+            inv.wrapping_mul(2u64.wrapping_sub(MULTIPLIER.wrapping_mul(inv)))
+        });
+        for _ in 0..steps {
+            self.seed = (self.seed.wrapping_sub(ADDEND)).wrapping_mul(inverse) & MASK;
+        }
+    }
+
+    fn next(&mut self, bits: u32) -> i32 {
+        self.seed = (self.seed.wrapping_mul(MULTIPLIER).wrapping_add(ADDEND)) & MASK;
+        (self.seed >> (48 - bits)) as i32
+    }
+
+    pub fn next_int(&mut self) -> i32 {
+        self.next(32)
+    }
+
+    /// `nextInt(bound)`, uniform in [0, bound)
+    pub fn next_int_bounded(&mut self, bound: i32) -> i32 {
+        if bound <= 0 {
+            panic!("bound must be positive");
+        }
+        let mut r = self.next(31);
+        let m = bound - 1;
+        if bound & m == 0 {
+            return ((bound as i64 * r as i64) >> 31) as i32;
+        }
+        // reject the values of the last incomplete range, detected by the overflow
+        let mut u = r;
+        loop {
+            r = u % bound;
+            if u.wrapping_sub(r).wrapping_add(m) >= 0 {
+                return r;
+            }
+            u = self.next(31);
+        }
+    }
+
+    pub fn next_long(&mut self) -> i64 {
+        ((self.next(32) as i64) << 32).wrapping_add(self.next(32) as i64)
+    }
+
+    pub fn next_double(&mut self) -> f64 {
+        (((self.next(26) as i64) << 27) + self.next(27) as i64) as f64 / (1u64 << 53) as f64
+    }
+
+    /// `nextBytes`: each `nextInt()` gives up to 4 bytes, least significant first
+    pub fn next_bytes(&mut self, bytes: &mut [u8]) {
+        for chunk in bytes.chunks_mut(4) {
+            let rnd = self.next_int();
+            for (i, byte) in chunk.iter_mut().enumerate() {
+                *byte = (rnd >> (8 * i)) as u8;
+            }
+        }
+    }
+
+    /// Clone from two consecutive `nextInt()` outputs: the first one is the top 32 bits of the
+    /// state, the 16 lower ones are brute-forced. Continues after the second output.
+    pub fn from_outputs(first: i32, second: i32) -> Option<JavaRandom> {
+        (0..1 << 16)
+            .map(|low| JavaRandom::from_state(((first as u32 as u64) << 16) | low))
+            .find_map(|mut rng| if rng.next_int() == second { Some(rng) } else { None })
+    }
+}
+
+impl RngCore for JavaRandom {
+    fn next_u32(&mut self) -> u32 {
+        self.next_int() as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.next_long() as u64
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.next_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn java_reference_outputs() {
+        let mut rng = JavaRandom::new(42);
+        assert_eq!(rng.next_int(), -1170105035);
+        assert_eq!(rng.next_int(), 234785527);
+        assert_eq!(rng.next_long(), -5843495416241995736);
+        assert_eq!(rng.next_double(), 0.30871945533265976);
+        assert_eq!(rng.next_int_bounded(100), 5);
+        assert_eq!(rng.next_int_bounded(1 << 20), 742088);
+
+        let mut bytes = [0; 10];
+        JavaRandom::new(7).next_bytes(&mut bytes);
+        assert_eq!(
+            bytes,
+            [0x99, 0x17, 0x0f, 0xbb, 0x18, 0x34, 0x77, 0xa3, 0x5a, 0x94]
+        );
+    }
+
+    #[test]
+    fn recover_from_two_outputs() {
+        let mut rng = JavaRandom::new(1337);
+        let (first, second) = (rng.next_int(), rng.next_int());
+        assert_eq!((first, second), (-1460590454, 747279288));
+
+        let mut clone = JavaRandom::from_outputs(first, second).unwrap();
+        assert_eq!(clone.next_int(), -1334692577);
+        rng.next_int();
+        assert_eq!(clone.next_long(), rng.next_long());
+        clone.rewind(5);
+        assert_eq!(clone.initial_seed(), 1337);
+    }
+}
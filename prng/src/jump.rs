@@ -0,0 +1,329 @@
+// Jumping ahead in the MT19937 sequence.
+//
+// One output step is a linear map T over the 19937-bit state with characteristic polynomial phi,
+// found once by Berlekamp-Massey on an output bit sequence. T^n is then r(T) with
+// r = x^n mod phi, evaluated on the state with Horner's rule: about 19937 steps whatever n is.
+use super::mt19937::{MT19937Rng, RngCore, COEFF_32};
+use std::sync::OnceLock;
+
+/// Polynomial over GF(2), coefficient of x^i in bit (i % 64) of word (i / 64)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Poly {
+    words: Vec<u64>,
+}
+
+impl Poly {
+    fn zero() -> Poly {
+        Poly { words: vec![] }
+    }
+
+    fn one() -> Poly {
+        Poly { words: vec![1] }
+    }
+
+    pub fn coeff(&self, i: usize) -> bool {
+        self.words.get(i / 64).is_some_and(|w| w >> (i % 64) & 1 == 1)
+    }
+
+    fn set(&mut self, i: usize) {
+        if self.words.len() <= i / 64 {
+            self.words.resize(i / 64 + 1, 0);
+        }
+        self.words[i / 64] |= 1 << (i % 64);
+    }
+
+    // coefficients of x^i..x^(i+64)
+    fn word_at(&self, i: usize) -> u64 {
+        let (word, bit) = (i / 64, i % 64);
+        let low = self.words.get(word).map_or(0, |w| w >> bit);
+        match self.words.get(word + 1) {
+            Some(high) if bit != 0 => low | high << (64 - bit),
+            _ => low,
+        }
+    }
+
+    /// Degree, `None` for the zero polynomial
+    pub fn degree(&self) -> Option<usize> {
+        self.words
+            .iter()
+            .rposition(|w| *w != 0)
+            .map(|i| i * 64 + 63 - self.words[i].leading_zeros() as usize)
+    }
+
+    // self += other * x^shift
+    fn add_shifted(&mut self, other: &Poly, shift: usize) {
+        let (word, bit) = (shift / 64, shift % 64);
+        let len = other.words.len() + word + 1;
+        if self.words.len() < len {
+            self.words.resize(len, 0);
+        }
+        for (i, w) in other.words.iter().enumerate() {
+            self.words[i + word] ^= w << bit;
+            if bit != 0 {
+                self.words[i + word + 1] ^= w >> (64 - bit);
+            }
+        }
+    }
+
+    // over GF(2), (sum a_i x^i)^2 = sum a_i x^(2i)
+    fn square(&self) -> Poly {
+        let spread = |mut x: u64| {
+            x &= 0xffff_ffff;
+            x = (x | x << 16) & 0x0000_ffff_0000_ffff;
+            x = (x | x << 8) & 0x00ff_00ff_00ff_00ff;
+            x = (x | x << 4) & 0x0f0f_0f0f_0f0f_0f0f;
+            x = (x | x << 2) & 0x3333_3333_3333_3333;
+            (x | x << 1) & 0x5555_5555_5555_5555
+        };
+        Poly {
+            words: self
+                .words
+                .iter()
+                .flat_map(|w| vec![spread(*w), spread(w >> 32)])
+                .collect(),
+        }
+    }
+
+    fn shift_left(&self, shift: usize) -> Poly {
+        let mut p = Poly::zero();
+        p.add_shifted(self, shift);
+        p
+    }
+}
+
+// Reduction modulo a monic polynomial, 8 coefficients at a time
+struct Modulus {
+    poly: Poly,
+    degree: usize,
+    // table[b] = q * poly with deg q < 8, whose coefficients of x^degree..x^(degree+8) are b
+    table: Vec<Poly>,
+}
+
+impl Modulus {
+    fn new(poly: Poly) -> Modulus {
+        let degree = poly.degree().expect("zero modulus");
+        let mut table = vec![Poly::zero(); 256];
+        for q in 1..256usize {
+            let mut multiple = Poly::zero();
+            for i in (0..8).filter(|i| q >> i & 1 == 1) {
+                multiple.add_shifted(&poly, i);
+            }
+            let top = (0..8).fold(0, |acc, i| acc | (multiple.coeff(degree + i) as usize) << i);
+            table[top] = multiple;
+        }
+        Modulus { poly, degree, table }
+    }
+
+    fn reduce(&self, p: &mut Poly) {
+        while let Some(d) = p.degree() {
+            if d < self.degree {
+                break;
+            }
+            if d < self.degree + 7 {
+                p.add_shifted(&self.poly, d - self.degree);
+                continue;
+            }
+            // cancel x^(d-7)..x^d at once
+            let low = d - 7;
+            let top = (0..8).fold(0, |acc, i| acc | (p.coeff(low + i) as usize) << i);
+            p.add_shifted(&self.table[top], low - self.degree);
+        }
+        p.words.truncate(self.degree / 64 + 1);
+    }
+
+    /// x^n mod poly
+    fn power_of_x(&self, n: u128) -> Poly {
+        let mut result = Poly::one();
+        for i in (0..128 - n.leading_zeros()).rev() {
+            result = result.square();
+            if n >> i & 1 == 1 {
+                result = result.shift_left(1);
+            }
+            self.reduce(&mut result);
+        }
+        result
+    }
+}
+
+// Shortest linear recurrence of `bits`, returned as its characteristic polynomial
+fn berlekamp_massey(bits: &[bool]) -> Poly {
+    // connection polynomial C with s[n] = sum_{i=1..L} c_i s[n-i]
+    let (mut c, mut b) = (Poly::one(), Poly::one());
+    let (mut l, mut m) = (0, 1);
+    // s[n-i] is coefficient N-1-n+i of the reversed sequence, so that the discrepancy is the
+    // parity of C & (reversed >> (N-1-n))
+    let mut reversed = Poly::zero();
+    for (j, _) in bits.iter().rev().enumerate().filter(|(_, bit)| **bit) {
+        reversed.set(j);
+    }
+    for n in 0..bits.len() {
+        let offset = bits.len() - 1 - n;
+        let discrepancy = c.words.iter().enumerate().fold(0, |acc, (k, w)| {
+            acc ^ (w & reversed.word_at(offset + 64 * k)).count_ones()
+        }) & 1;
+        if discrepancy == 0 {
+            m += 1;
+        } else if 2 * l <= n {
+            let previous = c.clone();
+            c.add_shifted(&b, m);
+            l = n + 1 - l;
+            b = previous;
+            m = 1;
+        } else {
+            c.add_shifted(&b, m);
+            m += 1;
+        }
+    }
+    // characteristic polynomial x^L C(1/x)
+    let mut poly = Poly::zero();
+    for i in (0..=l).filter(|i| c.coeff(*i)) {
+        poly.set(l - i);
+    }
+    poly
+}
+
+// characteristic polynomial of one MT19937 output step, degree 19937
+fn modulus() -> &'static Modulus {
+    static MODULUS: OnceLock<Modulus> = OnceLock::new();
+    MODULUS.get_or_init(|| {
+        let mut rng = MT19937Rng::new(5489);
+        let bits: Vec<bool> = (0..2 * 19937).map(|_| rng.next_u32() & 1 == 1).collect();
+        Modulus::new(berlekamp_massey(&bits))
+    })
+}
+
+/// x^n mod phi, to jump any number of MT19937 generators by n outputs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JumpPolynomial {
+    n: u128,
+    poly: Poly,
+}
+
+impl JumpPolynomial {
+    pub fn new(n: u128) -> JumpPolynomial {
+        // the first step is taken explicitly, see `Window::jump`
+        JumpPolynomial {
+            n,
+            poly: modulus().power_of_x(n.saturating_sub(1)),
+        }
+    }
+
+    pub fn steps(&self) -> u128 {
+        self.n
+    }
+}
+
+/// The 624 words about to be tempered, i.e. the next output is temper(words[start])
+#[derive(Debug, Clone)]
+pub(crate) struct Window {
+    words: Vec<u32>,
+    start: usize,
+}
+
+impl Window {
+    pub(crate) fn new(words: Vec<u32>) -> Window {
+        Window { words, start: 0 }
+    }
+
+    pub(crate) fn into_words(mut self) -> Vec<u32> {
+        self.words.rotate_left(self.start);
+        self.words
+    }
+
+    // drop the first word and append the next one of the sequence
+    fn step(&mut self) {
+        let n = COEFF_32.n as usize;
+        let (first, second, middle) = (
+            self.words[self.start],
+            self.words[(self.start + 1) % n],
+            self.words[(self.start + COEFF_32.m as usize) % n],
+        );
+        let lower_mask: u32 = (1 << COEFF_32.r) - 1;
+        let x = (first & !lower_mask) | (second & lower_mask);
+        let mut x_a = x >> 1;
+        if x & 1 == 1 {
+            x_a ^= COEFF_32.a;
+        }
+        self.words[self.start] = middle ^ x_a;
+        self.start = (self.start + 1) % n;
+    }
+
+    fn add(&mut self, other: &Window) {
+        let n = COEFF_32.n as usize;
+        for j in 0..n {
+            self.words[(self.start + j) % n] ^= other.words[(other.start + j) % n];
+        }
+    }
+
+    /// Advance by `jump.steps()` outputs
+    pub(crate) fn jump(&mut self, jump: &JumpPolynomial) {
+        if jump.n == 0 {
+            return;
+        }
+        // the lower bits of the first word don't survive a step, after which phi(T) = 0
+        self.step();
+        let state = self.clone();
+        let mut acc = Window::new(vec![0; COEFF_32.n as usize]);
+        if let Some(degree) = jump.poly.degree() {
+            for i in (0..=degree).rev() {
+                acc.step();
+                if jump.poly.coeff(i) {
+                    acc.add(&state);
+                }
+            }
+        }
+        *self = acc;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn characteristic_polynomial() {
+        let modulus = modulus();
+        assert_eq!(modulus.degree, 19937);
+        assert!(modulus.poly.coeff(0));
+    }
+
+    #[test]
+    fn jump_matches_stepping() {
+        for n in [1u128, 700, 30000].iter() {
+            let mut origin = MT19937Rng::new(42);
+            origin.next_u32();
+            let mut jumped = MT19937Rng::new(42);
+            jumped.next_u32();
+            jumped.jump_with(&JumpPolynomial::new(*n));
+            for _ in 0..*n {
+                origin.next_u32();
+            }
+            for _ in 0..1000 {
+                assert_eq!(origin.next_u32(), jumped.next_u32());
+            }
+        }
+    }
+
+    #[test]
+    fn jumps_compose() {
+        let mut twice = MT19937Rng::new(1);
+        let jump = JumpPolynomial::new(1 << 70);
+        twice.jump_with(&jump);
+        twice.jump_with(&jump);
+        let mut once = MT19937Rng::new(1);
+        once.jump(1 << 71);
+        for _ in 0..100 {
+            assert_eq!(once.next_u32(), twice.next_u32());
+        }
+    }
+
+    #[test]
+    fn substreams_are_spaced() {
+        let mut streams = MT19937Rng::substreams(7, 3);
+        let mut rng = MT19937Rng::new(7);
+        assert_eq!(streams[0].next_u32(), rng.next_u32());
+        rng.jump((2 << 64) - 1);
+        assert_eq!(streams[2].next_u32(), rng.next_u32());
+        assert_ne!(streams[1].next_u32(), streams[2].next_u32());
+    }
+}
@@ -0,0 +1,130 @@
+// Small-dimension lattice reduction with exact rational arithmetic, for the truncated-LCG attacks.
+use num::{BigInt, BigRational, Signed, Zero};
+
+fn dot(u: &[BigRational], v: &[BigRational]) -> BigRational {
+    u.iter()
+        .zip(v.iter())
+        .fold(BigRational::zero(), |acc, (a, b)| acc + a * b)
+}
+
+fn to_rational(v: &[BigInt]) -> Vec<BigRational> {
+    v.iter().map(|x| BigRational::from_integer(x.clone())).collect()
+}
+
+// Gram-Schmidt orthogonalization: the orthogonal vectors b* and the coefficients mu[i][j]
+fn gram_schmidt(basis: &[Vec<BigInt>]) -> (Vec<Vec<BigRational>>, Vec<Vec<BigRational>>) {
+    let n = basis.len();
+    let mut ortho: Vec<Vec<BigRational>> = Vec::with_capacity(n);
+    let mut mu = vec![vec![BigRational::zero(); n]; n];
+    for i in 0..n {
+        let mut v = to_rational(&basis[i]);
+        for j in 0..i {
+            mu[i][j] = dot(&to_rational(&basis[i]), &ortho[j]) / dot(&ortho[j], &ortho[j]);
+            for (x, y) in v.iter_mut().zip(ortho[j].iter()) {
+                *x -= &mu[i][j] * y;
+            }
+        }
+        ortho.push(v);
+    }
+    (ortho, mu)
+}
+
+fn sub_multiple(v: &mut [BigInt], u: &[BigInt], k: &BigInt) {
+    for (x, y) in v.iter_mut().zip(u.iter()) {
+        *x -= k * y;
+    }
+}
+
+/// LLL-reduce the rows of `basis` (linearly independent) with delta = 3/4, the Gram-Schmidt data
+/// being updated in place rather than recomputed
+pub fn lll(basis: &[Vec<BigInt>]) -> Vec<Vec<BigInt>> {
+    let mut b = basis.to_vec();
+    let n = b.len();
+    let delta = BigRational::new(3.into(), 4.into());
+    let half = BigRational::new(1.into(), 2.into());
+    let (ortho, mut mu) = gram_schmidt(&b);
+    // squared norms of the orthogonal vectors
+    let mut norms: Vec<BigRational> = ortho.iter().map(|v| dot(v, v)).collect();
+
+    // size-reduce b[k] with b[l]
+    let reduce = |b: &mut Vec<Vec<BigInt>>, mu: &mut Vec<Vec<BigRational>>, k: usize, l: usize| {
+        if mu[k][l].abs() > half {
+            let q = mu[k][l].round();
+            let bl = b[l].clone();
+            sub_multiple(&mut b[k], &bl, q.numer());
+            mu[k][l] -= &q;
+            let mu_l = mu[l].clone();
+            for (x, y) in mu[k].iter_mut().zip(mu_l.iter()).take(l) {
+                *x -= &q * y;
+            }
+        }
+    };
+
+    let mut k = 1;
+    while k < n {
+        reduce(&mut b, &mut mu, k, k - 1);
+        // Lovász condition
+        let bound = (&delta - &mu[k][k - 1] * &mu[k][k - 1]) * &norms[k - 1];
+        if norms[k] < bound {
+            b.swap(k, k - 1);
+            let (upper, lower) = mu.split_at_mut(k);
+            for (x, y) in upper[k - 1].iter_mut().zip(lower[0].iter_mut()).take(k - 1) {
+                std::mem::swap(x, y);
+            }
+            let m = mu[k][k - 1].clone();
+            let norm = &norms[k] + &m * &m * &norms[k - 1];
+            mu[k][k - 1] = &m * &norms[k - 1] / &norm;
+            norms[k] = &norms[k - 1] * &norms[k] / &norm;
+            norms[k - 1] = norm;
+            for i in k + 1..n {
+                let t = mu[i][k].clone();
+                mu[i][k] = &mu[i][k - 1] - &m * &t;
+                mu[i][k - 1] = t + &mu[k][k - 1] * &mu[i][k];
+            }
+            k = (k - 1).max(1);
+        } else {
+            for l in (0..k - 1).rev() {
+                reduce(&mut b, &mut mu, k, l);
+            }
+            k += 1;
+        }
+    }
+    b
+}
+
+/// Babai's nearest plane: a lattice vector close to `target`, the basis being LLL-reduced
+pub fn closest_vector(basis: &[Vec<BigInt>], target: &[BigInt]) -> Vec<BigInt> {
+    let (ortho, _) = gram_schmidt(basis);
+    let mut diff = target.to_vec();
+    for j in (0..basis.len()).rev() {
+        let c = (dot(&to_rational(&diff), &ortho[j]) / dot(&ortho[j], &ortho[j]))
+            .round()
+            .to_integer();
+        sub_multiple(&mut diff, &basis[j], &c);
+    }
+    target.iter().zip(diff.iter()).map(|(t, d)| t - d).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vectors(rows: &[&[i64]]) -> Vec<Vec<BigInt>> {
+        rows.iter()
+            .map(|r| r.iter().map(|x| BigInt::from(*x)).collect())
+            .collect()
+    }
+
+    #[test]
+    fn lll_reduction() {
+        // classic example, the reduced basis is made of short vectors
+        let basis = vectors(&[&[1, 1, 1], &[-1, 0, 2], &[3, 5, 6]]);
+        let reduced = lll(&basis);
+        assert_eq!(reduced, vectors(&[&[0, 1, 0], &[1, 0, 1], &[-1, 0, 2]]));
+
+        let target: Vec<BigInt> = [10, 3, 11].iter().map(|x| BigInt::from(*x)).collect();
+        let closest = closest_vector(&reduced, &target);
+        // both at distance 1
+        assert!(vectors(&[&[10, 3, 10], &[11, 3, 11]]).contains(&closest));
+    }
+}
@@ -0,0 +1,268 @@
+// Linear congruential generators: s' = (a * s + c) mod m, outputting some bits of the new state.
+//
+// With all the parameters known, outputs truncated to their top bits still leak the state through a
+// closest vector problem: s[i] = a^i * s[0] + c[i] (mod m) puts (s[0], ..., s[n-1]) close to a
+// lattice point whenever the known top bits are enough to determine it.
+use super::lattice;
+use super::mt19937::{impls, Error, RngCore};
+use num::{BigInt, Integer, One, Signed, ToPrimitive, Zero};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lcg {
+    pub a: u128,
+    pub c: u128,
+    pub m: u128,
+    /// The output is bits [shift, shift + bits) of the new state
+    pub shift: u32,
+    pub bits: u32,
+    state: u128,
+}
+
+impl Lcg {
+    /// Instantiate with the full state as output
+    pub fn new(a: u128, c: u128, m: u128, seed: u128) -> Lcg {
+        if !(2..=1 << 64).contains(&m) {
+            panic!("Invalid modulus, should be within [2, 2^64]");
+        }
+        Lcg {
+            a: a % m,
+            c: c % m,
+            m,
+            shift: 0,
+            bits: 128 - (m - 1).leading_zeros(),
+            state: seed % m,
+        }
+    }
+
+    /// Only output bits [shift, shift + bits) of the state, e.g. the top half
+    pub fn with_output(mut self, shift: u32, bits: u32) -> Lcg {
+        if bits == 0 || bits > 64 {
+            panic!("Invalid output width, should be within [1, 64]");
+        }
+        if shift.saturating_add(bits) > 128 - (self.m - 1).leading_zeros() {
+            panic!("Invalid output bits, should be within the bit length of the modulus");
+        }
+        self.shift = shift;
+        self.bits = bits;
+        self
+    }
+
+    /// C++ `std::minstd_rand`
+    pub fn minstd_rand(seed: u32) -> Lcg {
+        Lcg::new(48271, 0, (1 << 31) - 1, seed as u128)
+    }
+
+    /// C++ `std::minstd_rand0`, the Park-Miller "minimal standard"
+    pub fn minstd_rand0(seed: u32) -> Lcg {
+        Lcg::new(16807, 0, (1 << 31) - 1, seed as u128)
+    }
+
+    /// The ANSI C sample `rand()`, also glibc's `rand()` with a TYPE_0 state
+    pub fn ansi_c(seed: u32) -> Lcg {
+        Lcg::new(1103515245, 12345, 1 << 31, seed as u128)
+    }
+
+    /// MSVC `rand()`: bits 16..31 of the state
+    pub fn msvc(seed: u32) -> Lcg {
+        Lcg::new(214013, 2531011, 1 << 32, seed as u128).with_output(16, 15)
+    }
+
+    pub fn state(&self) -> u128 {
+        self.state
+    }
+
+    /// Same generator with another state
+    pub fn from_state(&self, state: u128) -> Lcg {
+        Lcg {
+            state: state % self.m,
+            ..self.clone()
+        }
+    }
+
+    pub fn next_output(&mut self) -> u64 {
+        self.state = (self.a * self.state + self.c) % self.m;
+        self.output(self.state)
+    }
+
+    fn output(&self, state: u128) -> u64 {
+        ((state >> self.shift) & ((1 << self.bits) - 1)) as u64
+    }
+
+    /// Clone an LCG with unknown parameters from consecutive full outputs (at least 6 for a
+    /// reliable modulus), continuing after the last one
+    pub fn crack_unknown(outputs: &[u64]) -> Option<Lcg> {
+        let s: Vec<BigInt> = outputs.iter().map(|x| BigInt::from(*x)).collect();
+        if s.len() < 4 {
+            return None;
+        }
+        // t[i+2] * t[i] - t[i+1]^2 = 0 (mod m) with t[i] = s[i+1] - s[i]
+        let t: Vec<BigInt> = s.windows(2).map(|w| &w[1] - &w[0]).collect();
+        let m = t
+            .windows(3)
+            .map(|w| (&w[2] * &w[0] - &w[1] * &w[1]).abs())
+            .fold(BigInt::zero(), |acc, u| acc.gcd(&u));
+        if m <= BigInt::one() || m > BigInt::one() << 64 {
+            return None;
+        }
+        // a = t[i+1] / t[i] (mod m) for the first invertible t[i], c = s[1] - a * s[0]
+        let a = (0..t.len() - 1).find_map(|i| {
+            mod_inverse(&t[i].mod_floor(&m), &m).map(|inverse| (&t[i + 1] * inverse).mod_floor(&m))
+        })?;
+        let c = (&s[1] - &a * &s[0]).mod_floor(&m);
+
+        let lcg = Lcg::new(a.to_u128()?, c.to_u128()?, m.to_u128()?, outputs[0] as u128);
+        lcg.verify(outputs)
+    }
+
+    /// The truncated-LCG attack: clone this generator (parameters known) from consecutive
+    /// truncated outputs, continuing after the last one. The number of outputs needed grows as
+    /// the outputs get shorter, e.g. 4 for the top half of a 64-bit state.
+    pub fn recover_truncated(&self, outputs: &[u64]) -> Option<Lcg> {
+        if outputs.is_empty() {
+            return None;
+        }
+        // the lower bits of a power-of-two modulus LCG don't depend on the upper ones, so the
+        // bits above the output can be dropped
+        let modulus = if self.m.is_power_of_two() {
+            self.m.min(1 << (self.shift + self.bits))
+        } else {
+            self.m
+        };
+        let lcg = Lcg {
+            m: modulus,
+            a: self.a % modulus,
+            c: self.c % modulus,
+            ..self.clone()
+        };
+        if self.shift == 0 {
+            return lcg.from_state(outputs[0] as u128).verify(outputs);
+        }
+
+        // s[i] = a^i * s[0] + c[i] (mod m)
+        let m = BigInt::from(modulus);
+        let (mut a_i, mut c_i) = (BigInt::one(), BigInt::zero());
+        let mut powers = vec![];
+        let mut target = vec![];
+        for y in outputs.iter() {
+            powers.push(a_i.clone());
+            // s[i] - c[i] = (y[i] << shift) - c[i] + z with 0 <= z < 2^shift, aim at the middle
+            target.push(
+                (BigInt::from(*y) << self.shift as usize) + (BigInt::one() << (self.shift - 1) as usize)
+                    - &c_i,
+            );
+            a_i = (&a_i * lcg.a).mod_floor(&m);
+            c_i = (&c_i * lcg.a + lcg.c).mod_floor(&m);
+        }
+        // rows (1, a, a^2, ...) and m * e_i, spanning all (s[0], a * s[0], ...) modulo m
+        let n = outputs.len();
+        let mut basis = vec![powers];
+        for i in 1..n {
+            let mut row = vec![BigInt::zero(); n];
+            row[i] = m.clone();
+            basis.push(row);
+        }
+        let closest = lattice::closest_vector(&lattice::lll(&basis), &target);
+        let s0 = closest[0].mod_floor(&m).to_u128()?;
+        lcg.from_state(s0).verify(outputs)
+    }
+
+    // Check that the current state produced `outputs[0]` and the next ones follow, returns the
+    // generator after the last one
+    fn verify(mut self, outputs: &[u64]) -> Option<Lcg> {
+        if self.output(self.state) != outputs[0] {
+            return None;
+        }
+        for y in outputs[1..].iter() {
+            if self.next_output() != *y {
+                return None;
+            }
+        }
+        Some(self)
+    }
+}
+
+fn mod_inverse(x: &BigInt, m: &BigInt) -> Option<BigInt> {
+    let gcd = x.extended_gcd(m);
+    if gcd.gcd != BigInt::one() {
+        return None;
+    }
+    Some(gcd.x.mod_floor(m))
+}
+
+impl RngCore for Lcg {
+    fn next_u32(&mut self) -> u32 {
+        self.next_output() as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        impls::next_u64_via_u32(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        impls::fill_bytes_via_next(self, dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reference_outputs() {
+        assert_eq!(Lcg::minstd_rand(1).next_u32(), 48271);
+        assert_eq!(Lcg::minstd_rand0(1).next_u32(), 16807);
+        assert_eq!(Lcg::ansi_c(1).next_u32(), 1103527590);
+        let mut msvc = Lcg::msvc(1);
+        let outputs: Vec<u32> = (0..3).map(|_| msvc.next_u32()).collect();
+        assert_eq!(outputs, vec![41, 18467, 6334]);
+    }
+
+    #[test]
+    fn crack_unknown_parameters() {
+        let mut lcg = Lcg::new(6364136223846793005, 1442695040888963407, 1 << 64, 1234);
+        let outputs: Vec<u64> = (0..8).map(|_| lcg.next_output()).collect();
+        let mut clone = Lcg::crack_unknown(&outputs).unwrap();
+        assert_eq!((clone.a, clone.c, clone.m), (lcg.a, lcg.c, lcg.m));
+        for _ in 0..10 {
+            assert_eq!(lcg.next_output(), clone.next_output());
+        }
+    }
+
+    #[test]
+    fn truncated_lcg_attack() {
+        // top half of a 64-bit state
+        let mut lcg =
+            Lcg::new(6364136223846793005, 1442695040888963407, 1 << 64, 0xdead_beef).with_output(32, 32);
+        let outputs: Vec<u64> = (0..6).map(|_| lcg.next_output()).collect();
+        let mut clone = lcg.recover_truncated(&outputs).unwrap();
+        for _ in 0..10 {
+            assert_eq!(lcg.next_u32(), clone.next_u32());
+        }
+
+        // MSVC rand() leaks 15 bits out of 31 relevant ones
+        let mut msvc = Lcg::msvc(31337);
+        let outputs: Vec<u64> = (0..8).map(|_| msvc.next_output()).collect();
+        let mut clone = msvc.recover_truncated(&outputs).unwrap();
+        for _ in 0..10 {
+            assert_eq!(msvc.next_u32(), clone.next_u32());
+        }
+
+        // non power of two modulus
+        let mut lcg = Lcg::minstd_rand(42).with_output(16, 15);
+        let outputs: Vec<u64> = (0..8).map(|_| lcg.next_output()).collect();
+        let mut clone = lcg.recover_truncated(&outputs).unwrap();
+        assert_eq!(lcg.next_u32(), clone.next_u32());
+    }
+
+    #[test]
+    #[should_panic(expected = "bit length of the modulus")]
+    fn output_beyond_modulus() {
+        // minstd_rand has a 31-bit state
+        Lcg::minstd_rand(42).with_output(16, 16);
+    }
+}
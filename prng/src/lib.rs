@@ -1,3 +1,19 @@
 #![deny(clippy::all)]
+pub mod analysis;
+pub mod clone;
+pub mod cpp;
+pub mod gf2;
+pub mod glibc;
+pub mod java;
+pub mod jump;
+pub mod lattice;
+pub mod lcg;
 pub mod mt19937;
 pub mod mt19937_64;
+pub mod numpy;
+pub mod pcg;
+pub mod python;
+pub mod seed_search;
+pub mod stream;
+pub mod token;
+pub mod xorshift;
@@ -1,5 +1,6 @@
 #![allow(non_snake_case)]
 #![allow(clippy::unreadable_literal)]
+use super::jump::{JumpPolynomial, Window};
 pub use rand_core::{impls, Error, RngCore};
 use std::cmp::Ordering;
 use std::fmt;
@@ -50,6 +51,41 @@ impl MT19937Rng {
         rng
     }
 
+    /// Instantiate a new RNG seeded with `init_by_array`, as Python and NumPy do for big seeds
+    pub fn new_by_array(key: &[u32]) -> MT19937Rng {
+        if key.is_empty() {
+            panic!("Invalid key, should not be empty");
+        }
+        let n = COEFF_32.n as usize;
+        let mut rng = MT19937Rng::new(19650218);
+        let mt = &mut rng.mt;
+        let (mut i, mut j) = (1, 0);
+        for _ in 0..n.max(key.len()) {
+            let prev = mt[i - 1] ^ (mt[i - 1] >> 30);
+            mt[i] = (mt[i] ^ prev.wrapping_mul(1664525))
+                .wrapping_add(key[j])
+                .wrapping_add(j as u32);
+            i += 1;
+            j = (j + 1) % key.len();
+            if i >= n {
+                mt[0] = mt[n - 1];
+                i = 1;
+            }
+        }
+        for _ in 0..n - 1 {
+            let prev = mt[i - 1] ^ (mt[i - 1] >> 30);
+            mt[i] = (mt[i] ^ prev.wrapping_mul(1566083941)).wrapping_sub(i as u32);
+            i += 1;
+            if i >= n {
+                mt[0] = mt[n - 1];
+                i = 1;
+            }
+        }
+        // MSB is 1, assuring non-zero initial state
+        mt[0] = 0x80000000;
+        rng
+    }
+
     pub fn from(mt: &[u32], index: usize) -> MT19937Rng {
         MT19937Rng {
             mt: mt.to_owned(),
@@ -103,6 +139,45 @@ impl MT19937Rng {
         }
         self.index = 0;
     }
+
+    /// Advance the generator by `n` outputs, as if `next_u32()` was called `n` times
+    pub fn jump(&mut self, n: u128) {
+        if n <= COEFF_32.n as u128 {
+            for _ in 0..n {
+                self.extract();
+            }
+            return;
+        }
+        self.jump_with(&JumpPolynomial::new(n));
+    }
+
+    /// Same as `jump(poly.steps())`, reusing a precomputed jump polynomial
+    pub fn jump_with(&mut self, poly: &JumpPolynomial) {
+        // the 624 words to be tempered next: the rest of mt, then the start of the next twist
+        let mut next = MT19937Rng::from(&self.mt, self.index);
+        next.twist();
+        let mut words = self.mt[self.index..].to_vec();
+        words.extend_from_slice(&next.mt[..self.index]);
+
+        let mut window = Window::new(words);
+        window.jump(poly);
+        self.mt = window.into_words();
+        self.index = 0;
+    }
+
+    /// `count` non-overlapping generators derived from `seed`, each 2^64 outputs after the previous
+    pub fn substreams(seed: u32, count: usize) -> Vec<MT19937Rng> {
+        let poly = JumpPolynomial::new(1 << 64);
+        let mut rng = MT19937Rng::new(seed);
+        let mut streams = vec![];
+        for i in 0..count {
+            if i > 0 {
+                rng.jump_with(&poly);
+            }
+            streams.push(MT19937Rng::from(&rng.mt, rng.index));
+        }
+        streams
+    }
 }
 
 impl RngCore for MT19937Rng {
@@ -147,4 +222,15 @@ mod tests {
         assert_eq!(result[..10], expected_first_ten);
         assert_eq!(result[990..], expected_last_ten);
     }
+
+    #[test]
+    fn init_by_array_correctness() {
+        // mt19937ar.c test driver: init_by_array({0x123, 0x234, 0x345, 0x456})
+        let mut rng = MT19937Rng::new_by_array(&[0x123, 0x234, 0x345, 0x456]);
+        let result: Vec<u32> = (0..5).map(|_| rng.next_u32()).collect();
+        assert_eq!(
+            result,
+            vec![1067595299, 955945823, 477289528, 4107218783, 4228976476]
+        );
+    }
 }
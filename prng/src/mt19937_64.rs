@@ -6,13 +6,13 @@ use std::fmt;
 use std::mem;
 
 #[rustfmt::skip]
-struct Coefficients {
-    w: u8, n: usize, m: usize, r: u8,
-    a: usize,
-    b: usize, c: usize,
-    s: usize, t: usize,
-    u: usize, d: usize, l: usize,
-    f: usize,
+pub struct Coefficients {
+    pub w: u8, pub n: usize, pub m: usize, pub r: u8,
+    pub a: usize,
+    pub b: usize, pub c: usize,
+    pub s: usize, pub t: usize,
+    pub u: usize, pub d: usize, pub l: usize,
+    pub f: usize,
 }
 
 impl fmt::Debug for Coefficients {
@@ -24,7 +24,7 @@ impl fmt::Debug for Coefficients {
 // NOTE: `if` and `match` in const is still a nightly feature
 // see: https://blog.rust-lang.org/inside-rust/2019/11/25/const-if-match.html
 #[rustfmt::skip]
-const COEFF_32: Coefficients = Coefficients {
+pub const COEFF_32: Coefficients = Coefficients {
     w: 32, n: 624, m: 397, r: 31,
     a: 0x9908B0DF,
     u: 11, d: 0xFFFFFFFF,
@@ -35,7 +35,7 @@ const COEFF_32: Coefficients = Coefficients {
 };
 
 #[rustfmt::skip]
-const COEFF_64: Coefficients = Coefficients {
+pub const COEFF_64: Coefficients = Coefficients {
     w: 64, n: 312, m: 156, r: 31,
     a: 0xB5026F5AA96619E9,
     u: 29, d: 0x5555555555555555,
@@ -69,6 +69,23 @@ impl MT19937Rng {
         rng
     }
 
+    /// Instantiate from a raw state, `index` being the position of the next word to temper
+    pub fn from(mt: &[usize], index: usize) -> MT19937Rng {
+        let coeff = match mem::size_of::<usize>() {
+            4 => COEFF_32,
+            8 => COEFF_64,
+            _ => panic!("Only 32-bit and 64-bit word size supported!"),
+        };
+        if mt.len() != coeff.n || index > coeff.n {
+            panic!("Invalid state, expecting {} words", coeff.n);
+        }
+        MT19937Rng {
+            mt: mt.to_owned(),
+            index,
+            coeff,
+        }
+    }
+
     // Initialize the generator from a seed
     fn init(&mut self, seed: usize) {
         self.mt[0] = seed;
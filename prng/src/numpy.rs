@@ -0,0 +1,118 @@
+// NumPy's legacy `RandomState` (`np.random.seed`, `np.random.rand`...) on top of MT19937.
+//
+// An int seed goes through `init_genrand` (`MT19937Rng::new`), an array seed through
+// `init_by_array`. Bounded integers are drawn by masked rejection, 64-bit ones from two outputs
+// with the first one on top, which is exactly `MT19937Rng::next_u64`.
+use super::mt19937::{MT19937Rng, RngCore};
+
+#[derive(Debug)]
+pub struct RandomState {
+    mt: MT19937Rng,
+    // second value of the last polar Box-Muller draw
+    gauss: Option<f64>,
+}
+
+impl RandomState {
+    /// Same as `np.random.RandomState(seed)` for an int seed
+    pub fn new(seed: u32) -> RandomState {
+        RandomState::from_rng(MT19937Rng::new(seed))
+    }
+
+    /// Same as `np.random.RandomState(key)` for an array seed
+    pub fn from_key(key: &[u32]) -> RandomState {
+        RandomState::from_rng(MT19937Rng::new_by_array(key))
+    }
+
+    /// Wrap a generator in the state of `RandomState`'s, e.g. a clone from its outputs
+    pub fn from_rng(mt: MT19937Rng) -> RandomState {
+        RandomState { mt, gauss: None }
+    }
+
+    pub fn into_inner(self) -> MT19937Rng {
+        self.mt
+    }
+
+    /// Same as `random_sample()` (or `rand()`), uniform in [0, 1) with 53 bits
+    pub fn random_sample(&mut self) -> f64 {
+        let a = self.mt.next_u32() >> 5;
+        let b = self.mt.next_u32() >> 6;
+        (a as f64 * 67108864.0 + b as f64) / 9007199254740992.0
+    }
+
+    /// Same as `randint(low, high)`, `high` excluded
+    pub fn randint(&mut self, low: i64, high: i64) -> i64 {
+        if low >= high {
+            panic!("Invalid range, low >= high");
+        }
+        let rng = high.wrapping_sub(low).wrapping_sub(1) as u64;
+        let mask = u64::MAX.checked_shr(rng.leading_zeros()).unwrap_or(0);
+        let value = if rng == 0 {
+            0
+        } else if rng <= u32::MAX as u64 {
+            loop {
+                let v = self.mt.next_u32() as u64 & mask;
+                if v <= rng {
+                    break v;
+                }
+            }
+        } else {
+            loop {
+                let v = self.mt.next_u64() & mask;
+                if v <= rng {
+                    break v;
+                }
+            }
+        };
+        low.wrapping_add(value as i64)
+    }
+
+    /// Same as `standard_normal()` (or `randn()`), by the polar method with the second value
+    /// kept for the next call
+    pub fn standard_normal(&mut self) -> f64 {
+        if let Some(gauss) = self.gauss.take() {
+            return gauss;
+        }
+        loop {
+            let x1 = 2.0 * self.random_sample() - 1.0;
+            let x2 = 2.0 * self.random_sample() - 1.0;
+            let r2 = x1 * x1 + x2 * x2;
+            if r2 < 1.0 && r2 != 0.0 {
+                let f = (-2.0 * r2.ln() / r2).sqrt();
+                self.gauss = Some(f * x1);
+                return f * x2;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numpy_reference_outputs() {
+        // np.random.seed(42); np.random.rand()
+        assert_eq!(RandomState::new(42).random_sample(), 0.3745401188473625);
+        // np.random.seed(42); np.random.randint(0, 100, 5)
+        let mut rng = RandomState::new(42);
+        let ints: Vec<i64> = (0..5).map(|_| rng.randint(0, 100)).collect();
+        assert_eq!(ints, vec![51, 92, 14, 71, 60]);
+        // np.random.seed(0); np.random.randn(3)
+        let mut rng = RandomState::new(0);
+        let normals: Vec<f64> = (0..3).map(|_| rng.standard_normal()).collect();
+        assert_eq!(
+            normals,
+            vec![1.764052345967664, 0.4001572083672233, 0.9787379841057392]
+        );
+    }
+
+    #[test]
+    fn randint_ranges() {
+        let mut rng = RandomState::new(1);
+        assert_eq!(rng.randint(5, 6), 5);
+        let value = rng.randint(-1_000_000_000_000, 1_000_000_000_000);
+        assert!((-1_000_000_000_000..1_000_000_000_000).contains(&value));
+        let value = rng.randint(i64::MIN, i64::MAX);
+        assert!(value < i64::MAX);
+    }
+}
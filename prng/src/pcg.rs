@@ -0,0 +1,145 @@
+// PCG32 (XSH RR 64/32): a 64-bit LCG whose output is a xorshift of the top bits, randomly rotated
+// by the top 5 bits.
+//
+// With the increment known, guessing the rotation of an output gives the top 37 bits of the state.
+// Two consecutive states then differ by a known multiplier up to their unknown 27 low bits, a 2D
+// closest vector problem. All 32 * 32 rotation pairs are tried and checked against the next outputs.
+use super::lattice;
+use super::mt19937::{impls, Error, RngCore};
+use num::{BigInt, ToPrimitive, Zero};
+
+const MULTIPLIER: u64 = 6364136223846793005;
+const LOW_BITS: u32 = 27;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pcg32 {
+    state: u64,
+    inc: u64,
+}
+
+impl Pcg32 {
+    /// Same as `pcg32_srandom_r(rng, init_state, init_seq)`
+    pub fn new(init_state: u64, init_seq: u64) -> Pcg32 {
+        let mut rng = Pcg32 {
+            state: 0,
+            inc: (init_seq << 1) | 1,
+        };
+        rng.next_u32();
+        rng.state = rng.state.wrapping_add(init_state);
+        rng.next_u32();
+        rng
+    }
+
+    /// The increment should be odd
+    pub fn from_state(state: u64, inc: u64) -> Pcg32 {
+        Pcg32 { state, inc: inc | 1 }
+    }
+
+    pub fn state(&self) -> u64 {
+        self.state
+    }
+
+    pub fn increment(&self) -> u64 {
+        self.inc
+    }
+
+    fn output(state: u64) -> u32 {
+        let xorshifted = (((state >> 18) ^ state) >> 27) as u32;
+        xorshifted.rotate_right((state >> 59) as u32)
+    }
+
+    // top 37 bits of the state given an output and its rotation
+    fn top_bits(output: u32, rotation: u32) -> u64 {
+        // x = bits 27..59 of state ^ (state >> 18), recovered from the top down
+        let x = output.rotate_left(rotation) as u64;
+        let mut state = (rotation as u64) << 59;
+        for j in (LOW_BITS..59).rev() {
+            let above = if j + 18 < 64 { state >> (j + 18) & 1 } else { 0 };
+            state |= ((x >> (j - LOW_BITS) & 1) ^ above) << j;
+        }
+        state
+    }
+
+    /// Clone from consecutive outputs (at least 3) given the increment, e.g. the default one of
+    /// the reference implementation. Continues after the last output.
+    pub fn recover(outputs: &[u32], inc: u64) -> Option<Pcg32> {
+        if outputs.len() < 3 {
+            return None;
+        }
+        let inc = inc | 1;
+        // (z0, a * z0 + k * 2^64) with s0 = h0 + z0 and s1 = h1 + z1, such that
+        // z1 = a * z0 - (h1 - a * h0 - inc) (mod 2^64)
+        let basis = lattice::lll(&[
+            vec![BigInt::from(1), BigInt::from(MULTIPLIER)],
+            vec![BigInt::zero(), BigInt::from(1u128 << 64)],
+        ]);
+        let half = BigInt::from(1u64 << (LOW_BITS - 1));
+
+        for r0 in 0..32 {
+            let h0 = Self::top_bits(outputs[0], r0);
+            for r1 in 0..32 {
+                let h1 = Self::top_bits(outputs[1], r1);
+                let t = h1.wrapping_sub(MULTIPLIER.wrapping_mul(h0)).wrapping_sub(inc);
+                // (z0, z1 + t) is in the lattice, aim at the middle of the possible z0 and z1
+                let target = vec![half.clone(), &half + t];
+                let closest = lattice::closest_vector(&basis, &target);
+                let z0 = match closest[0].to_u64() {
+                    Some(z0) if z0 < 1 << LOW_BITS => z0,
+                    _ => continue,
+                };
+                let mut rng = Pcg32::from_state(h0 | z0, inc);
+                if outputs.iter().all(|y| rng.next_u32() == *y) {
+                    return Some(rng);
+                }
+            }
+        }
+        None
+    }
+}
+
+impl RngCore for Pcg32 {
+    fn next_u32(&mut self) -> u32 {
+        let old = self.state;
+        self.state = old.wrapping_mul(MULTIPLIER).wrapping_add(self.inc);
+        Self::output(old)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        impls::next_u64_via_u32(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        impls::fill_bytes_via_next(self, dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pcg32_reference_outputs() {
+        // pcg32-demo
+        let mut rng = Pcg32::new(42, 54);
+        let outputs: Vec<u32> = (0..6).map(|_| rng.next_u32()).collect();
+        assert_eq!(
+            outputs,
+            vec![0xa15c02b7, 0x7b47f409, 0xba1d3330, 0x83d2f293, 0xbfa4784b, 0xcbed606e]
+        );
+    }
+
+    #[test]
+    fn recover_pcg32_state() {
+        let mut rng = Pcg32::new(0xdead_beef, 54);
+        let outputs: Vec<u32> = (0..4).map(|_| rng.next_u32()).collect();
+        let mut clone = Pcg32::recover(&outputs, rng.increment()).unwrap();
+        for _ in 0..100 {
+            assert_eq!(rng.next_u32(), clone.next_u32());
+        }
+    }
+}
@@ -0,0 +1,146 @@
+// CPython's `random` module on top of MT19937.
+//
+// Integer seeds go through `init_by_array` with their 32-bit words, least significant first.
+// `random()` takes 53 bits out of two outputs, `getrandbits(k)` fills words from the least
+// significant one and the last output is truncated from the top; everything else (`randrange`,
+// `randint`, `shuffle`...) rejects `getrandbits(n.bit_length())` until below n.
+use super::mt19937::{MT19937Rng, RngCore};
+use num::{BigUint, ToPrimitive};
+
+#[derive(Debug)]
+pub struct PythonRandom {
+    mt: MT19937Rng,
+}
+
+impl PythonRandom {
+    /// Same as `random.seed(seed)` for an int seed (negative ones use their absolute value)
+    pub fn new(seed: &BigUint) -> PythonRandom {
+        let mut key = seed.to_u32_digits();
+        if key.is_empty() {
+            key.push(0);
+        }
+        PythonRandom {
+            mt: MT19937Rng::new_by_array(&key),
+        }
+    }
+
+    /// Wrap a generator in the state of `random`'s, e.g. a clone from its outputs
+    pub fn from_rng(mt: MT19937Rng) -> PythonRandom {
+        PythonRandom { mt }
+    }
+
+    pub fn into_inner(self) -> MT19937Rng {
+        self.mt
+    }
+
+    /// Same as `random.random()`
+    pub fn random(&mut self) -> f64 {
+        let a = self.mt.next_u32() >> 5;
+        let b = self.mt.next_u32() >> 6;
+        (a as f64 * 67108864.0 + b as f64) / 9007199254740992.0
+    }
+
+    /// Same as `random.getrandbits(k)`
+    pub fn getrandbits(&mut self, k: u32) -> BigUint {
+        let words: Vec<u32> = (0..k.div_ceil(32))
+            .map(|i| {
+                let r = self.mt.next_u32();
+                match k - 32 * i {
+                    bits if bits < 32 => r >> (32 - bits),
+                    _ => r,
+                }
+            })
+            .collect();
+        BigUint::new(words)
+    }
+
+    // `_randbelow_with_getrandbits`, up to 2^64 for the full i64 range
+    fn randbelow(&mut self, n: u128) -> u128 {
+        if n == 0 {
+            panic!("Empty range");
+        }
+        let k = 128 - n.leading_zeros();
+        loop {
+            let r = self.getrandbits(k).to_u128().unwrap();
+            if r < n {
+                return r;
+            }
+        }
+    }
+
+    // `start + randbelow(width)`, computed without overflow
+    fn randspan(&mut self, start: i64, width: i128) -> i64 {
+        (start as i128 + self.randbelow(width as u128) as i128) as i64
+    }
+
+    /// Same as `random.randrange(start, stop)`
+    pub fn randrange(&mut self, start: i64, stop: i64) -> i64 {
+        if start >= stop {
+            panic!("Empty range for randrange({}, {})", start, stop);
+        }
+        self.randspan(start, stop as i128 - start as i128)
+    }
+
+    /// Same as `random.randint(a, b)`, both ends included
+    pub fn randint(&mut self, a: i64, b: i64) -> i64 {
+        if a > b {
+            panic!("Empty range for randint({}, {})", a, b);
+        }
+        self.randspan(a, b as i128 - a as i128 + 1)
+    }
+
+    /// Same as `random.shuffle(x)`
+    pub fn shuffle<T>(&mut self, x: &mut [T]) {
+        for i in (1..x.len()).rev() {
+            let j = self.randbelow(i as u128 + 1) as usize;
+            x.swap(i, j);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clone;
+
+    #[test]
+    fn python_reference_outputs() {
+        // random.seed(42); random.random(), random.getrandbits(32), random.getrandbits(70),
+        // random.randrange(10, 1000), random.randint(1, 6)
+        let mut rng = PythonRandom::new(&BigUint::from(42u32));
+        assert_eq!(rng.random(), 0.6394267984578837);
+        assert_eq!(rng.getrandbits(32), BigUint::from(107420369u32));
+        assert_eq!(rng.getrandbits(70), BigUint::from(281774556622676705531u128));
+        assert_eq!(rng.randrange(10, 1000), 238);
+        assert_eq!(rng.randint(1, 6), 2);
+
+        // random.seed(42); random.randint(-2**63, 2**63 - 1), random.randint(5, 2**63 - 1),
+        // random.randrange(-2**63, 2**63 - 1)
+        let mut rng = PythonRandom::new(&BigUint::from(42u32));
+        assert_eq!(rng.randint(i64::MIN, i64::MAX), -7169676182496904803);
+        assert_eq!(rng.randint(5, i64::MAX), 2536697757961699584);
+        assert_eq!(rng.randrange(i64::MIN, i64::MAX), -5105860564996768880);
+
+        // random.seed(2**80 + 12345); random.random()
+        let seed = (BigUint::from(1u32) << 80) + BigUint::from(12345u32);
+        assert_eq!(PythonRandom::new(&seed).random(), 0.7304434512634141);
+
+        // random.seed(7); x = list(range(10)); random.shuffle(x)
+        let mut x: Vec<u32> = (0..10).collect();
+        PythonRandom::new(&BigUint::from(7u32)).shuffle(&mut x);
+        assert_eq!(x, vec![8, 3, 1, 4, 7, 0, 9, 6, 2, 5]);
+    }
+
+    #[test]
+    fn predict_python_random() {
+        let mut rng = PythonRandom::new(&BigUint::from(1337u32));
+        let outputs: Vec<u32> = (0..624)
+            .map(|_| rng.getrandbits(32).iter_u32_digits().next().unwrap_or(0))
+            .collect();
+        let mut clone = PythonRandom::from_rng(clone::clone_from_outputs(&outputs));
+        for _ in 0..10 {
+            assert_eq!(rng.random(), clone.random());
+            assert_eq!(rng.randint(1, 100), clone.randint(1, 100));
+        }
+    }
+}
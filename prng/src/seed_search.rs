@@ -0,0 +1,209 @@
+// Brute-forcing the seed of a PRNG, e.g. seeded with a timestamp or a 16-bit value.
+//
+// Any generator works as long as it can be built from a seed: the constructor maps a candidate
+// seed to a fresh generator, and the predicate tells whether its outputs match the observation.
+use super::mt19937::RngCore;
+use std::marker::PhantomData;
+use std::ops::RangeInclusive;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+// number of seeds a thread takes at once, progress is reported after each chunk
+const CHUNK: u64 = 1 << 12;
+
+pub struct SeedSearch<R, C, P>
+where
+    R: RngCore,
+    C: Fn(u64) -> R + Sync,
+    P: Fn(&mut R) -> bool + Sync,
+{
+    constructor: C,
+    predicate: P,
+    threads: usize,
+    first_match: bool,
+    progress: Option<Box<dyn Fn(u64, u64) + Sync>>,
+    _rng: PhantomData<fn() -> R>,
+}
+
+impl<R, C, P> SeedSearch<R, C, P>
+where
+    R: RngCore,
+    C: Fn(u64) -> R + Sync,
+    P: Fn(&mut R) -> bool + Sync,
+{
+    /// e.g. `SeedSearch::new(|seed| MT19937Rng::new(seed as u32), |rng| rng.next_u32() == output)`
+    pub fn new(constructor: C, predicate: P) -> SeedSearch<R, C, P> {
+        SeedSearch {
+            constructor,
+            predicate,
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+            first_match: false,
+            progress: None,
+            _rng: PhantomData,
+        }
+    }
+
+    pub fn threads(mut self, threads: usize) -> SeedSearch<R, C, P> {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Stop as soon as a seed matches instead of returning all of them
+    pub fn first_match(mut self) -> SeedSearch<R, C, P> {
+        self.first_match = true;
+        self
+    }
+
+    /// Callback with the number of seeds searched so far and the total, called from the workers
+    pub fn progress<F>(mut self, progress: F) -> SeedSearch<R, C, P>
+    where
+        F: Fn(u64, u64) + Sync + 'static,
+    {
+        self.progress = Some(Box::new(progress));
+        self
+    }
+
+    /// Search a timestamp-seeded generator, the seed being the UNIX time in seconds within
+    /// [time - before, time + after]
+    pub fn time_window(&self, time: SystemTime, before: Duration, after: Duration) -> Vec<u64> {
+        let now = unix_time(time);
+        self.range(now.saturating_sub(before.as_secs())..=now.saturating_add(after.as_secs()))
+    }
+
+    /// All the matching seeds of `seeds` in increasing order (only one with `first_match`)
+    pub fn range(&self, seeds: RangeInclusive<u64>) -> Vec<u64> {
+        let (start, end) = seeds.into_inner();
+        if start > end {
+            return vec![];
+        }
+        // the number of seeds, minus one to avoid overflowing on the full u64 range
+        let last = end - start;
+        // workers claim chunk indices rather than offsets: there are at most 2^64 / CHUNK of them,
+        // so the counter can't wrap around and restart the search
+        let next = AtomicU64::new(0);
+        let searched = AtomicU64::new(0);
+        let found = AtomicBool::new(false);
+        let matches: Mutex<Vec<u64>> = Mutex::new(vec![]);
+
+        thread::scope(|s| {
+            for _ in 0..self.threads {
+                let (next, searched, found, matches) = (&next, &searched, &found, &matches);
+                s.spawn(move || loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    if index > last / CHUNK || found.load(Ordering::Relaxed) {
+                        return;
+                    }
+                    let chunk = index * CHUNK;
+                    let chunk_end = chunk.saturating_add(CHUNK - 1).min(last);
+                    for offset in chunk..=chunk_end {
+                        let seed = start + offset;
+                        if (self.predicate)(&mut (self.constructor)(seed)) {
+                            matches.lock().unwrap().push(seed);
+                            if self.first_match {
+                                found.store(true, Ordering::Relaxed);
+                                return;
+                            }
+                        }
+                    }
+                    let done = searched.fetch_add(chunk_end - chunk + 1, Ordering::Relaxed);
+                    if let Some(progress) = &self.progress {
+                        progress(done + chunk_end - chunk + 1, last.saturating_add(1));
+                    }
+                });
+            }
+        });
+
+        let mut matches = matches.into_inner().unwrap();
+        matches.sort_unstable();
+        if self.first_match {
+            matches.truncate(1);
+        }
+        matches
+    }
+}
+
+/// Seconds since the UNIX epoch, the usual timestamp seed
+pub fn unix_time(time: SystemTime) -> u64 {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .expect("Time before the UNIX epoch")
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mt19937, mt19937_64};
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::Arc;
+
+    #[test]
+    fn crack_timestamp_seed() {
+        let now = SystemTime::now();
+        let seed = unix_time(now) - 789;
+        let output = mt19937::MT19937Rng::new(seed as u32).next_u32();
+        let search = SeedSearch::new(
+            |seed| mt19937::MT19937Rng::new(seed as u32),
+            |rng| rng.next_u32() == output,
+        );
+        let hour = Duration::from_secs(3600);
+        assert_eq!(search.time_window(now, hour, hour), vec![seed]);
+
+        let output = mt19937_64::MT19937Rng::new(seed as usize).next_u64();
+        let search = SeedSearch::new(
+            |seed| mt19937_64::MT19937Rng::new(seed as usize),
+            |rng| rng.next_u64() == output,
+        )
+        .threads(3);
+        assert_eq!(search.time_window(now, hour, Duration::default()), vec![seed]);
+    }
+
+    #[test]
+    fn crack_16bit_seed_keystream() {
+        // only the last bytes of the keystream are known
+        let mut key_stream = vec![0; 40];
+        mt19937::MT19937Rng::new(0xbeef).fill_bytes(&mut key_stream);
+        let calls = Arc::new(AtomicUsize::new(0));
+        let progress_calls = calls.clone();
+        let search = SeedSearch::new(
+            |seed| mt19937::MT19937Rng::new(seed as u32),
+            |rng| {
+                let mut candidate = vec![0; 40];
+                rng.fill_bytes(&mut candidate);
+                candidate[26..] == key_stream[26..]
+            },
+        )
+        .progress(move |searched, total| {
+            assert!(searched <= total);
+            progress_calls.fetch_add(1, Ordering::Relaxed);
+        });
+        assert_eq!(search.range(0..=0xffff), vec![0xbeef]);
+        assert_eq!(calls.load(Ordering::Relaxed), 16);
+
+        let search = search.first_match();
+        assert_eq!(search.range(0xb000..=0xffff), vec![0xbeef]);
+        assert!(search.range(0xc000..=0xffff).is_empty());
+    }
+
+    #[test]
+    fn range_up_to_u64_max() {
+        let seed = u64::MAX - 5;
+        let output = mt19937_64::MT19937Rng::new(seed as usize).next_u64();
+        let search = SeedSearch::new(
+            |seed| mt19937_64::MT19937Rng::new(seed as usize),
+            |rng| rng.next_u64() == output,
+        );
+        assert_eq!(search.range(u64::MAX - 9000..=u64::MAX), vec![seed]);
+        assert!(search.range(u64::MAX - 4..=u64::MAX).is_empty());
+
+        // the full range stops at the first match
+        let output = mt19937_64::MT19937Rng::new(0x1234).next_u64();
+        let search = SeedSearch::new(
+            |seed| mt19937_64::MT19937Rng::new(seed as usize),
+            |rng| rng.next_u64() == output,
+        )
+        .first_match();
+        assert_eq!(search.range(0..=u64::MAX), vec![0x1234]);
+    }
+}
@@ -0,0 +1,138 @@
+// Keystream cipher out of any PRNG, e.g. the MT19937 cipher of challenge 24.
+//
+// Implementations differ in how they turn outputs into key bytes: `fill_bytes`, one byte per
+// output (`rand() & 0xff`), or whole 16, 32 or 64-bit outputs in either byte order.
+use super::mt19937::RngCore;
+
+/// How many bits of each output go into the keystream
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Width {
+    /// Whatever the generator's `fill_bytes` does
+    Bytes,
+    /// Low 8 bits of `next_u32()`
+    W8,
+    /// Low 16 bits of `next_u32()`
+    W16,
+    W32,
+    W64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ByteOrder {
+    Little,
+    Big,
+}
+
+#[derive(Debug)]
+pub struct StreamCipher<R: RngCore> {
+    rng: R,
+    width: Width,
+    order: ByteOrder,
+    // key bytes of the last output not used yet
+    pending: Vec<u8>,
+}
+
+impl<R: RngCore> StreamCipher<R> {
+    /// Keystream from `fill_bytes`, which starts afresh at each call of `keystream` or `apply`
+    pub fn new(rng: R) -> StreamCipher<R> {
+        StreamCipher {
+            rng,
+            width: Width::Bytes,
+            order: ByteOrder::Little,
+            pending: vec![],
+        }
+    }
+
+    pub fn width(mut self, width: Width) -> StreamCipher<R> {
+        self.width = width;
+        self
+    }
+
+    pub fn byte_order(mut self, order: ByteOrder) -> StreamCipher<R> {
+        self.order = order;
+        self
+    }
+
+    pub fn into_inner(self) -> R {
+        self.rng
+    }
+
+    // key bytes of the next output
+    fn next_block(&mut self) -> Vec<u8> {
+        let (value, len) = match self.width {
+            Width::Bytes => unreachable!(),
+            Width::W8 => (self.rng.next_u32() as u64, 1),
+            Width::W16 => (self.rng.next_u32() as u64, 2),
+            Width::W32 => (self.rng.next_u32() as u64, 4),
+            Width::W64 => (self.rng.next_u64(), 8),
+        };
+        let bytes = value.to_le_bytes()[..len].to_vec();
+        match self.order {
+            ByteOrder::Little => bytes,
+            ByteOrder::Big => bytes.into_iter().rev().collect(),
+        }
+    }
+
+    /// The next `len` bytes of keystream
+    pub fn keystream(&mut self, len: usize) -> Vec<u8> {
+        let mut key = vec![0; len];
+        if self.width == Width::Bytes {
+            self.rng.fill_bytes(&mut key);
+            return key;
+        }
+        for byte in key.iter_mut() {
+            if self.pending.is_empty() {
+                self.pending = self.next_block();
+                self.pending.reverse();
+            }
+            *byte = self.pending.pop().unwrap();
+        }
+        key
+    }
+
+    /// XOR `data` with the keystream, to encrypt as well as to decrypt
+    pub fn apply(&mut self, data: &[u8]) -> Vec<u8> {
+        let key = self.keystream(data.len());
+        data.iter().zip(key.iter()).map(|(d, k)| d ^ k).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mt19937::MT19937Rng;
+
+    #[test]
+    fn stream_cipher_roundtrip() {
+        let pt = b"hello, Rustacean!".to_vec();
+        for width in [Width::Bytes, Width::W8, Width::W16, Width::W32, Width::W64].iter() {
+            let ct = StreamCipher::new(MT19937Rng::new(1234)).width(*width).apply(&pt);
+            assert_ne!(ct, pt);
+            let mut cipher = StreamCipher::new(MT19937Rng::new(1234)).width(*width);
+            if *width == Width::Bytes {
+                assert_eq!(cipher.apply(&ct), pt);
+                continue;
+            }
+            // the keystream continues across calls
+            let mut decrypted = cipher.apply(&ct[..5]);
+            decrypted.extend(cipher.apply(&ct[5..]));
+            assert_eq!(decrypted, pt);
+        }
+    }
+
+    #[test]
+    fn keystream_layout() {
+        let mut rng = MT19937Rng::new(5489);
+        let (first, second) = (rng.next_u32(), rng.next_u32());
+
+        let mut cipher = StreamCipher::new(MT19937Rng::new(5489))
+            .width(Width::W32)
+            .byte_order(ByteOrder::Big);
+        let mut expected = first.to_be_bytes().to_vec();
+        expected.extend_from_slice(&second.to_be_bytes()[..2]);
+        assert_eq!(cipher.keystream(6), expected);
+
+        let mut cipher = StreamCipher::new(MT19937Rng::new(5489)).width(Width::W8);
+        assert_eq!(cipher.keystream(2), vec![first as u8, second as u8]);
+    }
+}
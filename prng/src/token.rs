@@ -0,0 +1,235 @@
+// Tokens (session ids, reset tokens, UUIDs...) drawn from a non-cryptographic PRNG.
+//
+// Each format consumes `next_u32()` outputs the way common helpers do, so a token reveals known
+// bits of those outputs: enough tokens give the seed (by brute force) or the whole MT19937 state.
+use super::clone::{self, Observation};
+use super::mt19937::{MT19937Rng, RngCore};
+use super::seed_search::SeedSearch;
+use std::ops::RangeInclusive;
+
+const ALPHANUMERIC: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenFormat {
+    /// That many hex digits, 8 per output from the most significant one
+    Hex(usize),
+    /// That many characters, `ALPHANUMERIC[next_u32() % 62]` each
+    Alphanumeric(usize),
+    /// Random UUID out of 4 outputs, version and variant bits overwritten
+    Uuid4,
+}
+
+impl TokenFormat {
+    pub fn generate<R: RngCore>(&self, rng: &mut R) -> String {
+        match self {
+            TokenFormat::Hex(len) => {
+                let mut token = String::new();
+                while token.len() < *len {
+                    token.push_str(&format!("{:08x}", rng.next_u32()));
+                }
+                token.truncate(*len);
+                token
+            }
+            TokenFormat::Alphanumeric(len) => (0..*len)
+                .map(|_| ALPHANUMERIC[rng.next_u32() as usize % ALPHANUMERIC.len()] as char)
+                .collect(),
+            TokenFormat::Uuid4 => {
+                let hex = TokenFormat::Hex(32).generate(rng);
+                let variant = u8::from_str_radix(&hex[16..17], 16).unwrap() & 0x3 | 0x8;
+                format!(
+                    "{}-{}-4{}-{:x}{}-{}",
+                    &hex[..8],
+                    &hex[8..12],
+                    &hex[13..16],
+                    variant,
+                    &hex[17..20],
+                    &hex[20..]
+                )
+            }
+        }
+    }
+
+    /// Number of outputs consumed by one token
+    pub fn outputs(&self) -> usize {
+        match self {
+            TokenFormat::Hex(len) => len.div_ceil(8),
+            TokenFormat::Alphanumeric(len) => *len,
+            TokenFormat::Uuid4 => 4,
+        }
+    }
+
+    /// The known bits `(mask, value)` of each output behind `token`, `None` if the token doesn't
+    /// have this format
+    pub fn leaks(&self, token: &str) -> Option<Vec<(u32, u32)>> {
+        match self {
+            TokenFormat::Hex(len) => {
+                if token.len() != *len || !token.bytes().all(|c| c.is_ascii_hexdigit()) {
+                    return None;
+                }
+                let leaks = token
+                    .as_bytes()
+                    .chunks(8)
+                    .map(|chunk| {
+                        let digits = std::str::from_utf8(chunk).unwrap();
+                        let shift = 32 - 4 * chunk.len() as u32;
+                        let value = (u64::from_str_radix(digits, 16).unwrap() << shift) as u32;
+                        ((u64::from(u32::MAX) >> shift << shift) as u32, value)
+                    })
+                    .collect();
+                Some(leaks)
+            }
+            // 62 is even, so the index gives away the lowest bit of the output
+            TokenFormat::Alphanumeric(len) => {
+                if token.len() != *len {
+                    return None;
+                }
+                token
+                    .bytes()
+                    .map(|c| {
+                        let index = ALPHANUMERIC.iter().position(|a| *a == c)?;
+                        Some((1, index as u32 & 1))
+                    })
+                    .collect()
+            }
+            TokenFormat::Uuid4 => {
+                let bytes = token.as_bytes();
+                let dashes = [8, 13, 18, 23];
+                if token.len() != 36
+                    || !dashes.iter().all(|i| bytes[*i] == b'-')
+                    || bytes[14] != b'4'
+                    || !b"89ab".contains(&bytes[19])
+                {
+                    return None;
+                }
+                let hex: String = token.chars().filter(|c| *c != '-').collect();
+                let mut leaks = TokenFormat::Hex(32).leaks(&hex)?;
+                leaks[1].0 &= !0xf000;
+                leaks[2].0 &= !0xc000_0000;
+                for leak in leaks.iter_mut() {
+                    leak.1 &= leak.0;
+                }
+                Some(leaks)
+            }
+        }
+    }
+}
+
+/// Seeds in `seeds` whose generator starts with `tokens` (e.g. a timestamp range), usually
+/// one if the tokens are PRNG-derived and none otherwise
+pub fn find_seed<R, C>(
+    format: TokenFormat,
+    tokens: &[&str],
+    constructor: C,
+    seeds: RangeInclusive<u64>,
+) -> Vec<u64>
+where
+    R: RngCore,
+    C: Fn(u64) -> R + Sync,
+{
+    if tokens.iter().any(|t| format.leaks(t).is_none()) {
+        return vec![];
+    }
+    SeedSearch::new(constructor, |rng| {
+        tokens.iter().all(|t| format.generate(rng) == *t)
+    })
+    .range(seeds)
+}
+
+/// Clone the MT19937 behind consecutive `tokens`, continuing after the last one. `None` if they
+/// can't come from one MT19937 stream, or don't leak enough to tell: the tokens must cover at least
+/// 624 outputs (the state), fully leaked, i.e. 156 32-digit hex tokens of 4 outputs each. Partially
+/// leaked outputs need more, e.g. about 270 UUIDs, since their 6 fixed bits are only made up for by
+/// the words of the next twist.
+pub fn recover_mt19937(format: TokenFormat, tokens: &[&str]) -> Option<MT19937Rng> {
+    let mut observations = vec![];
+    for token in tokens.iter() {
+        for (mask, value) in format.leaks(token)? {
+            let index = observations.len();
+            observations.push(Observation { index, value, mask });
+        }
+    }
+    clone::clone_from_observations(&observations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pcg::Pcg32;
+
+    #[test]
+    fn token_formats() {
+        let mut rng = MT19937Rng::new(5489);
+        let (first, second) = (rng.next_u32(), rng.next_u32());
+        let mut rng = MT19937Rng::new(5489);
+        let token = TokenFormat::Hex(12).generate(&mut rng);
+        assert_eq!(token, format!("{:08x}{:04x}", first, second >> 16));
+        assert_eq!(
+            TokenFormat::Hex(12).leaks(&token).unwrap(),
+            vec![(!0, first), (0xffff_0000, second & 0xffff_0000)]
+        );
+
+        let uuid = TokenFormat::Uuid4.generate(&mut rng);
+        assert_eq!(uuid.len(), 36);
+        assert_eq!(&uuid[14..15], "4");
+        assert!(TokenFormat::Uuid4.leaks(&uuid).is_some());
+        assert!(TokenFormat::Uuid4.leaks(&uuid.replace('-', "_")).is_none());
+
+        let token = TokenFormat::Alphanumeric(20).generate(&mut rng);
+        assert!(token.bytes().all(|c| c.is_ascii_alphanumeric()));
+        assert_eq!(TokenFormat::Alphanumeric(20).leaks(&token).unwrap().len(), 20);
+    }
+
+    #[test]
+    fn find_token_seed() {
+        let format = TokenFormat::Alphanumeric(16);
+        let mut rng = MT19937Rng::new(31337);
+        let token = format.generate(&mut rng);
+        let seeds = find_seed(
+            format,
+            &[&token],
+            |seed| MT19937Rng::new(seed as u32),
+            30000..=40000,
+        );
+        assert_eq!(seeds, vec![31337]);
+
+        let mut rng = Pcg32::new(31337, 54);
+        let token = format.generate(&mut rng);
+        let seeds = find_seed(
+            format,
+            &[&token],
+            |seed| MT19937Rng::new(seed as u32),
+            30000..=40000,
+        );
+        assert!(seeds.is_empty());
+    }
+
+    #[test]
+    fn recover_state_from_uuids() {
+        let mut rng = MT19937Rng::new(0xbad_5eed);
+        let uuids: Vec<String> = (0..300).map(|_| TokenFormat::Uuid4.generate(&mut rng)).collect();
+        let uuids: Vec<&str> = uuids.iter().map(|u| u.as_str()).collect();
+        let mut clone = recover_mt19937(TokenFormat::Uuid4, &uuids).unwrap();
+        assert_eq!(
+            TokenFormat::Uuid4.generate(&mut rng),
+            TokenFormat::Uuid4.generate(&mut clone)
+        );
+
+        let mut rng = Pcg32::new(0xbad_5eed, 54);
+        let uuids: Vec<String> = (0..300).map(|_| TokenFormat::Uuid4.generate(&mut rng)).collect();
+        let uuids: Vec<&str> = uuids.iter().map(|u| u.as_str()).collect();
+        assert!(recover_mt19937(TokenFormat::Uuid4, &uuids).is_none());
+    }
+
+    #[test]
+    fn hex_tokens_needed() {
+        // a 32-digit hex token leaks 4 full outputs, the state is 624 of them
+        let mut rng = MT19937Rng::new(42);
+        let tokens: Vec<String> = (0..156)
+            .map(|_| TokenFormat::Hex(32).generate(&mut rng))
+            .collect();
+        let tokens: Vec<&str> = tokens.iter().map(|t| t.as_str()).collect();
+        assert!(recover_mt19937(TokenFormat::Hex(32), &tokens[..155]).is_none());
+        let mut clone = recover_mt19937(TokenFormat::Hex(32), &tokens).unwrap();
+        assert_eq!(rng.next_u32(), clone.next_u32());
+    }
+}
@@ -0,0 +1,195 @@
+// xorshift128+, behind V8's `Math.random()` among others.
+//
+// The state update is linear over GF(2), thus leaked state bits (e.g. the 52 bits of a
+// `Math.random()` double) are linear equations over the 128 state bits, no SMT solver needed.
+use super::gf2::{BitVec, Solver};
+use super::mt19937::{impls, Error, RngCore};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Xorshift128Plus {
+    state0: u64,
+    state1: u64,
+}
+
+// MurmurHash3 finalizer, used by V8 to expand the seed
+fn murmur3(mut h: u64) -> u64 {
+    h ^= h >> 33;
+    h = h.wrapping_mul(0xFF51_AFD7_ED55_8CCD);
+    h ^= h >> 33;
+    h = h.wrapping_mul(0xC4CE_B9FE_1A85_EC53);
+    h ^= h >> 33;
+    h
+}
+
+impl Xorshift128Plus {
+    /// Seeded as V8's `Math.random()` with `--random_seed`
+    pub fn new(seed: u64) -> Xorshift128Plus {
+        Xorshift128Plus {
+            state0: murmur3(seed),
+            state1: murmur3(!seed),
+        }
+    }
+
+    pub fn from_state(state0: u64, state1: u64) -> Xorshift128Plus {
+        if state0 == 0 && state1 == 0 {
+            panic!("Invalid state, should not be all zero");
+        }
+        Xorshift128Plus { state0, state1 }
+    }
+
+    pub fn state(&self) -> (u64, u64) {
+        (self.state0, self.state1)
+    }
+
+    fn step(&mut self) {
+        let mut s1 = self.state0;
+        let s0 = self.state1;
+        self.state0 = s0;
+        s1 ^= s1 << 23;
+        s1 ^= s1 >> 17;
+        s1 ^= s0;
+        s1 ^= s0 >> 26;
+        self.state1 = s1;
+    }
+
+    /// One value of V8's `Math.random()` in generation order. V8 generates them 64 at a time and
+    /// hands each batch out in reverse order.
+    pub fn math_random(&mut self) -> f64 {
+        self.step();
+        f64::from_bits((self.state0 >> 12) | 0x3FF0_0000_0000_0000) - 1.0
+    }
+
+    /// Clone from leaked bits of `state0` after each step: `leaks[i] = (mask, value)` after the
+    /// (i+1)-th step. Continues after the last one, `None` if the leaks don't determine the state.
+    pub fn recover(leaks: &[(u64, u64)]) -> Option<Xorshift128Plus> {
+        // symbolic words over the 128 unknown bits of the initial state
+        let mut state0: Vec<BitVec> = (0..64).map(|i| BitVec::unit(128, i)).collect();
+        let mut state1: Vec<BitVec> = (64..128).map(|i| BitVec::unit(128, i)).collect();
+        let mut solver = Solver::new(128);
+        for (mask, value) in leaks.iter() {
+            let mut s1 = state0;
+            let s0 = state1;
+            s1 = xor(&s1, &shift_left(&s1, 23));
+            s1 = xor(&s1, &shift_right(&s1, 17));
+            s1 = xor(&s1, &s0);
+            s1 = xor(&s1, &shift_right(&s0, 26));
+            state0 = s0;
+            state1 = s1;
+            for i in (0..64).filter(|i| mask >> i & 1 == 1) {
+                solver.add(state0[i].clone(), value >> i & 1 == 1);
+            }
+        }
+        if !solver.determines(0..128) {
+            return None;
+        }
+        let x = solver.solve()?;
+        let word = |offset: usize| (0..64).fold(0u64, |acc, i| acc | (x.get(offset + i) as u64) << i);
+        let mut rng = Xorshift128Plus::from_state(word(0), word(64));
+        for _ in leaks.iter() {
+            rng.step();
+        }
+        Some(rng)
+    }
+
+    /// Clone from consecutive `Math.random()` values in generation order (3 are enough)
+    pub fn recover_math_random(values: &[f64]) -> Option<Xorshift128Plus> {
+        let leaks: Vec<(u64, u64)> = values
+            .iter()
+            .map(|v| (!0xfff, ((v + 1.0).to_bits() & ((1 << 52) - 1)) << 12))
+            .collect();
+        Xorshift128Plus::recover(&leaks)
+    }
+}
+
+fn xor(a: &[BitVec], b: &[BitVec]) -> Vec<BitVec> {
+    a.iter()
+        .zip(b.iter())
+        .map(|(x, y)| {
+            let mut z = x.clone();
+            z.xor_assign(y);
+            z
+        })
+        .collect()
+}
+
+fn shift_left(a: &[BitVec], k: usize) -> Vec<BitVec> {
+    (0..64)
+        .map(|i| {
+            if i >= k {
+                a[i - k].clone()
+            } else {
+                BitVec::new(a[0].len())
+            }
+        })
+        .collect()
+}
+
+fn shift_right(a: &[BitVec], k: usize) -> Vec<BitVec> {
+    (0..64)
+        .map(|i| {
+            if i + k < 64 {
+                a[i + k].clone()
+            } else {
+                BitVec::new(a[0].len())
+            }
+        })
+        .collect()
+}
+
+impl RngCore for Xorshift128Plus {
+    fn next_u32(&mut self) -> u32 {
+        self.next_u64() as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.step();
+        self.state0.wrapping_add(self.state1)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        impls::fill_bytes_via_next(self, dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn v8_math_random() {
+        // node --random_seed=42 -e "console.log(Math.random(), Math.random(), Math.random())"
+        let mut rng = Xorshift128Plus::new(42);
+        let mut batch: Vec<f64> = (0..64).map(|_| rng.math_random()).collect();
+        batch.reverse();
+        assert_eq!(
+            batch[..3].to_vec(),
+            vec![0.7939112874678715, 0.5254990606499601, 0.3518347850388237]
+        );
+    }
+
+    #[test]
+    fn recover_from_math_random() {
+        let mut rng = Xorshift128Plus::new(1337);
+        let values: Vec<f64> = (0..4).map(|_| rng.math_random()).collect();
+        let mut clone = Xorshift128Plus::recover_math_random(&values).unwrap();
+        for _ in 0..100 {
+            assert_eq!(rng.math_random(), clone.math_random());
+        }
+        assert_eq!(rng.next_u64(), clone.next_u64());
+
+        // the top byte of each state is not enough from 8 steps
+        let mut rng = Xorshift128Plus::new(7);
+        let leaks: Vec<(u64, u64)> = (0..8)
+            .map(|_| {
+                rng.step();
+                (0xff << 56, rng.state0 & 0xff << 56)
+            })
+            .collect();
+        assert!(Xorshift128Plus::recover(&leaks).is_none());
+    }
+}
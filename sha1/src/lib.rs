@@ -66,17 +66,19 @@ extern crate std;
 
 #[cfg(feature = "asm")]
 extern crate sha1_asm;
+/// The SHA-1 compression function, processing one block into the state
 #[cfg(all(feature = "asm", not(feature = "asm-aarch64")))]
 #[inline(always)]
-fn compress(state: &mut [u32; 5], block: &GenericArray<u8, U64>) {
+pub fn compress(state: &mut [u32; 5], block: &GenericArray<u8, U64>) {
     let block: &[u8; 64] = unsafe { core::mem::transmute(block) };
     sha1_asm::compress(state, block);
 }
 #[cfg(feature = "asm-aarch64")]
 mod aarch64;
+/// The SHA-1 compression function, processing one block into the state
 #[cfg(feature = "asm-aarch64")]
 #[inline(always)]
-fn compress(state: &mut [u32; 5], block: &GenericArray<u8, U64>) {
+pub fn compress(state: &mut [u32; 5], block: &GenericArray<u8, U64>) {
     // TODO: Replace this platform-specific call with is_aarch64_feature_detected!("sha1") once
     // that macro is stabilised and https://github.com/rust-lang/rfcs/pull/2725 is implemented
     // to let us use it on no_std.
@@ -89,9 +91,9 @@ fn compress(state: &mut [u32; 5], block: &GenericArray<u8, U64>) {
 }
 
 #[cfg(any(not(feature = "asm"), feature = "asm-aarch64"))]
-mod utils;
+pub mod utils;
 #[cfg(not(feature = "asm"))]
-use utils::compress;
+pub use utils::compress;
 
 use block_buffer::byteorder::{ByteOrder, BE};
 use block_buffer::BlockBuffer;
@@ -160,12 +162,39 @@ impl Reset for Sha1 {
 }
 
 impl Sha1 {
-    /// similiar to `result` and `fixed_result` but without padding
-    /// NOTE: only for demo of extension attack
-    pub fn raw_result(self) -> GenericArray<u8, U20> {
-        let mut out = GenericArray::default();
-        BE::write_u32_into(&self.h, &mut out);
-        out
+    /// Resume a computation from the chaining value `h` after `len` bytes of input, e.g. from
+    /// a digest for a length extension. `len` must be a multiple of the block size (64).
+    ///
+    /// ```rust
+    /// # extern crate sha1;
+    /// use sha1::{Digest, Sha1};
+    ///
+    /// let mut h = Sha1::new();
+    /// h.input(&[0u8; 128][..]);
+    /// let mut resumed = Sha1::from_state(h.state(), 128);
+    /// h.input(b"suffix");
+    /// resumed.input(b"suffix");
+    /// assert_eq!(h.result(), resumed.result());
+    /// ```
+    pub fn from_state(h: [u32; STATE_LEN], len: u64) -> Sha1 {
+        if !len.is_multiple_of(64) {
+            panic!("Invalid length, should be a multiple of the block size");
+        }
+        Sha1 {
+            h,
+            len,
+            buffer: Default::default(),
+        }
+    }
+
+    /// The current chaining value, i.e. the state after the last complete block of input
+    pub fn state(&self) -> [u32; STATE_LEN] {
+        self.h
+    }
+
+    /// Number of bytes of input so far
+    pub fn input_len(&self) -> u64 {
+        self.len
     }
 }
 impl_opaque_debug!(Sha1);
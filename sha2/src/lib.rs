@@ -0,0 +1,221 @@
+//! An implementation of the [SHA-2][1] cryptographic hash algorithms.
+//!
+//! There are 6 standard algorithms specified in the SHA-2 standard:
+//!
+//! * `Sha224`, which is the 32-bit `Sha256` algorithm with the result truncated
+//!   to 224 bits.
+//! * `Sha256`, which is the 32-bit `Sha256` algorithm.
+//! * `Sha384`, which is the 64-bit `Sha512` algorithm with the result truncated
+//!   to 384 bits.
+//! * `Sha512`, which is the 64-bit `Sha512` algorithm.
+//! * `Sha512Trunc224`, which is the 64-bit `Sha512` algorithm with the result
+//!   truncated to 224 bits.
+//! * `Sha512Trunc256`, which is the 64-bit `Sha512` algorithm with the result
+//!   truncated to 256 bits.
+//!
+//! Algorithmically, there are only 2 core algorithms: `Sha256` and `Sha512`.
+//! All other algorithms are just applications of these with different initial
+//! hash values, and truncated to different digest bit lengths.
+//!
+//! # Usage
+//!
+//! ```rust
+//! # #[macro_use] extern crate hex_literal;
+//! # extern crate sha2;
+//! # fn main() {
+//! use sha2::{Sha256, Sha512, Digest};
+//!
+//! // create a Sha256 object
+//! let mut hasher = Sha256::new();
+//!
+//! // write input message
+//! hasher.input(b"hello world");
+//!
+//! // read hash digest and consume hasher
+//! let result = hasher.result();
+//!
+//! assert_eq!(result[..], hex!("
+//!     b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9
+//! ")[..]);
+//!
+//! // same for Sha512
+//! let mut hasher = Sha512::new();
+//! hasher.input(b"hello world");
+//! let result = hasher.result();
+//!
+//! assert_eq!(result[..], hex!("
+//!     309ecc489c12d6eb4cc40f50c902f2b4d0ed77ee511a7c7a9bcd3ca86d4cd86f
+//!     989dd35bc5ff499670da34255b45b0cfd830e81f605dcf7dc5542e93ae9cd76f
+//! ")[..]);
+//! # }
+//! ```
+//!
+//! Every hasher can be resumed from a chaining value and a block-aligned input length, and the
+//! compression functions `compress256` and `compress512` are public:
+//!
+//! ```rust
+//! # extern crate sha2;
+//! use sha2::{Digest, Sha256, Sha512};
+//!
+//! let mut h = Sha256::new();
+//! h.input(&[0u8; 128][..]);
+//! let mut resumed = Sha256::from_state(h.state(), h.input_len());
+//! h.input(b"suffix");
+//! resumed.input(b"suffix");
+//! assert_eq!(h.result(), resumed.result());
+//!
+//! let mut h = Sha512::new();
+//! h.input(&[0u8; 256][..]);
+//! let mut resumed = Sha512::from_state(h.state(), h.input_len());
+//! h.input(b"suffix");
+//! resumed.input(b"suffix");
+//! assert_eq!(h.result(), resumed.result());
+//! ```
+//!
+//! Also see [RustCrypto/hashes][2] readme.
+//!
+//! [1]: https://en.wikipedia.org/wiki/SHA-2
+//! [2]: https://github.com/RustCrypto/hashes
+
+#![no_std]
+#![doc(html_logo_url = "https://raw.githubusercontent.com/RustCrypto/meta/master/logo_small.png")]
+
+extern crate block_buffer;
+extern crate fake_simd as simd;
+#[macro_use]
+extern crate opaque_debug;
+#[macro_use]
+pub extern crate digest;
+#[cfg(feature = "std")]
+extern crate std;
+#[cfg(test)]
+#[macro_use]
+extern crate hex_literal;
+
+mod consts;
+mod sha256;
+pub mod sha256_utils;
+mod sha512;
+pub mod sha512_utils;
+
+pub use digest::Digest;
+pub use sha256::{Sha224, Sha256};
+pub use sha256_utils::compress256;
+pub use sha512::{Sha384, Sha512, Sha512Trunc224, Sha512Trunc256};
+pub use sha512_utils::compress512;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // digests of "", "abc" and 200 times 'a' (several blocks)
+    fn check<D: Digest>(expected: [&[u8]; 3]) {
+        assert_eq!(D::digest(b"")[..], expected[0][..]);
+        assert_eq!(D::digest(b"abc")[..], expected[1][..]);
+        assert_eq!(D::digest(&[b'a'; 200][..])[..], expected[2][..]);
+    }
+
+    #[test]
+    fn sha256_family() {
+        check::<Sha224>([
+            &hex!("d14a028c2a3a2bc9476102bb288234c415a2b01f828ea62ac5b3e42f"),
+            &hex!("23097d223405d8228642a477bda255b32aadbce4bda0b3f7e36c9da7"),
+            &hex!("2559984fd15e055f0d84c346483508242f02653ab7956401e551511c"),
+        ]);
+        check::<Sha256>([
+            &hex!("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"),
+            &hex!("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"),
+            &hex!("c2a908d98f5df987ade41b5fce213067efbcc21ef2240212a41e54b5e7c28ae5"),
+        ]);
+    }
+
+    #[test]
+    fn sha512_family() {
+        check::<Sha384>([
+            &hex!(
+                "38b060a751ac96384cd9327eb1b1e36a21fdb71114be07434c0cc7bf63f6e1da
+                 274edebfe76f65fbd51ad2f14898b95b"
+            ),
+            &hex!(
+                "cb00753f45a35e8bb5a03d699ac65007272c32ab0eded1631a8b605a43ff5bed
+                 8086072ba1e7cc2358baeca134c825a7"
+            ),
+            &hex!(
+                "0691b6e978614b67d60557b2a2cddd53406508522efa21c624dbbfa8ab6e726d
+                 5c586b489c7c09f24109a64c10211d48"
+            ),
+        ]);
+        check::<Sha512>([
+            &hex!(
+                "cf83e1357eefb8bdf1542850d66d8007d620e4050b5715dc83f4a921d36ce9ce
+                 47d0d13c5d85f2b0ff8318d2877eec2f63b931bd47417a81a538327af927da3e"
+            ),
+            &hex!(
+                "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a
+                 2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f"
+            ),
+            &hex!(
+                "4b11459c33f52a22ee8236782714c150a3b2c60994e9acee17fe68947a3e6789
+                 f31e7668394592da7bef827cddca88c4e6f86e4df7ed1ae6cba71f3e98faee9f"
+            ),
+        ]);
+        check::<Sha512Trunc224>([
+            &hex!("6ed0dd02806fa89e25de060c19d3ac86cabb87d6a0ddd05c333b84f4"),
+            &hex!("4634270f707b6a54daae7530460842e20e37ed265ceee9a43e8924aa"),
+            &hex!("230fd37b82564b0c3b19d2cf19f91b297a4aca0124b4b691ccee1870"),
+        ]);
+        check::<Sha512Trunc256>([
+            &hex!("c672b8d1ef56ed28ab87c3622c5114069bdd3ad7b8f9737498d0c01ecef0967a"),
+            &hex!("53048e2681941ef99b2e29b76b4c7dabe4c2d0c634fc6d46e0e2f13107e7af23"),
+            &hex!("19b1e37317d7fd3d7651f397005e31f154ef4912d1345743d2d5889aaca28996"),
+        ]);
+    }
+
+    #[test]
+    fn resume_from_state() {
+        let mut h = Sha224::new();
+        h.input(&[0u8; 100][..]);
+        assert_eq!(h.input_len(), 100);
+        let mut resumed = Sha224::from_state(h.state(), 64);
+        resumed.input(&[0u8; 36][..]);
+        h.input(b"suffix");
+        resumed.input(b"suffix");
+        assert_eq!(resumed.input_len(), 106);
+        assert_eq!(h.result(), resumed.result());
+
+        let mut h = Sha512Trunc256::new();
+        h.input(&[0u8; 256][..]);
+        assert_eq!(h.input_len(), 256);
+        let mut resumed = Sha512Trunc256::from_state(h.state(), 256);
+        h.input(b"suffix");
+        resumed.input(b"suffix");
+        assert_eq!(h.result(), resumed.result());
+
+        // resuming from a digest, as in a length extension
+        let digest = Sha256::digest(b"abc");
+        let mut words = [0u32; 8];
+        for (word, bytes) in words.iter_mut().zip(digest.chunks(4)) {
+            *word = u32::from(bytes[0]) << 24
+                | u32::from(bytes[1]) << 16
+                | u32::from(bytes[2]) << 8
+                | u32::from(bytes[3]);
+        }
+        let mut padded = [0u8; 64];
+        padded[..3].copy_from_slice(b"abc");
+        padded[3] = 0x80;
+        padded[63] = 24;
+        let mut h = Sha256::new();
+        h.input(&padded[..]);
+        assert_eq!(h.state(), words);
+        let mut resumed = Sha256::from_state(words, 64);
+        h.input(b"suffix");
+        resumed.input(b"suffix");
+        assert_eq!(h.result(), resumed.result());
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_state_needs_whole_blocks() {
+        Sha384::from_state([0; 8], 100);
+    }
+}
@@ -0,0 +1,355 @@
+//! An implementation of the [SHA-3][1] cryptographic hash algorithms and the SHAKE
+//! extendable-output functions, on top of the Keccak-f[1600] sponge.
+//!
+//! # Usage
+//!
+//! ```rust
+//! # #[macro_use] extern crate hex_literal;
+//! # extern crate sha3;
+//! # fn main() {
+//! use sha3::{Digest, Sha3_256, Shake128};
+//! use sha3::digest::{ExtendableOutput, Input, XofReader};
+//!
+//! let result = Sha3_256::digest(b"abc");
+//! assert_eq!(result[..], hex!("
+//!     3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532
+//! ")[..]);
+//!
+//! // SHAKE outputs as many bytes as asked for
+//! let mut xof = Shake128::default();
+//! xof.input(b"abc");
+//! let mut output = [0u8; 8];
+//! xof.xof_result().read(&mut output);
+//! assert_eq!(output, hex!("5881092dd818bf5c"));
+//! # }
+//! ```
+//!
+//! Unlike Merkle-Damgård hashes, the digest is only part of the final sponge state: the
+//! capacity is never output, so hashing cannot be resumed from a digest.
+//!
+//! [1]: https://en.wikipedia.org/wiki/SHA-3
+#![no_std]
+#![allow(non_camel_case_types)]
+#[macro_use]
+extern crate opaque_debug;
+#[macro_use]
+pub extern crate digest;
+extern crate block_buffer;
+#[cfg(feature = "std")]
+extern crate std;
+#[cfg(test)]
+#[macro_use]
+extern crate hex_literal;
+use digest::generic_array::typenum::{U104, U136, U144, U168, U28, U32, U48, U64, U72};
+use digest::generic_array::GenericArray;
+pub use digest::Digest;
+use digest::{BlockInput, ExtendableOutput, FixedOutput, Input, Reset};
+
+#[rustfmt::skip]
+const RC: [u64; 24] = [
+    0x0000000000000001, 0x0000000000008082, 0x800000000000808a, 0x8000000080008000,
+    0x000000000000808b, 0x0000000080000001, 0x8000000080008081, 0x8000000000008009,
+    0x000000000000008a, 0x0000000000000088, 0x0000000080008009, 0x000000008000000a,
+    0x000000008000808b, 0x800000000000008b, 0x8000000000008089, 0x8000000000008003,
+    0x8000000000008002, 0x8000000000000080, 0x000000000000800a, 0x800000008000000a,
+    0x8000000080008081, 0x8000000000008080, 0x0000000080000001, 0x8000000080008008,
+];
+
+// rho rotations and pi lane order, following the lane moved into position 1
+#[rustfmt::skip]
+const RHO: [u32; 24] = [
+    1, 3, 6, 10, 15, 21, 28, 36, 45, 55, 2, 14, 27, 41, 56, 8, 25, 43, 62, 18, 39, 61, 20, 44,
+];
+#[rustfmt::skip]
+const PI: [usize; 24] = [
+    10, 7, 11, 17, 18, 3, 5, 16, 8, 21, 24, 4, 15, 23, 19, 13, 12, 2, 20, 14, 22, 9, 6, 1,
+];
+
+/// The Keccak-f[1600] permutation, lanes indexed by `x + 5 * y`
+pub fn keccak_f1600(a: &mut [u64; 25]) {
+    for rc in RC.iter() {
+        // theta
+        let mut c = [0u64; 5];
+        for x in 0..5 {
+            c[x] = a[x] ^ a[x + 5] ^ a[x + 10] ^ a[x + 15] ^ a[x + 20];
+        }
+        for x in 0..5 {
+            let d = c[(x + 4) % 5] ^ c[(x + 1) % 5].rotate_left(1);
+            for y in 0..5 {
+                a[x + 5 * y] ^= d;
+            }
+        }
+
+        // rho and pi
+        let mut last = a[1];
+        for i in 0..24 {
+            let tmp = a[PI[i]];
+            a[PI[i]] = last.rotate_left(RHO[i]);
+            last = tmp;
+        }
+
+        // chi
+        for y in 0..5 {
+            let row = [a[5 * y], a[5 * y + 1], a[5 * y + 2], a[5 * y + 3], a[5 * y + 4]];
+            for x in 0..5 {
+                a[x + 5 * y] = row[x] ^ (!row[(x + 1) % 5] & row[(x + 2) % 5]);
+            }
+        }
+
+        // iota
+        a[0] ^= rc;
+    }
+}
+
+/// A Keccak-f[1600] sponge, absorbing and squeezing `rate` bytes per permutation
+#[derive(Clone, Copy)]
+pub struct Sponge {
+    state: [u64; 25],
+    rate: usize,
+    pos: usize,
+}
+
+impl Sponge {
+    pub fn new(rate: usize) -> Sponge {
+        Sponge::from_state([0; 25], rate)
+    }
+
+    /// A sponge about to absorb (or squeeze) the first byte of a block from `state`
+    pub fn from_state(state: [u64; 25], rate: usize) -> Sponge {
+        if rate == 0 || rate >= 200 || !rate.is_multiple_of(8) {
+            panic!("Invalid rate, should be a multiple of 8 below 200");
+        }
+        Sponge { state, rate, pos: 0 }
+    }
+
+    pub fn state(&self) -> [u64; 25] {
+        self.state
+    }
+
+    pub fn rate(&self) -> usize {
+        self.rate
+    }
+
+    fn xor_byte(&mut self, i: usize, byte: u8) {
+        self.state[i / 8] ^= (byte as u64) << (8 * (i % 8));
+    }
+
+    pub fn absorb(&mut self, data: &[u8]) {
+        for &byte in data {
+            let pos = self.pos;
+            self.xor_byte(pos, byte);
+            self.pos += 1;
+            if self.pos == self.rate {
+                keccak_f1600(&mut self.state);
+                self.pos = 0;
+            }
+        }
+    }
+
+    /// Finish absorbing: `domain` holds the domain separation bits followed by the first
+    /// padding bit (0x06 for SHA-3, 0x1f for SHAKE, 0x01 for the original Keccak)
+    pub fn pad(&mut self, domain: u8) {
+        let (pos, last) = (self.pos, self.rate - 1);
+        self.xor_byte(pos, domain);
+        self.xor_byte(last, 0x80);
+        keccak_f1600(&mut self.state);
+        self.pos = 0;
+    }
+
+    pub fn squeeze(&mut self, out: &mut [u8]) {
+        for byte in out.iter_mut() {
+            if self.pos == self.rate {
+                keccak_f1600(&mut self.state);
+                self.pos = 0;
+            }
+            *byte = (self.state[self.pos / 8] >> (8 * (self.pos % 8))) as u8;
+            self.pos += 1;
+        }
+    }
+}
+
+macro_rules! sha3_impl {
+    ($name:ident, $rate:ident, $output:ident, $domain:expr, $doc:expr) => {
+        #[doc = $doc]
+        #[derive(Clone)]
+        pub struct $name {
+            sponge: Sponge,
+        }
+
+        impl Default for $name {
+            fn default() -> Self {
+                $name {
+                    sponge: Sponge::new(<$rate as digest::generic_array::typenum::Unsigned>::USIZE),
+                }
+            }
+        }
+
+        impl BlockInput for $name {
+            type BlockSize = $rate;
+        }
+
+        impl Input for $name {
+            fn input<B: AsRef<[u8]>>(&mut self, input: B) {
+                self.sponge.absorb(input.as_ref());
+            }
+        }
+
+        impl FixedOutput for $name {
+            type OutputSize = $output;
+
+            fn fixed_result(mut self) -> GenericArray<u8, Self::OutputSize> {
+                self.sponge.pad($domain);
+                let mut out = GenericArray::default();
+                self.sponge.squeeze(&mut out);
+                out
+            }
+        }
+
+        impl Reset for $name {
+            fn reset(&mut self) {
+                *self = Self::default();
+            }
+        }
+
+        impl_opaque_debug!($name);
+        impl_write!($name);
+    };
+}
+
+macro_rules! shake_impl {
+    ($name:ident, $rate:ident, $doc:expr) => {
+        #[doc = $doc]
+        #[derive(Clone)]
+        pub struct $name {
+            sponge: Sponge,
+        }
+
+        impl Default for $name {
+            fn default() -> Self {
+                $name {
+                    sponge: Sponge::new(<$rate as digest::generic_array::typenum::Unsigned>::USIZE),
+                }
+            }
+        }
+
+        impl BlockInput for $name {
+            type BlockSize = $rate;
+        }
+
+        impl Input for $name {
+            fn input<B: AsRef<[u8]>>(&mut self, input: B) {
+                self.sponge.absorb(input.as_ref());
+            }
+        }
+
+        impl ExtendableOutput for $name {
+            type Reader = Sha3XofReader;
+
+            fn xof_result(mut self) -> Sha3XofReader {
+                self.sponge.pad(0x1f);
+                Sha3XofReader {
+                    sponge: self.sponge,
+                }
+            }
+        }
+
+        impl Reset for $name {
+            fn reset(&mut self) {
+                *self = Self::default();
+            }
+        }
+
+        impl_opaque_debug!($name);
+        impl_write!($name);
+    };
+}
+
+sha3_impl!(Sha3_224, U144, U28, 0x06, "The SHA3-224 hasher");
+sha3_impl!(Sha3_256, U136, U32, 0x06, "The SHA3-256 hasher");
+sha3_impl!(Sha3_384, U104, U48, 0x06, "The SHA3-384 hasher");
+sha3_impl!(Sha3_512, U72, U64, 0x06, "The SHA3-512 hasher");
+sha3_impl!(Keccak256, U136, U32, 0x01, "Keccak-256 with the original padding");
+
+shake_impl!(Shake128, U168, "The SHAKE128 extendable-output function");
+shake_impl!(Shake256, U136, "The SHAKE256 extendable-output function");
+
+/// Squeezes the output of a SHAKE function
+#[derive(Clone)]
+pub struct Sha3XofReader {
+    sponge: Sponge,
+}
+
+impl digest::XofReader for Sha3XofReader {
+    fn read(&mut self, buffer: &mut [u8]) {
+        self.sponge.squeeze(buffer);
+    }
+}
+
+impl_opaque_debug!(Sha3XofReader);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use digest::XofReader;
+
+    #[test]
+    fn sha3_reference_digests() {
+        let long = [b'a'; 200];
+        assert_eq!(
+            Sha3_224::digest(b"abc")[..],
+            hex!("e642824c3f8cf24ad09234ee7d3c766fc9a3a5168d0c94ad73b46fdf")[..]
+        );
+        assert_eq!(
+            Sha3_224::digest(&long)[..],
+            hex!("455e0ccfc6010738ed93a793dffd79aff36debbd1a7eb6621bd6c722")[..]
+        );
+        assert_eq!(
+            Sha3_256::digest(&long)[..],
+            hex!("cce34485baf2bf2aca99b94833892a4f52896d3d153f7b840cc4f9fe695f1387")[..]
+        );
+        assert_eq!(
+            Sha3_384::digest(b"abc")[..],
+            hex!(
+                "ec01498288516fc926459f58e2c6ad8df9b473cb0fc08c2596da7cf0e49be4b2
+                 98d88cea927ac7f539f1edf228376d25"
+            )[..]
+        );
+        assert_eq!(
+            Sha3_512::digest(&long)[..],
+            hex!(
+                "eae6c85c6904f11075de9f9d5e1064371d000510fa3d2d79d40cf9be34892fb0
+                 1859d0a0234e138bcb0ad5c84f6c0dca226a414b0c9a2897cb695f5185fe36ec"
+            )[..]
+        );
+        // Keccak-256 of the empty string, as used by Ethereum
+        assert_eq!(
+            Keccak256::digest(b"")[..],
+            hex!("c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470")[..]
+        );
+    }
+
+    #[test]
+    fn shake_reference_outputs() {
+        let mut output = [0u8; 64];
+        Shake256::default().xof_result().read(&mut output);
+        assert_eq!(
+            output[..],
+            hex!(
+                "46b9dd2b0ba88d13233b3feb743eeb243fcd52ea62b81b82b50c27646ed5762f
+                 d75dc4ddd8c0f200cb05019d67b592f6fc821c49479ab48640292eacb3b7c4be"
+            )[..]
+        );
+
+        // reads can be split, across several permutations
+        let mut xof = Shake128::default();
+        xof.input(&[b'a'; 500][..]);
+        let mut reader = xof.xof_result();
+        let mut output = [0u8; 300];
+        reader.read(&mut output[..100]);
+        reader.read(&mut output[100..]);
+        assert_eq!(
+            output[280..],
+            hex!("3b07f002bd56ae04f1bbb2d81cd0e2de0d088c6b")[..]
+        );
+    }
+}